@@ -26,7 +26,15 @@ pub async fn get_connection() -> &'static Connection {
 }
 
 pub async fn init() -> Connection {
-    Connection::connect(&SETTINGS.amqp.url, ConnectionProperties::default())
-        .await
-        .unwrap()
+    connect().await.unwrap()
+}
+
+/// Opens a new AMQP connection, independent from the static one.
+///
+/// Used by long running tasks which must survive a broker outage and reconnect.
+///
+/// # Returns
+/// The established connection or the error of the broker.
+pub async fn connect() -> Result<Connection, lapin::Error> {
+    Connection::connect(&SETTINGS.amqp.url, ConnectionProperties::default()).await
 }
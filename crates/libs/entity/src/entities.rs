@@ -1,5 +1,9 @@
+pub mod app_client;
+pub mod app_consent;
 pub mod app_staff;
 pub mod application;
 pub mod key;
+pub mod notification;
+pub mod outbox;
 pub mod prelude;
 pub mod user;
@@ -0,0 +1,82 @@
+use rand::Rng;
+use sea_orm::entity::{prelude::*, ActiveValue};
+use util_lib::crypto;
+use uuid::Uuid;
+
+use time::OffsetDateTime;
+
+use super::app_staff::AppStaffPermissions;
+
+/// OAuth2 client of an application, authenticated with its id and secret.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "app_client")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub name: String,
+    /// Hash of the secret, a new secret set on the active model is hashed on save.
+    pub secret: String,
+    pub permissions: Vec<AppStaffPermissions>,
+    pub created_by_user_id: Uuid,
+    pub secret_rotated_at: OffsetDateTime,
+    #[sea_orm(default_value = "false")]
+    pub is_deleted: bool,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+impl Model {
+    pub fn is_valid_secret(&self, secret: &str) -> bool {
+        crypto::verify(secret, self.secret.as_str()).unwrap_or(false)
+    }
+
+    pub fn gen_secret() -> String {
+        rand::rng()
+            .sample_iter(rand::distr::Alphanumeric)
+            .take(48)
+            .map(char::from)
+            .collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::application::Entity",
+        from = "Column::ApplicationId",
+        to = "super::application::Column::Id"
+    )]
+    Application,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::CreatedByUserId",
+        to = "super::user::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    CreatedByUser,
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let mut s = self;
+
+        let now = OffsetDateTime::now_utc();
+        if insert {
+            s.id = ActiveValue::set(Uuid::new_v4());
+        }
+        // Save the hash of a new secret
+        if insert || !s.secret.is_unchanged() {
+            s.secret =
+                ActiveValue::set(crypto::get_default_hasher().hash(s.secret.unwrap().as_str()));
+            s.secret_rotated_at = ActiveValue::set(now);
+        }
+        s.updated_at = ActiveValue::set(now);
+        Ok(s)
+    }
+}
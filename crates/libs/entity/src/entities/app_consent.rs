@@ -0,0 +1,53 @@
+use sea_orm::entity::{prelude::*, ActiveValue};
+use uuid::Uuid;
+
+use time::OffsetDateTime;
+
+/// Scopes a user granted to an application on the OAuth2 authorization.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "app_consent")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub user_id: Uuid,
+    pub scopes: Vec<String>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::application::Entity",
+        from = "Column::ApplicationId",
+        to = "super::application::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Application,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let mut s = self;
+
+        if insert {
+            s.id = ActiveValue::set(Uuid::new_v4());
+        }
+        s.updated_at = ActiveValue::set(OffsetDateTime::now_utc());
+        Ok(s)
+    }
+}
@@ -22,15 +22,32 @@ pub enum AppStaffPermissions {
     ReadKeyDetail,
     UpdateKey,
     DeleteKey,
+
+    CreateStaff,
+    ReadStaff,
+    UpdateStaff,
+    DeleteStaff,
+
+    CreateClient,
+    ReadClient,
+    UpdateClient,
+    DeleteClient,
 }
 
 impl AppStaffPermissions {
     pub fn get_all() -> Vec<Self> {
         let mut result = Self::get_all_application();
         result.append(&mut Self::get_all_key());
+        result.append(&mut Self::get_all_staff());
+        result.append(&mut Self::get_all_client());
         result
     }
 
+    /// Checks that the given permissions contain every existing permission.
+    pub fn is_all(permissions: &[Self]) -> bool {
+        Self::get_all().iter().all(|perm| permissions.contains(perm))
+    }
+
     pub fn get_all_application() -> Vec<Self> {
         vec![
             Self::UpdateApplication,
@@ -48,6 +65,24 @@ impl AppStaffPermissions {
             Self::DeleteKey,
         ]
     }
+
+    pub fn get_all_staff() -> Vec<Self> {
+        vec![
+            Self::CreateStaff,
+            Self::ReadStaff,
+            Self::UpdateStaff,
+            Self::DeleteStaff,
+        ]
+    }
+
+    pub fn get_all_client() -> Vec<Self> {
+        vec![
+            Self::CreateClient,
+            Self::ReadClient,
+            Self::UpdateClient,
+            Self::DeleteClient,
+        ]
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
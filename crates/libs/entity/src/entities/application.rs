@@ -11,6 +11,8 @@ pub struct Model {
     pub name: String,
     #[sea_orm(column_type = "String(StringLen::N(2048))", default_value = "")]
     pub description: String,
+    /// Redirect URIs allowed on the OAuth2 authorization of the application.
+    pub redirect_uris: Vec<String>,
     #[sea_orm(default_value = "false")]
     pub is_deleted: bool,
     pub created_at: OffsetDateTime,
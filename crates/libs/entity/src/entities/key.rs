@@ -1,3 +1,4 @@
+use rand::Rng;
 use sea_orm::entity::{prelude::*, ActiveValue};
 use uuid::Uuid;
 
@@ -10,7 +11,7 @@ pub struct Model {
     pub id: Uuid,
     pub value: String,
     pub activated_at: Option<OffsetDateTime>,
-    pub lifetime: u32,
+    pub lifetime: i32,
     pub is_bunned: bool,
     pub application_id: Uuid,
     pub user_id: Uuid,
@@ -22,18 +23,49 @@ pub struct Model {
 }
 
 impl Model {
+    /// Checks that the key lifetime isn't over (a key that was never activated isn't expired).
     pub fn is_not_expired(&self) -> bool {
+        self.is_not_expired_at(OffsetDateTime::now_utc())
+    }
+
+    /// Checks that the key lifetime isn't over at the given moment.
+    pub fn is_not_expired_at(&self, now: OffsetDateTime) -> bool {
+        self.remaining_lifetime_at(now) > 0
+    }
+
+    /// Returns the number of seconds left before the key expires (`0` if already expired).
+    ///
+    /// The lifetime starts counting from the activation, so a key that was never activated
+    /// has its whole `lifetime` left.
+    pub fn remaining_lifetime_at(&self, now: OffsetDateTime) -> i64 {
         match self.activated_at {
             Some(v) => {
-                (v.unix_timestamp()
-                    - OffsetDateTime::now_utc()
-                        .to_offset(v.offset())
-                        .unix_timestamp())
-                    < self.lifetime as i64
+                let used = now.unix_timestamp() - v.unix_timestamp();
+                (self.lifetime as i64 - used.max(0)).max(0)
             }
-            None => true,
+            None => self.lifetime as i64,
         }
     }
+
+    /// Returns the moment the key expires (`None` if the key was never activated).
+    pub fn expires_at(&self) -> Option<OffsetDateTime> {
+        self.activated_at
+            .map(|v| v + time::Duration::seconds(self.lifetime as i64))
+    }
+
+    pub fn gen_value() -> String {
+        let chars: String = rand::rng()
+            .sample_iter(rand::distr::Alphanumeric)
+            .take(25)
+            .map(|c| char::from(c).to_ascii_uppercase())
+            .collect();
+        chars
+            .as_bytes()
+            .chunks(5)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+            .collect::<Vec<String>>()
+            .join("-")
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
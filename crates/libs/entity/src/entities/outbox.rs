@@ -0,0 +1,65 @@
+use sea_orm::entity::{prelude::*, ActiveValue};
+use strum_macros::{Display, EnumString, IntoStaticStr};
+use uuid::Uuid;
+
+use time::OffsetDateTime;
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, EnumString, IntoStaticStr, Display,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "String(StringLen::N(255))",
+    rename_all = "snake_case"
+)]
+pub enum OutboxStatus {
+    /// Waiting for its next attempt.
+    Pending,
+    Sent,
+    /// Dead letter, given up after too many failed attempts.
+    Failed,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "outbox")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub queue: String,
+    pub event: String,
+    #[sea_orm(column_type = "Text")]
+    pub payload: String,
+    pub status: OutboxStatus,
+    #[sea_orm(default_value = "0")]
+    pub attempts: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+    pub next_attempt_at: OffsetDateTime,
+    pub sent_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let mut s = self;
+
+        if insert {
+            // Keep the identifier of the event if given
+            if s.id.is_not_set() {
+                s.id = ActiveValue::set(Uuid::new_v4());
+            }
+            let now = OffsetDateTime::now_utc();
+            s.status = ActiveValue::set(OutboxStatus::Pending);
+            s.next_attempt_at = ActiveValue::set(now);
+            s.created_at = ActiveValue::set(now);
+        }
+        Ok(s)
+    }
+}
@@ -1,4 +1,8 @@
+pub use super::app_client::Entity as AppClient;
+pub use super::app_consent::Entity as AppConsent;
 pub use super::app_staff::Entity as AppStaff;
 pub use super::application::Entity as Application;
 pub use super::key::Entity as Key;
+pub use super::notification::Entity as Notification;
+pub use super::outbox::Entity as Outbox;
 pub use super::user::Entity as User;
@@ -1,10 +1,10 @@
 use rand::Rng;
 use sea_orm::entity::{prelude::*, ActiveValue};
 use strum_macros::{Display, EnumString, IntoStaticStr};
-use util_lib::crypto::{Bcrypt, Hasher};
+use util_lib::crypto;
 use uuid::Uuid;
 
-use crate::event::user as user_event;
+use crate::event::lifecycle;
 
 use time::{Date, OffsetDateTime};
 
@@ -18,12 +18,15 @@ use time::{Date, OffsetDateTime};
 )]
 pub enum UserStaffPermission {
     CreateApplication,
+    RestoreApplication,
 
     CreateStaffUser,
     DeleteStaffUser,
     UpdateStaffUser,
 
     DeleteUser,
+    RevokeUserSessions,
+    ClearLoginLockouts,
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
@@ -33,19 +36,39 @@ pub struct Model {
     pub id: Uuid,
     pub name: String,
     pub email: String,
+    /// Set once the user confirmed the email with the verification token.
+    pub email_verified_at: Option<OffsetDateTime>,
     pub password: String,
     pub birthday: Date,
     pub is_staff: bool,
     pub staff_permissions: Vec<UserStaffPermission>,
     #[sea_orm(default_value = "false")]
     pub is_deleted: bool,
+    /// Version of the issued tokens, bumped to revoke every token of the user.
+    #[sea_orm(default_value = "0")]
+    pub token_version: i32,
+    /// Base32 TOTP secret, set on enrolment and kept until MFA is disabled.
+    pub mfa_secret: Option<String>,
+    /// Set once the enrolment is confirmed with a code, MFA is enabled from then on.
+    pub mfa_enabled_at: Option<OffsetDateTime>,
+    /// Hashes of the unused recovery codes.
+    pub mfa_recovery_codes: Vec<String>,
+    /// MFA enforced by staff, the staff permissions apply only to sessions verified with MFA.
+    #[sea_orm(default_value = "false")]
+    pub mfa_required: bool,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
 }
 
 impl Model {
+    /// Checks the password, a malformed stored hash never matches.
     pub fn is_valid_password(&self, password: &str) -> bool {
-        Bcrypt::new().verify(password, self.password.as_str())
+        crypto::verify(password, self.password.as_str()).unwrap_or(false)
+    }
+
+    /// Checks the stored hash uses an outdated algorithm or parameters.
+    pub fn password_needs_rehash(&self) -> bool {
+        crypto::needs_rehash(self.password.as_str())
     }
 
     pub fn gen_password() -> String {
@@ -55,6 +78,50 @@ impl Model {
             .map(char::from)
             .collect()
     }
+
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    pub fn is_mfa_enabled(&self) -> bool {
+        self.mfa_enabled_at.is_some()
+    }
+
+    /// Returns new recovery codes (`xxxxx-xxxxx`), only their hashes must be saved.
+    pub fn gen_recovery_codes() -> Vec<String> {
+        (0..RECOVERY_CODES_COUNT)
+            .map(|_| {
+                let code: String = rand::rng()
+                    .sample_iter(rand::distr::Alphanumeric)
+                    .take(10)
+                    .map(|v| char::from(v).to_ascii_lowercase())
+                    .collect();
+                format!("{}-{}", &code[..5], &code[5..])
+            })
+            .collect()
+    }
+
+    pub fn hash_recovery_code(code: &str) -> String {
+        crypto::get_default_hasher().hash(&normalize_recovery_code(code))
+    }
+
+    /// Returns the position of the hash of the recovery code, `None` if the code is not valid.
+    pub fn find_recovery_code(&self, code: &str) -> Option<usize> {
+        let code = normalize_recovery_code(code);
+        self.mfa_recovery_codes
+            .iter()
+            .position(|hash| crypto::verify(&code, hash).unwrap_or(false))
+    }
+}
+
+/// Number of recovery codes given on MFA enrolment.
+pub const RECOVERY_CODES_COUNT: usize = 10;
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -62,32 +129,77 @@ pub enum Relation {}
 
 #[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
-    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    async fn before_save<C>(self, db: &C, insert: bool) -> Result<Self, DbErr>
     where
         C: ConnectionTrait,
     {
         let mut s = self;
 
-        let bcrypt = Bcrypt::new();
+        let hasher = crypto::get_default_hasher();
         if insert {
             s.id = ActiveValue::set(Uuid::new_v4());
-            s.password = ActiveValue::set(bcrypt.hash(s.password.unwrap().as_str()));
+            s.password = ActiveValue::set(hasher.hash(s.password.unwrap().as_str()));
+            s.token_version = ActiveValue::set(0);
         } else {
+            // Revoke issued tokens if the access of the user changed
+            if let Some(saved) = get_access_changed_model(db, &s).await? {
+                s.token_version = ActiveValue::set(saved.token_version + 1);
+            }
             // Check password on update (save hash if update)
             if !s.password.is_unchanged() {
-                s.password = ActiveValue::set(bcrypt.hash(s.password.unwrap().as_str()));
+                s.password = ActiveValue::set(hasher.hash(s.password.unwrap().as_str()));
             }
         }
         s.updated_at = ActiveValue::set(OffsetDateTime::now_utc());
-        user_event::create_or_update(&s, insert).await;
+        lifecycle::on_save::<Entity, C>(db, &s, insert).await?;
         Ok(s)
     }
 
-    async fn before_delete<C>(self, _db: &C) -> Result<Self, DbErr>
+    async fn before_delete<C>(self, db: &C) -> Result<Self, DbErr>
     where
         C: ConnectionTrait,
     {
-        user_event::delete(&self).await;
+        lifecycle::on_delete::<Entity, C>(db, &self).await?;
         Ok(self)
     }
 }
+
+/// Returns the stored user if the active model changes its password, staff flag, staff
+/// permissions, MFA (enabled or required) or deleted flag.
+async fn get_access_changed_model<C>(
+    db: &C,
+    active_model: &ActiveModel,
+) -> Result<Option<Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if !(active_model.password.is_set()
+        || active_model.is_staff.is_set()
+        || active_model.staff_permissions.is_set()
+        || active_model.mfa_enabled_at.is_set()
+        || active_model.mfa_required.is_set()
+        || active_model.is_deleted.is_set())
+    {
+        return Ok(None);
+    }
+    let id = match &active_model.id {
+        ActiveValue::Set(v) | ActiveValue::Unchanged(v) => *v,
+        ActiveValue::NotSet => return Ok(None),
+    };
+    let saved = match Entity::find_by_id(id).one(db).await? {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    // A new password is always a change (it is hashed with a new salt)
+    let is_changed = active_model.password.is_set()
+        || matches!(&active_model.is_staff, ActiveValue::Set(v) if *v != saved.is_staff)
+        || matches!(&active_model.staff_permissions, ActiveValue::Set(v) if *v != saved.staff_permissions)
+        || matches!(&active_model.mfa_enabled_at, ActiveValue::Set(v) if *v != saved.mfa_enabled_at)
+        || matches!(&active_model.mfa_required, ActiveValue::Set(v) if *v != saved.mfa_required)
+        || matches!(&active_model.is_deleted, ActiveValue::Set(v) if *v != saved.is_deleted);
+    match is_changed {
+        true => Ok(Some(saved)),
+        false => Ok(None),
+    }
+}
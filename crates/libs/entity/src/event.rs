@@ -1 +1,3 @@
+pub mod lifecycle;
+pub mod outbox;
 pub mod user;
@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+
+use sea_orm::{
+    sea_query::{sea_value_to_json_value, Value},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, Iden, Iterable,
+    ModelTrait, PrimaryKeyToColumn, QueryFilter,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+use time::{format_description::well_known::Rfc3339, serde::rfc3339, OffsetDateTime};
+use uuid::Uuid;
+
+use super::outbox;
+
+/// Version of the [`LifecycleEvent`] payload, increased on breaking changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Kind of change of an entity.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LifecycleKind {
+    Create,
+    Update,
+    SoftDelete,
+    Restore,
+    HardDelete,
+}
+
+impl LifecycleKind {
+    /// Returns the name of the event for the entity, e.g. `user-create`.
+    ///
+    /// A hard delete keeps the historical `<entity>-delete` name.
+    pub fn get_event_type(&self, entity_name: &str) -> String {
+        let kind = match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::SoftDelete => "soft-delete",
+            Self::Restore => "restore",
+            Self::HardDelete => "delete",
+        };
+        format!("{}-{}", entity_name, kind)
+    }
+}
+
+/// Values of a field before and after the change.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub before: Json,
+    pub after: Json,
+}
+
+/// Payload of an entity lifecycle event.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LifecycleEvent {
+    /// Identifier of the event, also sent as AMQP `message_id`.
+    pub event_id: Uuid,
+    /// Name of the event, e.g. `user-update`.
+    pub event_type: String,
+    pub kind: LifecycleKind,
+    pub schema_version: u32,
+    #[serde(with = "rfc3339")]
+    pub occurred_at: OffsetDateTime,
+    /// Name of the entity, e.g. `user`.
+    pub entity: String,
+    /// Primary key of the entity.
+    pub entity_id: Json,
+    /// Names of the changed fields (hidden fields included).
+    pub changed_fields: Vec<String>,
+    /// Values before and after the change of the changed fields (hidden fields excluded).
+    pub diff: BTreeMap<String, FieldDiff>,
+    /// Entity after the change, or before it for a hard delete (hidden fields excluded).
+    pub data: Map<String, Json>,
+}
+
+/// An entity whose changes are written as [`LifecycleEvent`] into the outbox.
+///
+/// The entity calls [`on_save`] at the end of `ActiveModelBehavior::before_save` and
+/// [`on_delete`] in `ActiveModelBehavior::before_delete`.
+pub trait LifecycleEntity: EntityTrait {
+    /// Returns the name of the entity in the events, e.g. `user`.
+    fn get_event_entity() -> &'static str;
+
+    /// Returns the name of the queue the events are published to.
+    fn get_event_queue() -> String;
+
+    /// Returns the fields whose values are never sent (e.g. password hash).
+    fn get_hidden_fields() -> Vec<Self::Column> {
+        Vec::new()
+    }
+
+    /// Returns the fields whose change alone doesn't emit an update event (e.g. `updated_at`).
+    fn get_untracked_fields() -> Vec<Self::Column> {
+        Vec::new()
+    }
+
+    /// Returns the soft delete column of the entity, if it is soft deleted.
+    fn get_deleted_column() -> Option<Self::Column> {
+        None
+    }
+}
+
+/// Writes the lifecycle event of a saved entity into the outbox.
+///
+/// The previous state is read with the connection of the save, so the event reflects the
+/// committed change only if the save runs in a transaction.
+///
+/// # Arguments
+/// * `db` - The connection (or transaction) saving the entity.
+/// * `active_model` - The active model being saved.
+/// * `insert` - Whether the entity is created.
+pub async fn on_save<E, C>(db: &C, active_model: &E::ActiveModel, insert: bool) -> Result<(), DbErr>
+where
+    E: LifecycleEntity,
+    C: ConnectionTrait,
+{
+    let before = match insert {
+        true => None,
+        false => get_saved_model::<E, C>(db, active_model).await?,
+    };
+
+    // Merge the saved state with the changes of the active model
+    let mut before_values = BTreeMap::<String, Json>::new();
+    let mut after_values = BTreeMap::<String, Json>::new();
+    for column in E::Column::iter() {
+        let name = column.to_string();
+        let before_value = before
+            .as_ref()
+            .map(|model| to_json_value(&model.get(column)));
+        let after_value = match active_model.get(column).into_value() {
+            Some(v) => Some(to_json_value(&v)),
+            None => before_value.to_owned(),
+        };
+        if let Some(v) = before_value {
+            before_values.insert(name.to_owned(), v);
+        }
+        if let Some(v) = after_value {
+            after_values.insert(name, v);
+        }
+    }
+
+    let changed_columns: Vec<E::Column> = E::Column::iter()
+        .filter(|column| {
+            let name = column.to_string();
+            before_values.get(&name) != after_values.get(&name)
+        })
+        .collect();
+
+    let kind = match insert {
+        true => LifecycleKind::Create,
+        false => {
+            let is_deleted = |values: &BTreeMap<String, Json>| {
+                E::get_deleted_column()
+                    .and_then(|column| values.get(&column.to_string()).cloned())
+                    .map(|v| v == Json::Bool(true))
+            };
+            match (is_deleted(&before_values), is_deleted(&after_values)) {
+                (Some(false), Some(true)) => LifecycleKind::SoftDelete,
+                (Some(true), Some(false)) => LifecycleKind::Restore,
+                _ => {
+                    let untracked: Vec<String> = E::get_untracked_fields()
+                        .iter()
+                        .map(|column| column.to_string())
+                        .collect();
+                    if changed_columns
+                        .iter()
+                        .all(|column| untracked.contains(&column.to_string()))
+                    {
+                        return Ok(());
+                    }
+                    LifecycleKind::Update
+                }
+            }
+        }
+    };
+
+    add_event::<E, C>(db, kind, &changed_columns, &before_values, after_values).await
+}
+
+/// Writes the hard delete event of an entity into the outbox.
+///
+/// # Arguments
+/// * `db` - The connection (or transaction) deleting the entity.
+/// * `active_model` - The active model being deleted.
+pub async fn on_delete<E, C>(db: &C, active_model: &E::ActiveModel) -> Result<(), DbErr>
+where
+    E: LifecycleEntity,
+    C: ConnectionTrait,
+{
+    let before = get_saved_model::<E, C>(db, active_model).await?;
+    let mut before_values = BTreeMap::<String, Json>::new();
+    for column in E::Column::iter() {
+        let value = match &before {
+            Some(model) => Some(model.get(column)),
+            None => active_model.get(column).into_value(),
+        };
+        if let Some(v) = value {
+            before_values.insert(column.to_string(), to_json_value(&v));
+        }
+    }
+    add_event::<E, C>(
+        db,
+        LifecycleKind::HardDelete,
+        &[],
+        &BTreeMap::new(),
+        before_values,
+    )
+    .await
+}
+
+/// Builds the event and writes it into the outbox.
+async fn add_event<E, C>(
+    db: &C,
+    kind: LifecycleKind,
+    changed_columns: &[E::Column],
+    before_values: &BTreeMap<String, Json>,
+    data_values: BTreeMap<String, Json>,
+) -> Result<(), DbErr>
+where
+    E: LifecycleEntity,
+    C: ConnectionTrait,
+{
+    let hidden: Vec<String> = E::get_hidden_fields()
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+
+    let mut entity_id = Vec::<Json>::new();
+    for key in E::PrimaryKey::iter() {
+        let name = key.into_column().to_string();
+        entity_id.push(data_values.get(&name).cloned().unwrap_or(Json::Null));
+    }
+    let entity_id = match entity_id.len() {
+        1 => entity_id.remove(0),
+        _ => Json::Array(entity_id),
+    };
+
+    let mut changed_fields = Vec::<String>::new();
+    let mut diff = BTreeMap::<String, FieldDiff>::new();
+    for column in changed_columns {
+        let name = column.to_string();
+        if !hidden.contains(&name) {
+            diff.insert(
+                name.to_owned(),
+                FieldDiff {
+                    before: before_values.get(&name).cloned().unwrap_or(Json::Null),
+                    after: data_values.get(&name).cloned().unwrap_or(Json::Null),
+                },
+            );
+        }
+        changed_fields.push(name);
+    }
+
+    let data: Map<String, Json> = data_values
+        .into_iter()
+        .filter(|(name, _)| !hidden.contains(name))
+        .collect();
+
+    let event = LifecycleEvent {
+        event_id: Uuid::new_v4(),
+        event_type: kind.get_event_type(E::get_event_entity()),
+        kind,
+        schema_version: SCHEMA_VERSION,
+        occurred_at: OffsetDateTime::now_utc(),
+        entity: E::get_event_entity().to_string(),
+        entity_id,
+        changed_fields,
+        diff,
+        data,
+    };
+    outbox::add(
+        db,
+        event.event_id,
+        E::get_event_queue(),
+        event.event_type.to_owned(),
+        serde_json::to_string(&event).unwrap(),
+    )
+    .await
+}
+
+/// Reads the stored state of the entity matching the primary key of the active model.
+async fn get_saved_model<E, C>(
+    db: &C,
+    active_model: &E::ActiveModel,
+) -> Result<Option<E::Model>, DbErr>
+where
+    E: LifecycleEntity,
+    C: ConnectionTrait,
+{
+    let mut filter = Condition::all();
+    for key in E::PrimaryKey::iter() {
+        let column = key.into_column();
+        match active_model.get(column).into_value() {
+            Some(v) => filter = filter.add(column.eq(v)),
+            None => return Ok(None),
+        }
+    }
+    E::find().filter(filter).one(db).await
+}
+
+/// Converts a database value into JSON, dates are formatted as RFC 3339.
+fn to_json_value(value: &Value) -> Json {
+    match value {
+        Value::TimeDateTimeWithTimeZone(Some(v)) => match v.format(&Rfc3339) {
+            Ok(v) => Json::String(v),
+            Err(_) => sea_value_to_json_value(value),
+        },
+        Value::TimeDate(Some(v)) => Json::String(v.to_string()),
+        _ => sea_value_to_json_value(value),
+    }
+}
@@ -0,0 +1,36 @@
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DbErr, Set};
+use uuid::Uuid;
+
+use crate::entities::outbox as outbox_entity;
+
+/// Writes an event into the outbox, to be published by the outbox relay.
+///
+/// The event must be written with the connection of the change emitting it, so both are
+/// committed or rolled back together.
+///
+/// # Arguments
+/// * `db` - The connection (or transaction) of the change.
+/// * `id` - The identifier of the event, sent as AMQP `message_id`.
+/// * `queue` - The name of the queue the event is published to.
+/// * `event` - The name of the event.
+/// * `payload` - The JSON content of the event.
+pub async fn add<C>(
+    db: &C,
+    id: Uuid,
+    queue: String,
+    event: String,
+    payload: String,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+{
+    let message = outbox_entity::ActiveModel {
+        id: Set(id),
+        queue: Set(queue),
+        event: Set(event),
+        payload: Set(payload),
+        ..Default::default()
+    };
+    message.insert(db).await?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use entity_lib::app_client;
+use time::OffsetDateTime;
+use util_lib::crypto::{Bcrypt, Hasher};
+use uuid::Uuid;
+
+fn app_client_model(secret: &str) -> app_client::Model {
+    let now = OffsetDateTime::now_utc();
+    app_client::Model {
+        id: Uuid::new_v4(),
+        application_id: Uuid::new_v4(),
+        name: "client".to_string(),
+        secret: Bcrypt::new().hash(secret),
+        permissions: Vec::new(),
+        created_by_user_id: Uuid::new_v4(),
+        secret_rotated_at: now,
+        is_deleted: false,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[test]
+fn generated_secrets_are_unique() {
+    let secret = app_client::Model::gen_secret();
+    assert_eq!(secret.len(), 48);
+    assert!(secret.chars().all(|c| c.is_ascii_alphanumeric()));
+    assert_ne!(secret, app_client::Model::gen_secret());
+}
+
+#[test]
+fn secret_is_checked_against_its_hash() {
+    let secret = app_client::Model::gen_secret();
+    let model = app_client_model(&secret);
+    assert_ne!(model.secret, secret);
+    assert!(model.is_valid_secret(&secret));
+    assert!(!model.is_valid_secret(&app_client::Model::gen_secret()));
+}
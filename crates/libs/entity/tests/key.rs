@@ -0,0 +1,69 @@
+use entity_lib::key;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+fn key_model(activated_at: Option<OffsetDateTime>, lifetime: i32) -> key::Model {
+    let now = OffsetDateTime::now_utc();
+    key::Model {
+        id: Uuid::new_v4(),
+        value: key::Model::gen_value(),
+        activated_at,
+        lifetime,
+        is_bunned: false,
+        application_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        created_by_user_id: Uuid::new_v4(),
+        is_deleted: false,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[test]
+fn not_activated_key_is_not_expired() {
+    let now = OffsetDateTime::now_utc();
+    let model = key_model(None, 60);
+    assert!(model.is_not_expired_at(now));
+    assert_eq!(model.remaining_lifetime_at(now), 60);
+    assert_eq!(model.expires_at(), None);
+}
+
+#[test]
+fn activated_key_counts_lifetime_from_activation() {
+    let now = OffsetDateTime::now_utc();
+    let model = key_model(Some(now - Duration::seconds(20)), 60);
+    assert!(model.is_not_expired_at(now));
+    assert_eq!(model.remaining_lifetime_at(now), 40);
+    assert_eq!(
+        model.expires_at(),
+        Some(now - Duration::seconds(20) + Duration::seconds(60))
+    );
+}
+
+#[test]
+fn activated_key_expires_after_lifetime() {
+    let now = OffsetDateTime::now_utc();
+    let model = key_model(Some(now - Duration::seconds(61)), 60);
+    assert!(!model.is_not_expired_at(now));
+    assert_eq!(model.remaining_lifetime_at(now), 0);
+
+    let model = key_model(Some(now - Duration::seconds(60)), 60);
+    assert!(!model.is_not_expired_at(now));
+}
+
+#[test]
+fn activation_in_future_does_not_extend_lifetime() {
+    let now = OffsetDateTime::now_utc();
+    let model = key_model(Some(now + Duration::seconds(30)), 60);
+    assert_eq!(model.remaining_lifetime_at(now), 60);
+}
+
+#[test]
+fn generated_value_is_grouped() {
+    let value = key::Model::gen_value();
+    let groups: Vec<&str> = value.split('-').collect();
+    assert_eq!(groups.len(), 5);
+    assert!(groups
+        .iter()
+        .all(|group| group.len() == 5 && group.chars().all(|c| c.is_ascii_alphanumeric())));
+}
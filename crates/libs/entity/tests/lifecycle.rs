@@ -0,0 +1,237 @@
+use entity_lib::{
+    event::lifecycle::{self, FieldDiff, LifecycleEvent, LifecycleKind},
+    outbox, user,
+};
+use sea_orm::{
+    ActiveValue, DatabaseBackend, DatabaseConnection, IntoActiveModel, MockDatabase, Set, Value,
+};
+use serde_json::json;
+use time::{Date, Month, OffsetDateTime};
+use uuid::Uuid;
+
+#[test]
+fn event_type_is_prefixed_by_entity() {
+    assert_eq!(LifecycleKind::Create.get_event_type("user"), "user-create");
+    assert_eq!(LifecycleKind::Update.get_event_type("user"), "user-update");
+    assert_eq!(
+        LifecycleKind::SoftDelete.get_event_type("user"),
+        "user-soft-delete"
+    );
+    assert_eq!(
+        LifecycleKind::Restore.get_event_type("user"),
+        "user-restore"
+    );
+}
+
+#[test]
+fn hard_delete_keeps_historical_event_type() {
+    assert_eq!(
+        LifecycleKind::HardDelete.get_event_type("user"),
+        "user-delete"
+    );
+}
+
+#[test]
+fn kind_is_serialized_in_kebab_case() {
+    assert_eq!(
+        serde_json::to_string(&LifecycleKind::SoftDelete).unwrap(),
+        "\"soft-delete\""
+    );
+}
+
+fn user_model() -> user::Model {
+    let now = OffsetDateTime::now_utc();
+    user::Model {
+        id: Uuid::new_v4(),
+        name: "user".to_string(),
+        email: "user@example.com".to_string(),
+        email_verified_at: None,
+        password: "hash".to_string(),
+        birthday: Date::from_calendar_date(2000, Month::January, 1).unwrap(),
+        is_staff: false,
+        staff_permissions: Vec::new(),
+        is_deleted: false,
+        token_version: 0,
+        mfa_secret: None,
+        mfa_enabled_at: None,
+        mfa_recovery_codes: Vec::new(),
+        mfa_required: false,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn outbox_model() -> outbox::Model {
+    outbox::Model {
+        id: Uuid::new_v4(),
+        queue: "queue".to_string(),
+        event: "event".to_string(),
+        payload: String::new(),
+        status: outbox::OutboxStatus::Pending,
+        attempts: 0,
+        last_error: None,
+        next_attempt_at: OffsetDateTime::now_utc(),
+        sent_at: None,
+        created_at: OffsetDateTime::now_utc(),
+    }
+}
+
+/// Returns the lifecycle events written into the outbox with the mock connection.
+fn get_events(db: DatabaseConnection) -> Vec<LifecycleEvent> {
+    db.into_transaction_log()
+        .iter()
+        .flat_map(|v| v.statements().to_vec())
+        .filter(|v| v.sql.starts_with(r#"INSERT INTO "outbox""#))
+        .flat_map(|v| v.values.map(|v| v.0).unwrap_or_default())
+        .filter_map(|v| match v {
+            Value::String(Some(v)) => serde_json::from_str::<LifecycleEvent>(&v).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Saves the active model over the stored model and returns the written events.
+async fn get_update_events(
+    saved: &user::Model,
+    active_model: &user::ActiveModel,
+) -> Vec<LifecycleEvent> {
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![saved.clone()]])
+        .append_query_results([vec![outbox_model()]])
+        .into_connection();
+    lifecycle::on_save::<user::Entity, _>(&db, active_model, false)
+        .await
+        .unwrap();
+    get_events(db)
+}
+
+#[tokio::test]
+async fn create_event_has_every_visible_field() {
+    let model = user_model();
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![outbox_model()]])
+        .into_connection();
+    lifecycle::on_save::<user::Entity, _>(&db, &model.clone().into_active_model(), true)
+        .await
+        .unwrap();
+    let mut events = get_events(db);
+    assert_eq!(events.len(), 1);
+    let event = events.remove(0);
+    assert_eq!(event.kind, LifecycleKind::Create);
+    assert_eq!(event.event_type, "user-create");
+    assert_eq!(event.entity, "user");
+    assert_eq!(event.entity_id, json!(model.id));
+    assert!(event.changed_fields.contains(&"password".to_string()));
+    assert_eq!(
+        event.diff.get("name"),
+        Some(&FieldDiff {
+            before: json!(null),
+            after: json!("user"),
+        })
+    );
+    assert_eq!(event.data.get("email"), Some(&json!("user@example.com")));
+    assert_eq!(event.data.get("birthday"), Some(&json!("2000-01-01")));
+}
+
+#[tokio::test]
+async fn update_event_has_the_diff_of_the_changed_fields() {
+    let model = user_model();
+    let mut active_model = model.clone().into_active_model();
+    active_model.name = Set("new name".to_string());
+    active_model.email = Set("user@example.com".to_string());
+    active_model.updated_at = Set(OffsetDateTime::now_utc());
+    let mut events = get_update_events(&model, &active_model).await;
+    assert_eq!(events.len(), 1);
+    let event = events.remove(0);
+    assert_eq!(event.kind, LifecycleKind::Update);
+    assert_eq!(event.event_type, "user-update");
+    // The email set to its value isn't a change, the untracked fields are listed with the others
+    assert_eq!(event.changed_fields, vec!["name", "updated_at"]);
+    assert_eq!(
+        event.diff.get("name"),
+        Some(&FieldDiff {
+            before: json!("user"),
+            after: json!("new name"),
+        })
+    );
+    assert!(!event.diff.contains_key("email"));
+    assert_eq!(event.data.get("name"), Some(&json!("new name")));
+    assert_eq!(event.data.get("email"), Some(&json!("user@example.com")));
+}
+
+#[tokio::test]
+async fn change_of_untracked_fields_only_has_no_event() {
+    let model = user_model();
+    let mut active_model = model.clone().into_active_model();
+    active_model.updated_at = Set(OffsetDateTime::now_utc() + time::Duration::seconds(1));
+    assert!(get_update_events(&model, &active_model).await.is_empty());
+
+    // Nothing changed
+    let active_model = model.clone().into_active_model();
+    assert!(get_update_events(&model, &active_model).await.is_empty());
+}
+
+#[tokio::test]
+async fn hidden_fields_are_listed_without_values() {
+    let model = user_model();
+    let mut active_model = model.clone().into_active_model();
+    active_model.password = Set("new hash".to_string());
+    let event = get_update_events(&model, &active_model).await.remove(0);
+    assert_eq!(event.kind, LifecycleKind::Update);
+    assert_eq!(event.changed_fields, vec!["password"]);
+    assert!(event.diff.is_empty());
+    assert!(!event.data.contains_key("password"));
+    let payload = serde_json::to_string(&event).unwrap();
+    assert!(!payload.contains("new hash"));
+    assert!(!payload.contains("\"hash\""));
+}
+
+#[tokio::test]
+async fn deleted_flag_emits_soft_delete_and_restore() {
+    let model = user_model();
+    let mut active_model = model.clone().into_active_model();
+    active_model.is_deleted = Set(true);
+    let event = get_update_events(&model, &active_model).await.remove(0);
+    assert_eq!(event.kind, LifecycleKind::SoftDelete);
+    assert_eq!(event.event_type, "user-soft-delete");
+    assert_eq!(
+        event.diff.get("is_deleted"),
+        Some(&FieldDiff {
+            before: json!(false),
+            after: json!(true),
+        })
+    );
+
+    let mut deleted = model.clone();
+    deleted.is_deleted = true;
+    let mut active_model = deleted.clone().into_active_model();
+    active_model.is_deleted = Set(false);
+    let event = get_update_events(&deleted, &active_model).await.remove(0);
+    assert_eq!(event.kind, LifecycleKind::Restore);
+    assert_eq!(event.event_type, "user-restore");
+}
+
+#[tokio::test]
+async fn hard_delete_event_has_the_deleted_entity() {
+    let model = user_model();
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![model.clone()]])
+        .append_query_results([vec![outbox_model()]])
+        .into_connection();
+    // Only the primary key is known, the values are read from the database
+    let active_model = user::ActiveModel {
+        id: ActiveValue::Unchanged(model.id),
+        ..Default::default()
+    };
+    lifecycle::on_delete::<user::Entity, _>(&db, &active_model)
+        .await
+        .unwrap();
+    let event = get_events(db).remove(0);
+    assert_eq!(event.kind, LifecycleKind::HardDelete);
+    assert_eq!(event.event_type, "user-delete");
+    assert_eq!(event.entity_id, json!(model.id));
+    assert!(event.changed_fields.is_empty());
+    assert!(event.diff.is_empty());
+    assert_eq!(event.data.get("name"), Some(&json!("user")));
+    assert!(!event.data.contains_key("password"));
+}
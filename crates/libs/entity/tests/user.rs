@@ -0,0 +1,214 @@
+use entity_lib::{
+    event::lifecycle::{self, LifecycleEvent},
+    outbox, user,
+};
+use sea_orm::{
+    ActiveModelBehavior, ActiveValue, DatabaseBackend, DatabaseConnection, IntoActiveModel,
+    MockDatabase, Set, Value,
+};
+use time::{Date, Month, OffsetDateTime};
+use uuid::Uuid;
+
+fn user_model(recovery_codes: &[String]) -> user::Model {
+    let now = OffsetDateTime::now_utc();
+    user::Model {
+        id: Uuid::new_v4(),
+        name: "user".to_string(),
+        email: "user@example.com".to_string(),
+        email_verified_at: None,
+        password: String::new(),
+        birthday: Date::from_calendar_date(2000, Month::January, 1).unwrap(),
+        is_staff: true,
+        staff_permissions: Vec::new(),
+        is_deleted: false,
+        token_version: 0,
+        mfa_secret: None,
+        mfa_enabled_at: Some(now),
+        mfa_recovery_codes: recovery_codes
+            .iter()
+            .map(|v| user::Model::hash_recovery_code(v))
+            .collect(),
+        mfa_required: false,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[test]
+fn generated_recovery_codes_are_unique() {
+    let codes = user::Model::gen_recovery_codes();
+    assert_eq!(codes.len(), user::RECOVERY_CODES_COUNT);
+    for code in &codes {
+        assert_eq!(code.len(), 11);
+        assert_eq!(code.chars().nth(5), Some('-'));
+        assert_eq!(codes.iter().filter(|v| *v == code).count(), 1);
+    }
+}
+
+#[test]
+fn recovery_code_is_found_by_its_hash() {
+    let codes = vec!["abcde-12345".to_string(), "fghij-67890".to_string()];
+    let model = user_model(&codes);
+    assert!(model.is_mfa_enabled());
+    assert_eq!(model.find_recovery_code("fghij-67890"), Some(1));
+    // Separator and case are ignored
+    assert_eq!(model.find_recovery_code(" ABCDE12345 "), Some(0));
+    assert_eq!(model.find_recovery_code("abcde-12346"), None);
+}
+
+fn outbox_model() -> outbox::Model {
+    outbox::Model {
+        id: Uuid::new_v4(),
+        queue: "queue".to_string(),
+        event: "event".to_string(),
+        payload: String::new(),
+        status: outbox::OutboxStatus::Pending,
+        attempts: 0,
+        last_error: None,
+        next_attempt_at: OffsetDateTime::now_utc(),
+        sent_at: None,
+        created_at: OffsetDateTime::now_utc(),
+    }
+}
+
+/// Returns the lifecycle events written into the outbox with the mock connection.
+fn get_events(db: DatabaseConnection) -> Vec<LifecycleEvent> {
+    db.into_transaction_log()
+        .iter()
+        .flat_map(|v| v.statements().to_vec())
+        .filter(|v| v.sql.starts_with(r#"INSERT INTO "outbox""#))
+        .flat_map(|v| v.values.map(|v| v.0).unwrap_or_default())
+        .filter_map(|v| match v {
+            Value::String(Some(v)) => serde_json::from_str::<LifecycleEvent>(&v).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn mfa_secrets_are_hidden_from_events() {
+    let mut model = user_model(&["abcde-12345".to_string()]);
+    model.mfa_secret = Some("JBSWY3DPEHPK3PXP".to_string());
+
+    // Create
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![outbox_model()]])
+        .into_connection();
+    lifecycle::on_save::<user::Entity, _>(&db, &model.clone().into_active_model(), true)
+        .await
+        .unwrap();
+    let event = get_events(db).remove(0);
+    assert!(event.data.contains_key("email"));
+    for hidden in ["password", "mfa_secret", "mfa_recovery_codes"] {
+        assert!(!event.data.contains_key(hidden));
+    }
+
+    // Update of the secret
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![model.clone()]])
+        .append_query_results([vec![outbox_model()]])
+        .into_connection();
+    let mut active_model = model.clone().into_active_model();
+    active_model.mfa_secret = Set(Some("KRSXG5CTMVRXEZLU".to_string()));
+    active_model.mfa_recovery_codes = Set(Vec::new());
+    lifecycle::on_save::<user::Entity, _>(&db, &active_model, false)
+        .await
+        .unwrap();
+    let event = get_events(db).remove(0);
+    assert!(event.changed_fields.contains(&"mfa_secret".to_string()));
+    assert!(event
+        .changed_fields
+        .contains(&"mfa_recovery_codes".to_string()));
+    assert!(event.diff.is_empty());
+    assert!(!event.data.contains_key("mfa_secret"));
+    assert!(!serde_json::to_string(&event)
+        .unwrap()
+        .contains("KRSXG5CTMVRXEZLU"));
+}
+
+/// Name of a field and its change on the active model.
+type Change = (&'static str, fn(&mut user::ActiveModel));
+
+/// Saves the active model over the stored model and returns the token version to be saved.
+async fn get_saved_token_version(saved: &user::Model, active_model: user::ActiveModel) -> i32 {
+    // The stored user is read to compare the access fields set, then to write the event
+    let mut db = MockDatabase::new(DatabaseBackend::Postgres);
+    if active_model.password.is_set()
+        || active_model.is_staff.is_set()
+        || active_model.staff_permissions.is_set()
+        || active_model.mfa_enabled_at.is_set()
+        || active_model.mfa_required.is_set()
+        || active_model.is_deleted.is_set()
+    {
+        db = db.append_query_results([vec![saved.clone()]]);
+    }
+    let db = db
+        .append_query_results([vec![saved.clone()]])
+        .append_query_results([vec![outbox_model()]])
+        .into_connection();
+    let active_model = active_model.before_save(&db, false).await.unwrap();
+    match active_model.token_version {
+        ActiveValue::Set(v) | ActiveValue::Unchanged(v) => v,
+        ActiveValue::NotSet => panic!("token version must be set"),
+    }
+}
+
+#[tokio::test]
+async fn access_changes_bump_token_version() {
+    let mut saved = user_model(&[]);
+    saved.token_version = 3;
+    let changes: Vec<Change> = vec![
+        ("password", |v| v.password = Set("new password".to_string())),
+        ("is_staff", |v| v.is_staff = Set(false)),
+        ("staff_permissions", |v| {
+            v.staff_permissions = Set(vec![user::UserStaffPermission::DeleteUser])
+        }),
+        ("mfa_enabled_at", |v| v.mfa_enabled_at = Set(None)),
+        ("mfa_required", |v| v.mfa_required = Set(true)),
+        ("is_deleted", |v| v.is_deleted = Set(true)),
+    ];
+    for (field, change) in changes {
+        let mut active_model = saved.clone().into_active_model();
+        change(&mut active_model);
+        assert_eq!(
+            get_saved_token_version(&saved, active_model).await,
+            4,
+            "{}",
+            field
+        );
+    }
+}
+
+#[tokio::test]
+async fn other_changes_keep_token_version() {
+    let mut saved = user_model(&[]);
+    saved.token_version = 3;
+    let changes: Vec<Change> = vec![
+        ("nothing", |_| {}),
+        ("name", |v| v.name = Set("new name".to_string())),
+        ("email", |v| v.email = Set("new@example.com".to_string())),
+        ("mfa_secret", |v| {
+            v.mfa_secret = Set(Some("JBSWY3DPEHPK3PXP".to_string()))
+        }),
+        ("mfa_recovery_codes", |v| {
+            v.mfa_recovery_codes = Set(Vec::new())
+        }),
+        // Access fields set to their saved value
+        ("is_staff", |v| v.is_staff = Set(true)),
+        ("staff_permissions", |v| {
+            v.staff_permissions = Set(Vec::new())
+        }),
+        ("mfa_required", |v| v.mfa_required = Set(false)),
+        ("is_deleted", |v| v.is_deleted = Set(false)),
+    ];
+    for (field, change) in changes {
+        let mut active_model = saved.clone().into_active_model();
+        change(&mut active_model);
+        assert_eq!(
+            get_saved_token_version(&saved, active_model).await,
+            3,
+            "{}",
+            field
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use time::OffsetDateTime;
+
+use util_lib::retry::RetryPolicy;
+
+use crate::{settings::SETTINGS, store::NotificationStore, transport::Transport};
+
+/// Task sending the persisted messages with a transport.
+///
+/// A message which can't be sent is retried with an exponential backoff, and given up after
+/// `NOTIFICATION_DISPATCHER_MAX_ATTEMPTS` attempts (at once if it can never be sent). The
+/// messages are claimed with a lease, so several dispatchers can share a store, and the sent
+/// messages are removed after `NOTIFICATION_DISPATCHER_SENT_RETENTION_SEC`.
+///
+/// # Example
+/// ```rust,ignore
+/// tokio::spawn(Dispatcher::new(NotificationRepository::new().await, get_default_transport()?).run());
+/// ```
+pub struct Dispatcher<S: NotificationStore> {
+    store: S,
+    transport: Box<dyn Transport>,
+    retry: RetryPolicy,
+}
+
+impl<S: NotificationStore> Dispatcher<S> {
+    /// Creates a new dispatcher reading the messages from the store.
+    pub fn new(store: S, transport: Box<dyn Transport>) -> Self {
+        let settings = &SETTINGS.dispatcher;
+        Self {
+            store,
+            transport,
+            retry: RetryPolicy {
+                max_attempts: settings.max_attempts,
+                backoff_base_sec: settings.backoff_base_sec,
+                backoff_max_sec: settings.backoff_max_sec,
+            },
+        }
+    }
+
+    /// Replaces the retry policy of the settings.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn get_store(&self) -> &S {
+        &self.store
+    }
+
+    /// Sends the due messages forever, polling the store every
+    /// `NOTIFICATION_DISPATCHER_INTERVAL_MS` milliseconds.
+    pub async fn run(self) {
+        let interval = Duration::from_millis(SETTINGS.dispatcher.interval_ms);
+        loop {
+            self.dispatch_due().await;
+            self.delete_sent().await;
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Sends one batch (`NOTIFICATION_DISPATCHER_BATCH_SIZE`) of due messages.
+    ///
+    /// # Returns
+    /// The number of sent messages.
+    pub async fn dispatch_due(&self) -> usize {
+        let settings = &SETTINGS.dispatcher;
+        let messages = match self
+            .store
+            .claim_due(settings.batch_size, settings.lease_sec)
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("Notification: failed to get due messages: {:?}", e);
+                return 0;
+            }
+        };
+
+        let mut sent: usize = 0;
+        for message in messages {
+            let result = match self.transport.send(&message).await {
+                Ok(_) => {
+                    sent += 1;
+                    self.store.mark_sent(message.id).await
+                }
+                Err(e) => {
+                    let attempts = message.attempts + 1;
+                    let retry_at = self.retry.get_retry_at(attempts, e.is_retryable());
+                    log::error!(
+                        "Notification: failed to send message {} (attempt {}, {}): {}",
+                        message.id,
+                        attempts,
+                        match retry_at {
+                            Some(_) => "retried",
+                            None => "given up",
+                        },
+                        e
+                    );
+                    self.store
+                        .mark_failed(message.id, &e.to_string(), retry_at)
+                        .await
+                }
+            };
+            if let Err(e) = result {
+                log::error!(
+                    "Notification: failed to mark message {}: {:?}",
+                    message.id,
+                    e
+                );
+            }
+        }
+        sent
+    }
+
+    /// Removes the messages sent for more than `NOTIFICATION_DISPATCHER_SENT_RETENTION_SEC`.
+    pub async fn delete_sent(&self) {
+        let before = OffsetDateTime::now_utc()
+            - time::Duration::seconds(SETTINGS.dispatcher.sent_retention_sec as i64);
+        if let Err(e) = self.store.delete_sent(before).await {
+            log::error!("Notification: failed to delete sent messages: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+pub mod dispatcher;
+pub mod settings;
+pub mod store;
+pub mod template;
+pub mod transport;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub use dispatcher::Dispatcher;
+pub use store::NotificationStore;
+pub use template::{Templates, TEMPLATES};
+pub use transport::Transport;
+
+/// A notification to send, rendered into a [`Message`] with the templates of its name.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// Name of the templates, e.g. `user-password-reset`.
+    pub template: String,
+    /// Email address of the recipient.
+    pub recipient: String,
+    /// Preferred locale of the recipient (e.g. `en`, `en-US`), the default locale if `None`.
+    pub locale: Option<String>,
+    /// Values available to the templates.
+    pub context: Value,
+}
+
+/// A rendered notification, persisted until it is sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    pub id: Uuid,
+    /// Name of the templates the message was rendered with.
+    pub template: String,
+    /// Locale of the templates the message was rendered with.
+    pub locale: String,
+    pub recipient: String,
+    pub subject: String,
+    pub body_text: String,
+    pub body_html: Option<String>,
+    /// Failed attempts to send the message.
+    pub attempts: u32,
+}
+
+/// Error to enqueue a notification.
+#[derive(Debug)]
+pub enum ErrorEnqueue<E> {
+    /// The templates of the notification are missing or can't be rendered.
+    Render(template::ErrorRender),
+    /// The message can't be persisted.
+    Store(E),
+}
+
+impl<E: fmt::Debug> fmt::Display for ErrorEnqueue<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Render(e) => write!(f, "render error: {}", e),
+            Self::Store(e) => write!(f, "store error: {:?}", e),
+        }
+    }
+}
+
+/// Renders the notification with the global templates and persists the message, the
+/// [`Dispatcher`] sends it.
+///
+/// # Returns
+/// The identifier of the message.
+///
+/// # Example
+/// ```rust,ignore
+/// let notification = Notification {
+///     template: "user-password-reset".to_string(),
+///     recipient: "user@example.com".to_string(),
+///     locale: Some("en".to_string()),
+///     context: serde_json::json!({"name": "User", "token": "..."}),
+/// };
+/// enqueue(&NotificationRepository::new().await, &notification).await?;
+/// ```
+pub async fn enqueue<S: NotificationStore>(
+    store: &S,
+    notification: &Notification,
+) -> Result<Uuid, ErrorEnqueue<S::Error>> {
+    let message = TEMPLATES
+        .render(notification)
+        .map_err(ErrorEnqueue::Render)?;
+    store.add(&message).await.map_err(ErrorEnqueue::Store)?;
+    Ok(message.id)
+}
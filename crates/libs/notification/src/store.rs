@@ -0,0 +1,50 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::Message;
+
+/// Storage of the messages read by the [`crate::Dispatcher`].
+///
+/// A message is persisted before any attempt to send it, so it survives a failure of the
+/// transport or a restart, and it is sent again until it is marked as sent or given up.
+#[async_trait]
+pub trait NotificationStore: Send + Sync {
+    type Error: fmt::Debug + Send;
+
+    /// Saves a new message, due at once.
+    async fn add(&self, message: &Message) -> Result<(), Self::Error>;
+
+    /// Returns the oldest messages due to be sent and postpones their next attempt by
+    /// `lease_sec`, so concurrent dispatchers don't send them twice.
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of messages to return.
+    /// * `lease_sec` - Time the messages are held, they are due again after it if the
+    ///   dispatcher stops before marking them.
+    async fn claim_due(&self, limit: u64, lease_sec: u64) -> Result<Vec<Message>, Self::Error>;
+
+    /// Marks the message as sent and removes its bodies, which can hold tokens.
+    async fn mark_sent(&self, id: Uuid) -> Result<(), Self::Error>;
+
+    /// Records a failed attempt to send the message.
+    ///
+    /// # Arguments
+    /// * `error` - The error of the transport.
+    /// * `retry_at` - The time of the next attempt, `None` to give the message up and remove its
+    ///   bodies.
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), Self::Error>;
+
+    /// Removes the messages sent before the given time.
+    ///
+    /// # Returns
+    /// The number of removed messages.
+    async fn delete_sent(&self, before: OffsetDateTime) -> Result<u64, Self::Error>;
+}
@@ -0,0 +1,302 @@
+use std::{fmt, fs, io, path::Path};
+
+use minijinja::{Environment, Value};
+use once_cell::sync::Lazy;
+use uuid::Uuid;
+
+use crate::{settings::SETTINGS, Message, Notification};
+
+/// Templates of the application: the built-in ones, overridden by the ones of the
+/// `NOTIFICATION_TEMPLATES_DIR` directory.
+pub static TEMPLATES: Lazy<Templates> = Lazy::new(|| {
+    let settings = &SETTINGS.notification;
+    let mut templates = Templates::with_builtin(&settings.default_locale);
+    if !settings.templates_dir.is_empty() {
+        templates
+            .load_dir(Path::new(&settings.templates_dir))
+            .unwrap();
+    }
+    templates
+});
+
+/// Templates shipped with the library, `(locale, name, part, source)`.
+const BUILTIN: &[(&str, &str, Part, &str)] = &[
+    (
+        "en",
+        "user-password-reset",
+        Part::Subject,
+        include_str!("../templates/en/user-password-reset.subject"),
+    ),
+    (
+        "en",
+        "user-password-reset",
+        Part::Text,
+        include_str!("../templates/en/user-password-reset.txt"),
+    ),
+    (
+        "en",
+        "user-password-reset",
+        Part::Html,
+        include_str!("../templates/en/user-password-reset.html"),
+    ),
+    (
+        "en",
+        "user-email-verification",
+        Part::Subject,
+        include_str!("../templates/en/user-email-verification.subject"),
+    ),
+    (
+        "en",
+        "user-email-verification",
+        Part::Text,
+        include_str!("../templates/en/user-email-verification.txt"),
+    ),
+    (
+        "en",
+        "user-email-verification",
+        Part::Html,
+        include_str!("../templates/en/user-email-verification.html"),
+    ),
+    (
+        "ru",
+        "user-password-reset",
+        Part::Subject,
+        include_str!("../templates/ru/user-password-reset.subject"),
+    ),
+    (
+        "ru",
+        "user-password-reset",
+        Part::Text,
+        include_str!("../templates/ru/user-password-reset.txt"),
+    ),
+    (
+        "ru",
+        "user-password-reset",
+        Part::Html,
+        include_str!("../templates/ru/user-password-reset.html"),
+    ),
+    (
+        "ru",
+        "user-email-verification",
+        Part::Subject,
+        include_str!("../templates/ru/user-email-verification.subject"),
+    ),
+    (
+        "ru",
+        "user-email-verification",
+        Part::Text,
+        include_str!("../templates/ru/user-email-verification.txt"),
+    ),
+    (
+        "ru",
+        "user-email-verification",
+        Part::Html,
+        include_str!("../templates/ru/user-email-verification.html"),
+    ),
+];
+
+/// Part of a message rendered by a template, the file extension of the template.
+///
+/// The subject and the text body are required, the HTML body is optional. Only the HTML body is
+/// escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    Subject,
+    Text,
+    Html,
+}
+
+impl Part {
+    pub fn get_extension(&self) -> &'static str {
+        match self {
+            Self::Subject => "subject",
+            Self::Text => "txt",
+            Self::Html => "html",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "subject" => Some(Self::Subject),
+            "txt" => Some(Self::Text),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorRender {
+    /// No locale has the subject and the text templates of the notification.
+    TemplateNotFound(String),
+    Template(minijinja::Error),
+}
+
+impl fmt::Display for ErrorRender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TemplateNotFound(name) => write!(f, "template {} not found", name),
+            Self::Template(e) => write!(f, "template error: {}", e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorLoad {
+    Io(io::Error),
+    Template(minijinja::Error),
+}
+
+impl fmt::Display for ErrorLoad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Template(e) => write!(f, "template error: {}", e),
+        }
+    }
+}
+
+/// Templates of the notifications per locale.
+///
+/// A notification is rendered with the templates of the locale of the recipient, else of its
+/// language (`en` for `en-US`), else of the default locale.
+pub struct Templates {
+    env: Environment<'static>,
+    default_locale: String,
+}
+
+impl Templates {
+    /// Creates an empty set of templates.
+    pub fn new(default_locale: &str) -> Self {
+        Self {
+            env: Environment::new(),
+            default_locale: normalize_locale(default_locale),
+        }
+    }
+
+    /// Creates the set of the built-in templates.
+    pub fn with_builtin(default_locale: &str) -> Self {
+        let mut templates = Self::new(default_locale);
+        for (locale, name, part, source) in BUILTIN {
+            templates
+                .add(locale, name, *part, source.to_string())
+                .unwrap();
+        }
+        templates
+    }
+
+    /// Adds a template, replacing the one of the same locale, name and part.
+    pub fn add(
+        &mut self,
+        locale: &str,
+        name: &str,
+        part: Part,
+        source: String,
+    ) -> Result<(), minijinja::Error> {
+        self.env
+            .add_template_owned(get_key(&normalize_locale(locale), name, part), source)
+    }
+
+    /// Adds the templates of a directory, as `<locale>/<name>.<subject|txt|html>` files.
+    ///
+    /// # Returns
+    /// The number of added templates.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize, ErrorLoad> {
+        let mut count: usize = 0;
+        for locale_entry in fs::read_dir(dir).map_err(ErrorLoad::Io)? {
+            let locale_path = locale_entry.map_err(ErrorLoad::Io)?.path();
+            if !locale_path.is_dir() {
+                continue;
+            }
+            let locale = locale_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            for entry in fs::read_dir(&locale_path).map_err(ErrorLoad::Io)? {
+                let path = entry.map_err(ErrorLoad::Io)?.path();
+                let (name, part) = match (
+                    path.file_stem().map(|v| v.to_string_lossy()),
+                    path.extension()
+                        .and_then(|v| Part::from_extension(&v.to_string_lossy())),
+                ) {
+                    (Some(name), Some(part)) => (name.to_string(), part),
+                    _ => continue,
+                };
+                let source = fs::read_to_string(&path).map_err(ErrorLoad::Io)?;
+                self.add(&locale, &name, part, source)
+                    .map_err(ErrorLoad::Template)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Returns the locale whose templates render the notification, `None` if there is none.
+    pub fn get_locale(&self, name: &str, locale: Option<&str>) -> Option<String> {
+        let mut candidates = Vec::<String>::new();
+        if let Some(v) = locale {
+            let locale = normalize_locale(v);
+            if let Some((language, _)) = locale.split_once('-') {
+                candidates.push(language.to_string());
+            }
+            candidates.insert(0, locale);
+        }
+        candidates.push(self.default_locale.to_owned());
+        candidates.into_iter().find(|v| {
+            self.has_template(v, name, Part::Subject) && self.has_template(v, name, Part::Text)
+        })
+    }
+
+    /// Renders the notification into a new message.
+    pub fn render(&self, notification: &Notification) -> Result<Message, ErrorRender> {
+        let locale = match self.get_locale(&notification.template, notification.locale.as_deref()) {
+            Some(v) => v,
+            None => {
+                return Err(ErrorRender::TemplateNotFound(
+                    notification.template.to_owned(),
+                ))
+            }
+        };
+        let context = Value::from_serialize(&notification.context);
+        let render = |part: Part| {
+            self.env
+                .get_template(&get_key(&locale, &notification.template, part))
+                .and_then(|v| v.render(&context))
+                .map_err(ErrorRender::Template)
+        };
+        let body_html = match self.has_template(&locale, &notification.template, Part::Html) {
+            true => Some(render(Part::Html)?),
+            false => None,
+        };
+        Ok(Message {
+            id: Uuid::new_v4(),
+            template: notification.template.to_owned(),
+            locale: locale.to_owned(),
+            recipient: notification.recipient.to_owned(),
+            // A subject is a single line
+            subject: render(Part::Subject)?
+                .lines()
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" "),
+            body_text: render(Part::Text)?,
+            body_html,
+            attempts: 0,
+        })
+    }
+
+    fn has_template(&self, locale: &str, name: &str, part: Part) -> bool {
+        self.env.get_template(&get_key(locale, name, part)).is_ok()
+    }
+}
+
+fn get_key(locale: &str, name: &str, part: Part) -> String {
+    format!("{}/{}.{}", locale, name, part.get_extension())
+}
+
+/// Lowercases the locale and separates its subtags with `-` (`en_US` is `en-us`).
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().to_lowercase().replace('_', "-")
+}
@@ -0,0 +1,109 @@
+pub mod amqp;
+pub mod file;
+pub mod log;
+pub mod smtp;
+
+pub use self::log::Log;
+pub use amqp::Amqp;
+pub use file::File;
+pub use smtp::Smtp;
+
+use std::{fmt, io};
+
+use async_trait::async_trait;
+
+use crate::{settings::SETTINGS, Message};
+
+/// Delivery of the rendered messages.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends the message, an error makes the dispatcher retry it later.
+    async fn send(&self, message: &Message) -> Result<(), ErrorSend>;
+}
+
+/// Error to send a message.
+#[derive(Debug)]
+pub enum ErrorSend {
+    /// The message can't be built, e.g. the sender or the recipient isn't a valid address.
+    InvalidMessage(String),
+    Io(io::Error),
+    Smtp(lettre::transport::smtp::Error),
+    Amqp(lapin::Error),
+    /// The broker refused the message (negative publisher confirm).
+    Nack,
+}
+
+impl ErrorSend {
+    /// Checks another attempt could succeed, an invalid message never will.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Self::InvalidMessage(_))
+    }
+}
+
+impl fmt::Display for ErrorSend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMessage(v) => write!(f, "invalid message: {}", v),
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Smtp(e) => write!(f, "smtp error: {}", e),
+            Self::Amqp(e) => write!(f, "amqp error: {}", e),
+            Self::Nack => write!(f, "message refused by the broker"),
+        }
+    }
+}
+
+impl From<io::Error> for ErrorSend {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<lettre::transport::smtp::Error> for ErrorSend {
+    fn from(e: lettre::transport::smtp::Error) -> Self {
+        Self::Smtp(e)
+    }
+}
+
+impl From<lapin::Error> for ErrorSend {
+    fn from(e: lapin::Error) -> Self {
+        Self::Amqp(e)
+    }
+}
+
+/// Error to choose the transport of the messages.
+#[derive(Debug)]
+pub enum ErrorTransport {
+    /// `NOTIFICATION_TRANSPORT` is not set.
+    MissingTransport,
+    /// `NOTIFICATION_TRANSPORT` is not `log`, `file`, `smtp` or `amqp`.
+    UnknownTransport(String),
+}
+
+impl fmt::Display for ErrorTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTransport => write!(
+                f,
+                "NOTIFICATION_TRANSPORT must be set to log, file, smtp or amqp"
+            ),
+            Self::UnknownTransport(v) => write!(
+                f,
+                "unknown NOTIFICATION_TRANSPORT {}, expected log, file, smtp or amqp",
+                v
+            ),
+        }
+    }
+}
+
+/// Returns the transport chosen with `NOTIFICATION_TRANSPORT`, called once at startup so a
+/// missing or unknown transport stops the service.
+pub fn get_default_transport() -> Result<Box<dyn Transport>, ErrorTransport> {
+    match SETTINGS.notification.transport.to_lowercase().as_str() {
+        "smtp" => Ok(Box::new(Smtp::new())),
+        "amqp" => Ok(Box::new(Amqp::new())),
+        "file" => Ok(Box::new(File::new(&SETTINGS.notification.file_dir))),
+        "log" => Ok(Box::new(Log)),
+        "" => Err(ErrorTransport::MissingTransport),
+        v => Err(ErrorTransport::UnknownTransport(v.to_string())),
+    }
+}
@@ -0,0 +1,111 @@
+use adapter_lib::amqp::connect;
+use async_trait::async_trait;
+use lapin::{
+    options::{BasicPublishOptions, ConfirmSelectOptions, QueueDeclareOptions},
+    types::{AMQPValue, FieldTable, LongString, ShortString},
+    BasicProperties, Channel, Connection,
+};
+use repository_amqp_lib::{event::notification as event_notification, outbox::EVENT_HEADER_KEY};
+use tokio::sync::Mutex;
+
+use super::{ErrorSend, Transport};
+use crate::Message;
+
+/// Publishes the messages as JSON on a queue, for a mailer outside of the application.
+///
+/// The transport uses its own connection with publisher confirms: a message is sent once the
+/// broker acknowledged it. The name of the templates is sent in the event header.
+pub struct Amqp {
+    queue: String,
+    connection: Mutex<Option<(Connection, Channel)>>,
+}
+
+impl Amqp {
+    /// Creates the transport publishing on the notification queue (`AMQP_NOTIFICATION_QUEUE`).
+    pub fn new() -> Self {
+        Self::with_queue(&event_notification::get_queue())
+    }
+
+    pub fn with_queue(queue: &str) -> Self {
+        Self {
+            queue: queue.to_owned(),
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// Returns the confirm mode channel of the transport, opening a new connection (and declaring
+    /// the queue) if needed.
+    async fn get_channel(&self) -> Result<Channel, lapin::Error> {
+        let mut connection = self.connection.lock().await;
+        if let Some((conn, channel)) = connection.as_ref() {
+            if conn.status().connected() && channel.status().connected() {
+                return Ok(channel.clone());
+            }
+        }
+
+        let conn = connect().await?;
+        let channel = conn.create_channel().await?;
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await?;
+        let options = QueueDeclareOptions {
+            durable: true,
+            ..Default::default()
+        };
+        channel
+            .queue_declare(&self.queue, options, FieldTable::default())
+            .await?;
+        *connection = Some((conn, channel.clone()));
+        Ok(channel)
+    }
+}
+
+impl Default for Amqp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Transport for Amqp {
+    async fn send(&self, message: &Message) -> Result<(), ErrorSend> {
+        let channel = self.get_channel().await?;
+        let payload = serde_json::to_vec(message).map_err(std::io::Error::from)?;
+        let result = channel
+            .basic_publish(
+                "",
+                &self.queue,
+                BasicPublishOptions::default(),
+                &payload,
+                get_properties(message),
+            )
+            .await;
+        let confirmation = match result {
+            Ok(v) => v.await,
+            Err(e) => Err(e),
+        };
+        match confirmation {
+            Ok(v) if v.is_ack() => Ok(()),
+            Ok(_) => Err(ErrorSend::Nack),
+            Err(e) => {
+                // Reconnect on next message
+                *self.connection.lock().await = None;
+                Err(e.into())
+            }
+        }
+    }
+}
+
+/// Generates persistent AMQP properties with the event header of the message.
+fn get_properties(message: &Message) -> BasicProperties {
+    let mut headers = FieldTable::default();
+    headers.insert(
+        ShortString::from(EVENT_HEADER_KEY),
+        AMQPValue::LongString(LongString::from(message.template.as_bytes())),
+    );
+    BasicProperties::default()
+        .with_content_type(ShortString::from("application/json"))
+        .with_message_id(ShortString::from(message.id.to_string()))
+        .with_delivery_mode(2)
+        .with_headers(headers)
+}
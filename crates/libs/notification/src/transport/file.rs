@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use super::{ErrorSend, Transport};
+use crate::Message;
+
+/// Writes every message as a JSON file (`<id>.json`) of a directory, for local development and
+/// tests.
+pub struct File {
+    dir: PathBuf,
+}
+
+impl File {
+    /// Creates the transport, the directory is created on the first message.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the path of the file of the message.
+    pub fn get_path(&self, message: &Message) -> PathBuf {
+        self.dir.join(format!("{}.json", message.id))
+    }
+}
+
+#[async_trait]
+impl Transport for File {
+    async fn send(&self, message: &Message) -> Result<(), ErrorSend> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let content = serde_json::to_vec_pretty(message).map_err(std::io::Error::from)?;
+        tokio::fs::write(self.get_path(message), content).await?;
+        Ok(())
+    }
+}
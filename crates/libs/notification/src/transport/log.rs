@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use super::{ErrorSend, Transport};
+use crate::Message;
+
+/// Writes the messages into the log, for local development.
+///
+/// The whole text body is logged (with the tokens it carries), it must not be used in
+/// production.
+pub struct Log;
+
+#[async_trait]
+impl Transport for Log {
+    async fn send(&self, message: &Message) -> Result<(), ErrorSend> {
+        log::info!(
+            "Notification {} to {} ({}): {}\n{}",
+            message.id,
+            message.recipient,
+            message.locale,
+            message.subject,
+            message.body_text
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox, MultiPart, SinglePart},
+    AsyncSmtpTransport, AsyncTransport, Message as Email, Tokio1Executor,
+};
+
+use super::{ErrorSend, Transport};
+use crate::{settings::SETTINGS, Message};
+
+/// Sends the messages by email through an SMTP server.
+///
+/// A message with an HTML body is sent as `multipart/alternative` with its text body.
+pub struct Smtp {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    from: String,
+}
+
+impl Smtp {
+    /// Creates the transport from `NOTIFICATION_SMTP_URL` and `NOTIFICATION_SMTP_FROM`.
+    pub fn new() -> Self {
+        Self::with_url(&SETTINGS.smtp.url, &SETTINGS.smtp.from).unwrap()
+    }
+
+    /// Creates the transport of a connection URL (`smtp://` or `smtps://`, with credentials).
+    pub fn with_url(url: &str, from: &str) -> Result<Self, ErrorSend> {
+        Ok(Self {
+            mailer: AsyncSmtpTransport::<Tokio1Executor>::from_url(url)?.build(),
+            from: from.to_owned(),
+        })
+    }
+
+    /// Builds the email of the message.
+    pub fn get_email(&self, message: &Message) -> Result<Email, ErrorSend> {
+        let from: Mailbox = self
+            .from
+            .parse()
+            .map_err(|_| ErrorSend::InvalidMessage(self.from.to_owned()))?;
+        let to: Mailbox = message
+            .recipient
+            .parse()
+            .map_err(|_| ErrorSend::InvalidMessage(message.recipient.to_owned()))?;
+        let builder = Email::builder()
+            .message_id(Some(format!("<{}@rbca>", message.id)))
+            .from(from)
+            .to(to)
+            .subject(message.subject.to_owned());
+        let text = SinglePart::builder()
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body_text.to_owned());
+        let email = match &message.body_html {
+            Some(v) => builder.multipart(
+                MultiPart::alternative().singlepart(text).singlepart(
+                    SinglePart::builder()
+                        .header(ContentType::TEXT_HTML)
+                        .body(v.to_owned()),
+                ),
+            ),
+            None => builder.singlepart(text),
+        };
+        email.map_err(|e| ErrorSend::InvalidMessage(e.to_string()))
+    }
+}
+
+impl Default for Smtp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Transport for Smtp {
+    async fn send(&self, message: &Message) -> Result<(), ErrorSend> {
+        self.mailer.send(self.get_email(message)?).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+use async_trait::async_trait;
+use notification_lib::{
+    store::NotificationStore,
+    transport::{ErrorSend, File},
+    Dispatcher, Message, Transport,
+};
+use time::{Duration, OffsetDateTime};
+use util_lib::retry::RetryPolicy;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+enum State {
+    Pending,
+    Sent,
+    Failed,
+}
+
+/// Store keeping the messages in memory with the time of their next attempt or of their sending,
+/// every pending message is due.
+#[derive(Default)]
+struct MemoryStore {
+    messages: Mutex<Vec<(Message, State, Option<OffsetDateTime>)>>,
+}
+
+impl MemoryStore {
+    fn get(&self, id: Uuid) -> (Message, State, Option<OffsetDateTime>) {
+        self.messages
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|v| v.0.id == id)
+            .unwrap()
+            .clone()
+    }
+}
+
+#[async_trait]
+impl NotificationStore for MemoryStore {
+    type Error = ();
+
+    async fn add(&self, message: &Message) -> Result<(), ()> {
+        self.messages
+            .lock()
+            .unwrap()
+            .push((message.clone(), State::Pending, None));
+        Ok(())
+    }
+
+    async fn claim_due(&self, limit: u64, _lease_sec: u64) -> Result<Vec<Message>, ()> {
+        Ok(self
+            .messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|v| v.1 == State::Pending)
+            .take(limit as usize)
+            .map(|v| v.0.clone())
+            .collect())
+    }
+
+    async fn mark_sent(&self, id: Uuid) -> Result<(), ()> {
+        let mut messages = self.messages.lock().unwrap();
+        let message = messages.iter_mut().find(|v| v.0.id == id).unwrap();
+        message.0.body_text = String::new();
+        message.0.body_html = None;
+        message.1 = State::Sent;
+        message.2 = Some(OffsetDateTime::now_utc());
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        _error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), ()> {
+        let mut messages = self.messages.lock().unwrap();
+        let message = messages.iter_mut().find(|v| v.0.id == id).unwrap();
+        message.0.attempts += 1;
+        message.2 = retry_at;
+        if retry_at.is_none() {
+            message.0.body_text = String::new();
+            message.0.body_html = None;
+            message.1 = State::Failed;
+        }
+        Ok(())
+    }
+
+    async fn delete_sent(&self, before: OffsetDateTime) -> Result<u64, ()> {
+        let mut messages = self.messages.lock().unwrap();
+        let count = messages.len();
+        messages.retain(|v| v.1 != State::Sent || v.2.unwrap() >= before);
+        Ok((count - messages.len()) as u64)
+    }
+}
+
+/// Transport failing the first `failures` sends.
+struct FailingTransport {
+    failures: usize,
+    retryable: bool,
+    sends: AtomicUsize,
+}
+
+#[async_trait]
+impl Transport for FailingTransport {
+    async fn send(&self, _message: &Message) -> Result<(), ErrorSend> {
+        if self.sends.fetch_add(1, Ordering::SeqCst) >= self.failures {
+            return Ok(());
+        }
+        match self.retryable {
+            true => Err(ErrorSend::Nack),
+            false => Err(ErrorSend::InvalidMessage("recipient".to_string())),
+        }
+    }
+}
+
+fn get_message() -> Message {
+    Message {
+        id: Uuid::new_v4(),
+        template: "greeting".to_string(),
+        locale: "en".to_string(),
+        recipient: "user@example.com".to_string(),
+        subject: "Hello".to_string(),
+        body_text: "Hello User".to_string(),
+        body_html: None,
+        attempts: 0,
+    }
+}
+
+async fn get_dispatcher(
+    failures: usize,
+    retryable: bool,
+    message: &Message,
+) -> Dispatcher<MemoryStore> {
+    let store = MemoryStore::default();
+    store.add(message).await.unwrap();
+    let transport = FailingTransport {
+        failures,
+        retryable,
+        sends: AtomicUsize::new(0),
+    };
+    Dispatcher::new(store, Box::new(transport)).with_retry(RetryPolicy {
+        max_attempts: 3,
+        backoff_base_sec: 30,
+        backoff_max_sec: 3600,
+    })
+}
+
+#[tokio::test]
+async fn failed_message_is_retried_with_backoff() {
+    let message = get_message();
+    let dispatcher = get_dispatcher(1, true, &message).await;
+
+    let before = OffsetDateTime::now_utc();
+    assert_eq!(dispatcher.dispatch_due().await, 0);
+    let (_, state, retry_at) = dispatcher.get_store().get(message.id);
+    assert_eq!(state, State::Pending);
+    assert!(retry_at.unwrap() >= before + time::Duration::seconds(30));
+
+    assert_eq!(dispatcher.dispatch_due().await, 1);
+    let (stored, state, _) = dispatcher.get_store().get(message.id);
+    assert_eq!(state, State::Sent);
+    assert_eq!(stored.attempts, 1);
+}
+
+#[tokio::test]
+async fn message_is_given_up_after_max_attempts() {
+    let message = get_message();
+    let dispatcher = get_dispatcher(usize::MAX, true, &message).await;
+    for _ in 0..3 {
+        dispatcher.dispatch_due().await;
+    }
+    let (stored, state, retry_at) = dispatcher.get_store().get(message.id);
+    assert_eq!(state, State::Failed);
+    assert_eq!(stored.attempts, 3);
+    assert_eq!(retry_at, None);
+    assert_eq!(dispatcher.dispatch_due().await, 0);
+}
+
+#[tokio::test]
+async fn invalid_message_is_not_retried() {
+    let message = get_message();
+    let dispatcher = get_dispatcher(1, false, &message).await;
+    dispatcher.dispatch_due().await;
+    let (stored, state, _) = dispatcher.get_store().get(message.id);
+    assert_eq!(state, State::Failed);
+    assert_eq!(stored.attempts, 1);
+}
+
+#[tokio::test]
+async fn sent_message_is_removed_after_retention() {
+    let message = get_message();
+    let dispatcher = get_dispatcher(0, true, &message).await;
+    assert_eq!(dispatcher.dispatch_due().await, 1);
+    let (stored, _, _) = dispatcher.get_store().get(message.id);
+    assert_eq!(stored.body_text, "");
+
+    let old = get_message();
+    let pending = get_message();
+    let now = OffsetDateTime::now_utc();
+    dispatcher.get_store().messages.lock().unwrap().extend([
+        (old.to_owned(), State::Sent, Some(now - Duration::days(30))),
+        (
+            pending.to_owned(),
+            State::Pending,
+            Some(now - Duration::days(30)),
+        ),
+    ]);
+
+    dispatcher.delete_sent().await;
+    let messages = dispatcher.get_store().messages.lock().unwrap();
+    assert!(!messages.iter().any(|v| v.0.id == old.id));
+    assert!(messages.iter().any(|v| v.0.id == message.id));
+    assert!(messages.iter().any(|v| v.0.id == pending.id));
+}
+
+#[tokio::test]
+async fn file_transport_writes_message_as_json() {
+    let dir = std::env::temp_dir().join(format!("notification-{}", Uuid::new_v4()));
+    let transport = File::new(&dir);
+    let message = get_message();
+    transport.send(&message).await.unwrap();
+
+    let content = std::fs::read_to_string(transport.get_path(&message)).unwrap();
+    assert_eq!(serde_json::from_str::<Message>(&content).unwrap(), message);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,83 @@
+use notification_lib::{
+    template::{ErrorRender, Part},
+    Notification, Templates,
+};
+use serde_json::json;
+
+fn get_notification(locale: Option<&str>) -> Notification {
+    Notification {
+        template: "user-password-reset".to_string(),
+        recipient: "user@example.com".to_string(),
+        locale: locale.map(|v| v.to_string()),
+        context: json!({
+            "name": "<User>",
+            "token": "token",
+            "expires_at": "2026-10-18T00:00:00Z",
+        }),
+    }
+}
+
+#[test]
+fn locale_falls_back_to_language_then_default() {
+    let templates = Templates::with_builtin("en");
+    assert_eq!(
+        templates.get_locale("user-password-reset", Some("ru_RU")),
+        Some("ru".to_string())
+    );
+    assert_eq!(
+        templates.get_locale("user-password-reset", Some("de-DE")),
+        Some("en".to_string())
+    );
+    assert_eq!(
+        templates.get_locale("user-password-reset", None),
+        Some("en".to_string())
+    );
+    assert_eq!(templates.get_locale("unknown", Some("en")), None);
+
+    let message = templates.render(&get_notification(Some("RU"))).unwrap();
+    assert_eq!(message.locale, "ru");
+    assert_eq!(message.recipient, "user@example.com");
+    assert_eq!(message.attempts, 0);
+}
+
+#[test]
+fn only_html_body_is_escaped() {
+    let message = Templates::with_builtin("en")
+        .render(&get_notification(None))
+        .unwrap();
+    assert!(message.body_text.contains("Hello <User>,"));
+    assert!(message.body_html.unwrap().contains("Hello &lt;User&gt;,"));
+}
+
+#[test]
+fn subject_is_one_line_and_html_is_optional() {
+    let mut templates = Templates::new("en");
+    templates
+        .add(
+            "en",
+            "greeting",
+            Part::Subject,
+            "\n  Hello\n{{ name }}  \n".to_string(),
+        )
+        .unwrap();
+    templates
+        .add("en", "greeting", Part::Text, "Hello {{ name }}".to_string())
+        .unwrap();
+    let message = templates
+        .render(&Notification {
+            template: "greeting".to_string(),
+            recipient: "user@example.com".to_string(),
+            locale: None,
+            context: json!({"name": "User"}),
+        })
+        .unwrap();
+    assert_eq!(message.subject, "Hello User");
+    assert_eq!(message.body_text, "Hello User");
+    assert_eq!(message.body_html, None);
+}
+
+#[test]
+fn missing_template_is_an_error() {
+    let result = Templates::new("en").render(&get_notification(None));
+    assert!(matches!(result, Err(ErrorRender::TemplateNotFound(v)) if v == "user-password-reset"));
+}
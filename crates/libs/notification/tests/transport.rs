@@ -0,0 +1,11 @@
+use notification_lib::transport::{get_default_transport, ErrorTransport};
+
+#[test]
+fn unknown_transport_is_refused() {
+    // The settings are read once, the only test of this binary sets them
+    std::env::set_var("NOTIFICATION_TRANSPORT", "pigeon");
+    match get_default_transport() {
+        Err(ErrorTransport::UnknownTransport(v)) => assert_eq!(v, "pigeon"),
+        _ => panic!("unknown transport must be refused"),
+    }
+}
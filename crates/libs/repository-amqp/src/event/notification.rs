@@ -0,0 +1,9 @@
+use crate::settings::SETTINGS;
+
+/// Returns the name of the queue the notifications to send to the users are published to.
+///
+/// The AMQP transport of the notification library publishes the rendered messages on it, for a
+/// mailer outside of the application.
+pub fn get_queue() -> String {
+    SETTINGS.notification.queue.to_owned()
+}
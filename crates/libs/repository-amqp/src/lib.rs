@@ -1,10 +1,9 @@
 pub mod event;
+pub mod outbox;
 pub mod settings;
 
-use adapter_lib::amqp::get_connection;
 use lapin::{
-    options::{BasicPublishOptions, QueueDeclareOptions},
-    types::{FieldTable, ShortString},
+    options::BasicPublishOptions, publisher_confirm::Confirmation, types::ShortString,
     BasicProperties, Channel,
 };
 use uuid::Uuid;
@@ -80,36 +79,6 @@ pub(crate) fn get_properties(content_type: ContentType) -> BasicProperties {
     properties
 }
 
-/// Opens a channel and declares a queue.
-///
-/// # Arguments
-/// * `queue_name` - The name of the queue.
-/// * `options` - Options for queue declaration.
-///
-/// # Returns
-/// A `Channel` instance.
-///
-/// # Example
-/// ```rust
-/// use your_crate::get_channel;
-/// use lapin::options::QueueDeclareOptions;
-///
-/// #[tokio::main]
-/// async fn main() {
-///     let channel = get_channel(&"my_queue".to_string(), QueueDeclareOptions::default()).await;
-///     // Channel is ready to use
-/// }
-/// ```
-pub(crate) async fn get_channel(queue_name: &String, options: QueueDeclareOptions) -> Channel {
-    let connection = get_connection().await;
-    let channel = connection.create_channel().await.unwrap();
-    channel
-        .queue_declare(queue_name, options, FieldTable::default())
-        .await
-        .unwrap();
-    channel
-}
-
 /// Publishes a message to a specified exchange and queue.
 ///
 /// # Arguments
@@ -119,21 +88,25 @@ pub(crate) async fn get_channel(queue_name: &String, options: QueueDeclareOption
 /// * `queue_name` - The name of the queue.
 /// * `properties` - Message properties.
 ///
+/// # Returns
+/// The confirmation of the broker (`Confirmation::NotRequested` if the channel isn't in
+/// confirm mode), or the error of the broker.
+///
 /// # Example
 /// ```rust
-/// use your_crate::{get_channel, publish, get_properties, ContentType};
-/// use lapin::options::QueueDeclareOptions;
+/// use your_crate::{publish, get_properties, ContentType};
 ///
 /// #[tokio::main]
 /// async fn main() {
 ///     let queue_name = "my_queue".to_string();
 ///     let exchange_name = "".to_string(); // Default exchange
-///     let channel = get_channel(&queue_name, QueueDeclareOptions::default()).await;
 ///     let properties = get_properties(ContentType::Text);
 ///     let message = "Hello, world!".to_string();
 ///
-///     publish(&message, &channel, &exchange_name, &queue_name, properties).await;
-///     // Message published successfully
+///     let confirmation = publish(&message, &channel, &exchange_name, &queue_name, properties)
+///         .await
+///         .unwrap();
+///     assert!(confirmation.is_ack());
 /// }
 /// ```
 pub(crate) async fn publish(
@@ -142,7 +115,7 @@ pub(crate) async fn publish(
     exchange_name: &String,
     queue_name: &String,
     properties: BasicProperties,
-) {
+) -> Result<Confirmation, lapin::Error> {
     channel
         .basic_publish(
             exchange_name,
@@ -151,8 +124,6 @@ pub(crate) async fn publish(
             content.as_bytes(),
             properties,
         )
+        .await?
         .await
-        .unwrap()
-        .await
-        .unwrap();
 }
@@ -0,0 +1,300 @@
+use std::{collections::HashSet, fmt, time::Duration};
+
+use adapter_lib::amqp::connect;
+use async_trait::async_trait;
+use lapin::{
+    options::{ConfirmSelectOptions, QueueDeclareOptions},
+    types::{AMQPValue, FieldTable, LongString, ShortString},
+    BasicProperties, Channel, Connection,
+};
+use time::OffsetDateTime;
+use util_lib::retry::RetryPolicy;
+use uuid::Uuid;
+
+use crate::{get_properties as get_base_properties, publish, settings::SETTINGS, ContentType};
+
+/// Header key holding the name of the event of a message.
+pub const EVENT_HEADER_KEY: &str = "x-event";
+
+/// A message written into the outbox and waiting to be published.
+#[derive(Debug, Clone)]
+pub struct OutboxMessage {
+    /// Identifier of the message, sent as AMQP `message_id` so consumers can deduplicate it.
+    pub id: Uuid,
+    /// Name of the queue the message is published to.
+    pub queue: String,
+    /// Name of the event, sent in the [`EVENT_HEADER_KEY`] header.
+    pub event: String,
+    /// JSON content of the message.
+    pub payload: String,
+    /// Number of failed attempts to publish the message.
+    pub attempts: u32,
+}
+
+/// Storage of the outbox messages read by the [`Relay`].
+///
+/// The outbox is written in the same database transaction as the change emitting the event, so an
+/// event exists only if its change is committed.
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    type Error: fmt::Debug + Send;
+
+    /// Returns the oldest messages due to be published and postpones their next attempt by
+    /// `lease_sec`, so concurrent relays don't publish them twice.
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of messages to return.
+    /// * `lease_sec` - Time the messages are held, they are due again after it if the relay
+    ///   stops before marking them.
+    async fn claim_pending(
+        &self,
+        limit: u64,
+        lease_sec: u64,
+    ) -> Result<Vec<OutboxMessage>, Self::Error>;
+
+    /// Marks the message as published.
+    async fn mark_sent(&self, id: Uuid) -> Result<(), Self::Error>;
+
+    /// Records a failed attempt to publish the message.
+    ///
+    /// # Arguments
+    /// * `error` - The error of the broker.
+    /// * `retry_at` - The time of the next attempt, `None` to give the message up (dead letter).
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), Self::Error>;
+
+    /// Removes the messages published before the given time.
+    ///
+    /// # Returns
+    /// The number of removed messages.
+    async fn delete_sent(&self, before: OffsetDateTime) -> Result<u64, Self::Error>;
+}
+
+/// Error to publish an outbox message.
+#[derive(Debug)]
+pub enum ErrorPublish {
+    /// The broker can't be reached or closed the channel.
+    Amqp(lapin::Error),
+    /// The broker refused the message (negative publisher confirm).
+    Nack,
+}
+
+impl fmt::Display for ErrorPublish {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Amqp(e) => write!(f, "amqp error: {}", e),
+            Self::Nack => write!(f, "message refused by the broker"),
+        }
+    }
+}
+
+impl From<lapin::Error> for ErrorPublish {
+    fn from(e: lapin::Error) -> Self {
+        Self::Amqp(e)
+    }
+}
+
+/// Task publishing the pending messages of an outbox.
+///
+/// The relay uses its own AMQP connection with publisher confirms: a message is marked as sent
+/// only after the broker acknowledged it. Messages are published at least once, a message can be
+/// published again if it can't be marked as sent. On a broker error the relay reconnects on the
+/// next poll.
+///
+/// A message which can't be published is retried with an exponential backoff, and given up (kept
+/// as a dead letter) after `AMQP_OUTBOX_MAX_ATTEMPTS` attempts. The messages are claimed with a
+/// lease, so several relays can share an outbox, and the published messages are removed after
+/// `AMQP_OUTBOX_SENT_RETENTION_SEC`.
+///
+/// # Example
+/// ```rust,ignore
+/// tokio::spawn(Relay::new(OutboxRepository::new().await).run());
+/// ```
+pub struct Relay<S: OutboxStore> {
+    store: S,
+    connection: Option<(Connection, Channel)>,
+    declared_queues: HashSet<String>,
+    retry: RetryPolicy,
+}
+
+impl<S: OutboxStore> Relay<S> {
+    /// Creates a new relay reading the messages from the store.
+    pub fn new(store: S) -> Self {
+        let settings = &SETTINGS.outbox;
+        Self {
+            store,
+            connection: None,
+            declared_queues: HashSet::new(),
+            retry: RetryPolicy {
+                max_attempts: settings.max_attempts,
+                backoff_base_sec: settings.backoff_base_sec,
+                backoff_max_sec: settings.backoff_max_sec,
+            },
+        }
+    }
+
+    /// Replaces the retry policy of the settings.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn get_store(&self) -> &S {
+        &self.store
+    }
+
+    /// Publishes the pending messages forever, polling the outbox every
+    /// `AMQP_OUTBOX_INTERVAL_MS` milliseconds.
+    pub async fn run(mut self) {
+        let interval = Duration::from_millis(SETTINGS.outbox.interval_ms);
+        loop {
+            self.relay_pending().await;
+            self.delete_sent().await;
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Publishes one batch (`AMQP_OUTBOX_BATCH_SIZE`) of pending messages in creation order.
+    ///
+    /// A failed message is postponed and the next ones are published, except on a broker error:
+    /// the rest of the batch is due again after the lease.
+    ///
+    /// # Returns
+    /// The number of published messages.
+    pub async fn relay_pending(&mut self) -> usize {
+        let settings = &SETTINGS.outbox;
+        let messages = match self
+            .store
+            .claim_pending(settings.batch_size, settings.lease_sec)
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("Outbox: failed to get pending messages: {:?}", e);
+                return 0;
+            }
+        };
+
+        let mut published: usize = 0;
+        for message in messages {
+            let (result, is_broker_error) = match self.publish(&message).await {
+                Ok(_) => {
+                    published += 1;
+                    (self.store.mark_sent(message.id).await, false)
+                }
+                Err(e) => {
+                    let attempts = message.attempts + 1;
+                    let retry_at = self.retry.get_retry_at(attempts, true);
+                    log::error!(
+                        "Outbox: failed to publish message {} (attempt {}, {}): {}",
+                        message.id,
+                        attempts,
+                        match retry_at {
+                            Some(_) => "retried",
+                            None => "given up",
+                        },
+                        e
+                    );
+                    let is_broker_error = matches!(e, ErrorPublish::Amqp(_));
+                    if is_broker_error {
+                        // Reconnect on next poll
+                        self.connection = None;
+                    }
+                    (
+                        self.store
+                            .mark_failed(message.id, &e.to_string(), retry_at)
+                            .await,
+                        is_broker_error,
+                    )
+                }
+            };
+            if let Err(e) = result {
+                log::error!("Outbox: failed to mark message {}: {:?}", message.id, e);
+            }
+            if is_broker_error {
+                break;
+            }
+        }
+        published
+    }
+
+    /// Removes the messages published for more than `AMQP_OUTBOX_SENT_RETENTION_SEC`.
+    pub async fn delete_sent(&self) {
+        let before = OffsetDateTime::now_utc()
+            - time::Duration::seconds(SETTINGS.outbox.sent_retention_sec as i64);
+        if let Err(e) = self.store.delete_sent(before).await {
+            log::error!("Outbox: failed to delete sent messages: {:?}", e);
+        }
+    }
+
+    /// Publishes the message and waits for the confirmation of the broker.
+    async fn publish(&mut self, message: &OutboxMessage) -> Result<(), ErrorPublish> {
+        let channel = self.get_channel().await?;
+
+        // Queues are declared once per connection
+        if !self.declared_queues.contains(&message.queue) {
+            let options = QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            };
+            channel
+                .queue_declare(&message.queue, options, FieldTable::default())
+                .await?;
+            self.declared_queues.insert(message.queue.to_owned());
+        }
+
+        let confirmation = publish(
+            &message.payload,
+            &channel,
+            &"".to_string(),
+            &message.queue,
+            get_properties(message),
+        )
+        .await?;
+        if !confirmation.is_ack() {
+            return Err(ErrorPublish::Nack);
+        }
+        Ok(())
+    }
+
+    /// Returns the confirm mode channel of the relay, opening a new connection if needed.
+    async fn get_channel(&mut self) -> Result<Channel, lapin::Error> {
+        if let Some((connection, channel)) = &self.connection {
+            if connection.status().connected() && channel.status().connected() {
+                return Ok(channel.clone());
+            }
+        }
+
+        let connection = connect().await?;
+        let channel = connection.create_channel().await?;
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await?;
+        self.declared_queues.clear();
+        self.connection = Some((connection, channel.clone()));
+        Ok(channel)
+    }
+}
+
+/// Generates persistent AMQP properties with the event headers of the message.
+fn get_properties(message: &OutboxMessage) -> BasicProperties {
+    let properties = get_base_properties(ContentType::Json);
+    let mut headers = FieldTable::default();
+    headers.insert(
+        ShortString::from("x-services"),
+        AMQPValue::LongString(LongString::from("*".as_bytes())),
+    );
+    headers.insert(
+        ShortString::from(EVENT_HEADER_KEY),
+        AMQPValue::LongString(LongString::from(message.event.as_bytes())),
+    );
+
+    properties
+        .with_message_id(ShortString::from(message.id.to_string()))
+        .with_delivery_mode(2)
+        .with_headers(headers)
+}
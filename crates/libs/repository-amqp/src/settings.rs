@@ -3,10 +3,14 @@ use once_cell::sync::Lazy;
 
 pub(crate) static SETTINGS: Lazy<Settings> = Lazy::new(|| Settings {
     user_event: UserEvent::from_env().unwrap(),
+    notification: Notification::from_env().unwrap(),
+    outbox: Outbox::from_env().unwrap(),
 });
 
 pub(crate) struct Settings {
     pub user_event: UserEvent,
+    pub notification: Notification,
+    pub outbox: Outbox,
 }
 
 #[derive(EnvSettings)]
@@ -15,3 +19,35 @@ pub(crate) struct UserEvent {
     #[env_settings(default = "userv_event_queue")]
     pub queue: String,
 }
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "AMQP_NOTIFICATION_")]
+pub(crate) struct Notification {
+    #[env_settings(default = "notification_queue")]
+    pub queue: String,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "AMQP_OUTBOX_")]
+pub(crate) struct Outbox {
+    /// Delay between two polls of the outbox (in milliseconds).
+    #[env_settings(default = 1000)]
+    pub interval_ms: u64,
+    /// Maximum number of messages published by one poll.
+    #[env_settings(default = 100)]
+    pub batch_size: u64,
+    /// Attempts to publish a message before it is given up (dead letter).
+    #[env_settings(default = 20)]
+    pub max_attempts: u32,
+    /// Delay after the first failed publish, doubled on every next failure.
+    #[env_settings(default = 5)]
+    pub backoff_base_sec: u64,
+    #[env_settings(default = 3600)]
+    pub backoff_max_sec: u64,
+    /// Time a relay holds the messages it is publishing, other relays skip them.
+    #[env_settings(default = 60)]
+    pub lease_sec: u64,
+    /// Time the published messages are kept before they are removed.
+    #[env_settings(default = 604800)]
+    pub sent_retention_sec: u64,
+}
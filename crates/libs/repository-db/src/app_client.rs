@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+pub use entity_lib::app_client as app_client_entity;
+use sea_orm::{Condition, DbErr};
+
+use crate::{builder::QueryBuilder, connection::Connection};
+pub use crate::{DeletedMode, Repository, SoftDelete};
+
+pub struct AppClient {
+    db: Connection,
+    deleted_mode: DeletedMode,
+}
+
+impl QueryBuilder<app_client_entity::Entity> for AppClient {}
+
+#[async_trait]
+impl Repository<app_client_entity::Entity> for AppClient {
+    async fn new() -> Self {
+        Self {
+            db: Connection::get_pool().await,
+            deleted_mode: DeletedMode::default(),
+        }
+    }
+
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
+    }
+
+    fn get_default_filter(&self) -> Condition {
+        self.get_deleted_filter()
+    }
+
+    async fn delete(&self, filter: Condition) -> Result<(), DbErr> {
+        self.soft_delete(filter).await
+    }
+}
+
+impl SoftDelete<app_client_entity::Entity> for AppClient {
+    fn get_deleted_column() -> app_client_entity::Column {
+        app_client_entity::Column::IsDeleted
+    }
+
+    fn get_deleted_mode(&self) -> DeletedMode {
+        self.deleted_mode
+    }
+
+    fn set_deleted_mode(&mut self, mode: DeletedMode) {
+        self.deleted_mode = mode;
+    }
+}
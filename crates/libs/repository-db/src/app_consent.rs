@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+pub use entity_lib::app_consent as app_consent_entity;
+
+pub use crate::Repository;
+use crate::{builder::QueryBuilder, connection::Connection};
+
+pub struct AppConsent {
+    db: Connection,
+}
+
+impl QueryBuilder<app_consent_entity::Entity> for AppConsent {}
+
+#[async_trait]
+impl Repository<app_consent_entity::Entity> for AppConsent {
+    async fn new() -> Self {
+        Self {
+            db: Connection::get_pool().await,
+        }
+    }
+
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
+    }
+}
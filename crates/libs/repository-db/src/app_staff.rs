@@ -1,23 +1,59 @@
-use adapter_lib::db;
 use async_trait::async_trait;
 pub use entity_lib::app_staff as app_staff_entity;
-use sea_orm::DatabaseConnection;
+use sea_orm::{ColumnTrait, Condition, DbErr};
+use uuid::Uuid;
 
 pub use crate::Repository;
+use crate::{builder::QueryBuilder, connection::Connection};
 
 pub struct AppStaff {
-    db: &'static DatabaseConnection,
+    db: Connection,
 }
 
+impl AppStaff {
+    /// Retrieves the permissions a user holds on an application.
+    ///
+    /// # Returns
+    /// - `Ok(Some(permissions))` if the user is a staff member of the application.
+    /// - `Ok(None)` if the user isn't a staff member of the application.
+    ///
+    /// # Example
+    /// ```rust
+    /// let rep = AppStaff::new().await;
+    /// if let Some(perms) = rep.get_permissions(application_id, user_id).await? {
+    ///     let can_read = perms.contains(&app_staff_entity::AppStaffPermissions::ReadKey);
+    /// }
+    /// ```
+    pub async fn get_permissions(
+        &self,
+        application_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<Vec<app_staff_entity::AppStaffPermissions>>, DbErr> {
+        let filter = Condition::all()
+            .add(app_staff_entity::Column::ApplicationId.eq(application_id))
+            .add(app_staff_entity::Column::UserId.eq(user_id));
+        match self.get_one(Some(filter)).await {
+            Ok(v) => Ok(v.map(|model| model.permissions)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl QueryBuilder<app_staff_entity::Entity> for AppStaff {}
+
 #[async_trait]
 impl Repository<app_staff_entity::Entity> for AppStaff {
     async fn new() -> Self {
         Self {
-            db: db::get_connection().await,
+            db: Connection::get_pool().await,
         }
     }
 
-    async fn get_db(&self) -> &'static DatabaseConnection {
-        self.db
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
     }
 }
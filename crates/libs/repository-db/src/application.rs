@@ -1,17 +1,14 @@
-use adapter_lib::db;
 use async_trait::async_trait;
 pub use entity_lib::{app_staff as app_staff_entity, application as application_entity};
 use orm_util_lib::{get_limit, get_offset};
-use sea_orm::{
-    prelude::Expr, Condition, DatabaseConnection, DbErr, EntityTrait, JoinType, PaginatorTrait,
-    QueryFilter, QuerySelect, RelationTrait,
-};
+use sea_orm::{Condition, DbErr, JoinType, PaginatorTrait, QuerySelect, RelationTrait};
 
-use crate::builder::QueryBuilder;
-pub use crate::Repository;
+use crate::{builder::QueryBuilder, connection::Connection};
+pub use crate::{DeletedMode, Repository, SoftDelete};
 
 pub struct Application {
-    db: &'static DatabaseConnection,
+    db: Connection,
+    deleted_mode: DeletedMode,
 }
 
 impl Application {
@@ -23,6 +20,7 @@ impl Application {
     ) -> Result<(Vec<application_entity::Model>, i64, u64, u64), DbErr> {
         let limit = get_limit(limit);
         let offset = get_offset(offset);
+        let filter = Some(self.get_read_filter(filter));
 
         let db = self.get_db().await;
 
@@ -60,24 +58,38 @@ impl QueryBuilder<application_entity::Entity> for Application {}
 impl Repository<application_entity::Entity> for Application {
     async fn new() -> Self {
         Self {
-            db: db::get_connection().await,
+            db: Connection::get_pool().await,
+            deleted_mode: DeletedMode::default(),
         }
     }
 
-    async fn get_db(&self) -> &'static DatabaseConnection {
-        self.db
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
+    }
+
+    fn get_default_filter(&self) -> Condition {
+        self.get_deleted_filter()
     }
 
     async fn delete(&self, filter: Condition) -> Result<(), DbErr> {
-        let db = self.get_db().await;
-        match application_entity::Entity::update_many()
-            .col_expr(application_entity::Column::IsDeleted, Expr::value(true))
-            .filter(filter)
-            .exec(db)
-            .await
-        {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
-        }
+        self.soft_delete(filter).await
+    }
+}
+
+impl SoftDelete<application_entity::Entity> for Application {
+    fn get_deleted_column() -> application_entity::Column {
+        application_entity::Column::IsDeleted
+    }
+
+    fn get_deleted_mode(&self) -> DeletedMode {
+        self.deleted_mode
+    }
+
+    fn set_deleted_mode(&mut self, mode: DeletedMode) {
+        self.deleted_mode = mode;
     }
 }
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use adapter_lib::db;
+use async_trait::async_trait;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr, ExecResult,
+    QueryResult, Statement, TransactionTrait,
+};
+use tokio::sync::Mutex;
+
+/// Shared transaction, taken out (`None`) once committed or rolled back.
+type SharedTxn = Arc<Mutex<Option<DatabaseTransaction>>>;
+
+/// Connection used by a repository to run its queries.
+///
+/// A repository uses the static connection pool by default and can be switched to the
+/// transaction of a [`UnitOfWork`] (see [`crate::Repository::with_txn`]).
+#[derive(Clone)]
+pub enum Connection {
+    /// Queries run directly on the connection pool.
+    Pool(&'static DatabaseConnection),
+    /// Queries run inside the transaction of a unit of work.
+    Txn(SharedTxn, DbBackend),
+}
+
+impl Connection {
+    /// Returns a connection to the static connection pool.
+    pub async fn get_pool() -> Self {
+        Self::Pool(db::get_connection().await)
+    }
+
+    /// Returns whether the queries run inside the transaction of a unit of work.
+    pub fn is_txn(&self) -> bool {
+        matches!(self, Self::Txn(..))
+    }
+}
+
+fn txn_closed_err() -> DbErr {
+    DbErr::Custom("unit of work transaction is allready closed".to_string())
+}
+
+#[async_trait]
+impl ConnectionTrait for Connection {
+    fn get_database_backend(&self) -> DbBackend {
+        match self {
+            Self::Pool(conn) => conn.get_database_backend(),
+            Self::Txn(_, backend) => *backend,
+        }
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        match self {
+            Self::Pool(conn) => conn.execute(stmt).await,
+            Self::Txn(txn, _) => match txn.lock().await.as_ref() {
+                Some(txn) => txn.execute(stmt).await,
+                None => Err(txn_closed_err()),
+            },
+        }
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        match self {
+            Self::Pool(conn) => conn.execute_unprepared(sql).await,
+            Self::Txn(txn, _) => match txn.lock().await.as_ref() {
+                Some(txn) => txn.execute_unprepared(sql).await,
+                None => Err(txn_closed_err()),
+            },
+        }
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        match self {
+            Self::Pool(conn) => conn.query_one(stmt).await,
+            Self::Txn(txn, _) => match txn.lock().await.as_ref() {
+                Some(txn) => txn.query_one(stmt).await,
+                None => Err(txn_closed_err()),
+            },
+        }
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        match self {
+            Self::Pool(conn) => conn.query_all(stmt).await,
+            Self::Txn(txn, _) => match txn.lock().await.as_ref() {
+                Some(txn) => txn.query_all(stmt).await,
+                None => Err(txn_closed_err()),
+            },
+        }
+    }
+}
+
+/// A database transaction shared by several repositories.
+///
+/// Every repository switched to the unit of work (see [`crate::Repository::with_txn`]) runs its
+/// operations inside the same transaction, so they are all committed or all rolled back. A unit of
+/// work dropped without [`UnitOfWork::commit`] is rolled back.
+///
+/// # Example
+/// ```rust,ignore
+/// let uow = UnitOfWork::begin().await?;
+/// let app_rep = Application::new().await.with_txn(&uow);
+/// let app_staff_rep = AppStaff::new().await.with_txn(&uow);
+///
+/// let app = app_rep.create(new_app).await?;
+/// app_staff_rep.create(new_app_staff).await?;
+/// uow.commit().await?;
+/// ```
+pub struct UnitOfWork {
+    txn: SharedTxn,
+    backend: DbBackend,
+}
+
+impl UnitOfWork {
+    /// Begins a new transaction on the static connection pool.
+    ///
+    /// # Returns
+    /// The unit of work, or the error of the database if the transaction can't begin.
+    pub async fn begin() -> Result<Self, DbErr> {
+        let conn = db::get_connection().await;
+        let txn = conn.begin().await?;
+        Ok(Self {
+            txn: Arc::new(Mutex::new(Some(txn))),
+            backend: conn.get_database_backend(),
+        })
+    }
+
+    /// Returns a connection running the queries inside the transaction.
+    pub fn get_connection(&self) -> Connection {
+        Connection::Txn(self.txn.clone(), self.backend)
+    }
+
+    /// Commits every operation done inside the unit of work.
+    ///
+    /// Repositories still holding the connection of the unit of work fail on the next query.
+    pub async fn commit(self) -> Result<(), DbErr> {
+        match self.txn.lock().await.take() {
+            Some(txn) => txn.commit().await,
+            None => Err(txn_closed_err()),
+        }
+    }
+
+    /// Rolls back every operation done inside the unit of work.
+    ///
+    /// Repositories still holding the connection of the unit of work fail on the next query.
+    pub async fn rollback(self) -> Result<(), DbErr> {
+        match self.txn.lock().await.take() {
+            Some(txn) => txn.rollback().await,
+            None => Err(txn_closed_err()),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use sea_orm::{
+    prelude::Expr, Condition, ConnectionTrait, DbBackend, DbErr, DeriveIden, EntityTrait, Iden,
+    QueryFilter, Statement, UpdateMany, Value,
+};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+/// Entity of the messages sent by a background task (the outbox and the notifications).
+///
+/// A message is claimed with a lease, retried until it is sent or given up, and removed after a
+/// retention once sent. Its table has the columns of [`Delivery`].
+pub(crate) trait DeliveryEntity: EntityTrait {
+    type Status: Into<Value>;
+
+    /// Status of the messages waiting for their next attempt.
+    const PENDING: Self::Status;
+    const SENT: Self::Status;
+    /// Status of the messages given up.
+    const FAILED: Self::Status;
+}
+
+/// Columns shared by the tables of a [`DeliveryEntity`].
+#[derive(DeriveIden)]
+enum Delivery {
+    Id,
+    Status,
+    Attempts,
+    LastError,
+    NextAttemptAt,
+    SentAt,
+    CreatedAt,
+}
+
+/// Returns the oldest messages due to be sent and postpones their next attempt by `lease_sec`,
+/// so concurrent tasks don't send them twice.
+pub(crate) async fn claim<E, C>(db: &C, limit: u64, lease_sec: u64) -> Result<Vec<E::Model>, DbErr>
+where
+    E: DeliveryEntity,
+    C: ConnectionTrait,
+{
+    let now = OffsetDateTime::now_utc();
+    // Rows claimed by a concurrent task are locked, they are skipped
+    let statement = Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"WITH "claimed" AS (
+                UPDATE "{table}" SET "{next_attempt_at}" = $1
+                WHERE "{id}" IN (
+                    SELECT "{id}" FROM "{table}"
+                    WHERE "{status}" = $2 AND "{next_attempt_at}" <= $3
+                    ORDER BY "{created_at}"
+                    LIMIT $4
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING *
+            )
+            SELECT * FROM "claimed" ORDER BY "{created_at}""#,
+            table = E::default().table_name(),
+            id = Delivery::Id.to_string(),
+            status = Delivery::Status.to_string(),
+            next_attempt_at = Delivery::NextAttemptAt.to_string(),
+            created_at = Delivery::CreatedAt.to_string(),
+        ),
+        [
+            (now + Duration::seconds(lease_sec as i64)).into(),
+            E::PENDING.into(),
+            now.into(),
+            (limit as i64).into(),
+        ],
+    );
+    E::find().from_raw_sql(statement).all(db).await
+}
+
+/// Returns the query marking the message as sent.
+pub(crate) fn get_mark_sent_query<E: DeliveryEntity>(id: Uuid) -> UpdateMany<E> {
+    E::update_many()
+        .col_expr(Delivery::Status, Expr::value(E::SENT))
+        .col_expr(Delivery::SentAt, Expr::value(OffsetDateTime::now_utc()))
+        .filter(Expr::col(Delivery::Id).eq(id))
+}
+
+/// Returns the query recording a failed attempt to send the message.
+///
+/// # Arguments
+/// * `retry_at` - The time of the next attempt, `None` to give the message up.
+pub(crate) fn get_mark_failed_query<E: DeliveryEntity>(
+    id: Uuid,
+    error: &str,
+    retry_at: Option<OffsetDateTime>,
+) -> UpdateMany<E> {
+    let query = E::update_many()
+        .col_expr(Delivery::LastError, Expr::value(error.to_string()))
+        .col_expr(Delivery::Attempts, Expr::col(Delivery::Attempts).add(1));
+    match retry_at {
+        Some(v) => query.col_expr(Delivery::NextAttemptAt, Expr::value(v)),
+        None => query.col_expr(Delivery::Status, Expr::value(E::FAILED)),
+    }
+    .filter(Expr::col(Delivery::Id).eq(id))
+}
+
+/// Removes the messages sent before the given time.
+///
+/// # Returns
+/// The number of removed messages.
+pub(crate) async fn delete_sent<E, C>(db: &C, before: OffsetDateTime) -> Result<u64, DbErr>
+where
+    E: DeliveryEntity,
+    C: ConnectionTrait,
+{
+    let result = E::delete_many()
+        .filter(
+            Condition::all()
+                .add(Expr::col(Delivery::Status).eq(E::SENT))
+                .add(Expr::col(Delivery::SentAt).lt(before)),
+        )
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+pub use entity_lib::key as key_entity;
+use sea_orm::{sea_query::Expr, ColumnTrait, Condition, DbErr, EntityTrait, QueryFilter};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{builder::QueryBuilder, connection::Connection};
+pub use crate::{DeletedMode, Repository, SoftDelete};
+
+pub struct Key {
+    db: Connection,
+    deleted_mode: DeletedMode,
+}
+
+impl QueryBuilder<key_entity::Entity> for Key {}
+
+impl Key {
+    /// Activates the key at the given moment, if it isn't activated yet.
+    ///
+    /// The check and the activation are one update, so a key verified concurrently is activated
+    /// once and its lifetime starts from the first verification.
+    ///
+    /// # Returns
+    /// `true` if the key was activated by this call.
+    pub async fn activate(
+        &self,
+        key_id: Uuid,
+        activated_at: OffsetDateTime,
+    ) -> Result<bool, DbErr> {
+        let result = key_entity::Entity::update_many()
+            .col_expr(key_entity::Column::ActivatedAt, Expr::value(activated_at))
+            .col_expr(key_entity::Column::UpdatedAt, Expr::value(activated_at))
+            .filter(key_entity::Column::Id.eq(key_id))
+            .filter(key_entity::Column::ActivatedAt.is_null())
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
+}
+
+#[async_trait]
+impl Repository<key_entity::Entity> for Key {
+    async fn new() -> Self {
+        Self {
+            db: Connection::get_pool().await,
+            deleted_mode: DeletedMode::default(),
+        }
+    }
+
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
+    }
+
+    fn get_default_filter(&self) -> Condition {
+        self.get_deleted_filter()
+    }
+
+    async fn delete(&self, filter: Condition) -> Result<(), DbErr> {
+        self.soft_delete(filter).await
+    }
+}
+
+impl SoftDelete<key_entity::Entity> for Key {
+    fn get_deleted_column() -> key_entity::Column {
+        key_entity::Column::IsDeleted
+    }
+
+    fn get_deleted_mode(&self) -> DeletedMode {
+        self.deleted_mode
+    }
+
+    fn set_deleted_mode(&mut self, mode: DeletedMode) {
+        self.deleted_mode = mode;
+    }
+}
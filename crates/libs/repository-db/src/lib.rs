@@ -1,13 +1,22 @@
+pub mod app_client;
+pub mod app_consent;
 pub mod app_staff;
 pub mod application;
+pub mod connection;
+mod delivery;
+pub mod key;
+pub mod notification;
+pub mod outbox;
 pub mod user;
 
+pub use connection::{Connection, UnitOfWork};
+
 use async_trait::async_trait;
 use orm_util_lib::{get_limit, get_offset};
 use sea_orm::{
-    sea_query::IntoValueTuple, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr,
-    EntityTrait, InsertResult, IntoActiveModel, Iterable, PaginatorTrait, PrimaryKeyToColumn,
-    PrimaryKeyTrait, QueryFilter, QuerySelect, TryGetableMany,
+    sea_query::IntoValueTuple, ActiveModelTrait, ColumnTrait, Condition, DbErr, EntityTrait,
+    InsertResult, IntoActiveModel, Iterable, PaginatorTrait, PrimaryKeyToColumn, PrimaryKeyTrait,
+    QueryFilter, QuerySelect, TryGetableMany,
 };
 
 /// A trait that defines common repository methods for working with entities.
@@ -34,7 +43,43 @@ where
     /// ```
     async fn new() -> Self;
 
-    async fn get_db(&self) -> &'static DatabaseConnection;
+    /// Returns the connection the repository runs its queries on.
+    async fn get_db(&self) -> &Connection;
+
+    /// Replaces the connection the repository runs its queries on.
+    fn set_db(&mut self, db: Connection);
+
+    /// Makes the repository run its operations inside the transaction of the unit of work.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let uow = UnitOfWork::begin().await?;
+    /// let repo = YourRepository::new().await.with_txn(&uow);
+    /// ```
+    fn with_txn(mut self, uow: &UnitOfWork) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_db(uow.get_connection());
+        self
+    }
+
+    /// Returns the condition added to every read of the repository.
+    ///
+    /// Repositories implementing [`SoftDelete`] override it to hide soft deleted records
+    /// (see [`SoftDelete::get_deleted_filter`]).
+    ///
+    /// # Returns
+    /// A condition matching all records by default.
+    fn get_default_filter(&self) -> Condition {
+        Condition::all()
+    }
+
+    /// Combines the optional filter of a read with the default filter of the repository.
+    fn get_read_filter(&self, filter: Option<Condition>) -> Condition {
+        let filter: Condition = filter.unwrap_or(Condition::all());
+        Condition::all().add(filter).add(self.get_default_filter())
+    }
 
     /// Retrieves multiple `Self::Model` records from the database.
     ///
@@ -58,6 +103,7 @@ where
     {
         let limit = get_limit(limit);
         let offset = get_offset(offset);
+        let filter = Some(self.get_read_filter(filter));
 
         let db = self.get_db().await;
 
@@ -83,6 +129,7 @@ where
         Self: builder::QueryBuilder<E>,
         T: TryGetableMany + Send,
     {
+        let filter = Some(self.get_read_filter(filter));
         let db = self.get_db().await;
         Self::select_only(filter, columns)
             .into_tuple()
@@ -91,7 +138,7 @@ where
     }
 
     async fn is_exist(&self, filter: Option<Condition>) -> Result<bool, DbErr> {
-        let filter: Condition = filter.unwrap_or(Condition::all());
+        let filter = self.get_read_filter(filter);
         let db = self.get_db().await;
         match E::find().filter(filter).limit(1).count(db).await {
             Ok(count) => Ok(count > 0),
@@ -99,9 +146,15 @@ where
         }
     }
 
+    /// Inserts a record.
+    ///
+    /// Outside of a unit of work the insert and the hooks of the entity (e.g. the events written
+    /// into the outbox) run in their own transaction.
     async fn create(&self, active_model: E::ActiveModel) -> Result<E::Model, DbErr> {
-        let db = self.get_db().await;
-        active_model.insert(db).await
+        let (db, uow) = begin_write(self.get_db().await).await?;
+        let model = active_model.insert(&db).await?;
+        commit_write(uow).await?;
+        Ok(model)
     }
 
     async fn create_multiple(
@@ -113,7 +166,7 @@ where
     }
 
     async fn get_one(&self, filter: Option<Condition>) -> Result<Option<E::Model>, DbErr> {
-        let filter: Condition = filter.unwrap_or(Condition::all());
+        let filter = self.get_read_filter(filter);
 
         let db = self.get_db().await;
         E::find().filter(filter).one(db).await
@@ -124,39 +177,28 @@ where
         Pk: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType> + Send + Sync + Clone,
     {
         let db = self.get_db().await;
-        E::find_by_id(ids).one(db).await
+        E::find_by_id(ids)
+            .filter(self.get_default_filter())
+            .one(db)
+            .await
     }
 
+    /// Updates a record.
+    ///
+    /// Outside of a unit of work the update and the hooks of the entity (e.g. the events written
+    /// into the outbox) run in their own transaction.
     async fn update(&self, active_model: E::ActiveModel) -> Result<E::Model, DbErr> {
-        let db = self.get_db().await;
-        active_model.update(db).await
+        let (db, uow) = begin_write(self.get_db().await).await?;
+        let model = active_model.update(&db).await?;
+        commit_write(uow).await?;
+        Ok(model)
     }
 
     async fn delete_by_id<Pk>(&self, ids: Pk) -> Result<(), DbErr>
     where
         Pk: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType> + Send + Sync + Clone,
     {
-        let mut filter: Condition = Condition::all();
-
-        // Collect all primary keys into a Vec before awaiting to ensure they are Send.
-        // This prevents issues where the iterator itself is not Send and might be moved across threads
-        // when the async function is suspended at an await point.
-        let keys: Vec<_> = E::PrimaryKey::iter().collect();
-        let mut keys = keys.into_iter();
-
-        // Add filter by ids
-        for id in ids.into().into_value_tuple() {
-            if let Some(key) = keys.next() {
-                let col = key.into_column();
-                filter = filter.add(col.eq(id));
-            } else {
-                panic!("primary key arity mismatch");
-            }
-        }
-        if keys.next().is_some() {
-            panic!("primary key arity mismatch");
-        }
-        self.delete(filter).await
+        self.delete(get_pk_filter::<E, Pk>(ids)).await
     }
 
     async fn delete(&self, filter: Condition) -> Result<(), DbErr> {
@@ -168,6 +210,157 @@ where
     }
 }
 
+/// Builds the condition matching the records with the given primary key.
+fn get_pk_filter<E, Pk>(ids: Pk) -> Condition
+where
+    E: EntityTrait,
+    Pk: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+{
+    let mut filter: Condition = Condition::all();
+    let mut keys = E::PrimaryKey::iter();
+
+    // Add filter by ids
+    for id in ids.into().into_value_tuple() {
+        if let Some(key) = keys.next() {
+            let col = key.into_column();
+            filter = filter.add(col.eq(id));
+        } else {
+            panic!("primary key arity mismatch");
+        }
+    }
+    if keys.next().is_some() {
+        panic!("primary key arity mismatch");
+    }
+    filter
+}
+
+/// Returns the connection to run a write on, beginning a transaction if the repository isn't
+/// already inside a unit of work.
+async fn begin_write(db: &Connection) -> Result<(Connection, Option<UnitOfWork>), DbErr> {
+    if db.is_txn() {
+        return Ok((db.clone(), None));
+    }
+    let uow = UnitOfWork::begin().await?;
+    Ok((uow.get_connection(), Some(uow)))
+}
+
+/// Commits the transaction begun by [`begin_write`], if any.
+async fn commit_write(uow: Option<UnitOfWork>) -> Result<(), DbErr> {
+    match uow {
+        Some(uow) => uow.commit().await,
+        None => Ok(()),
+    }
+}
+
+/// Visibility of soft deleted records for the reads of a [`SoftDelete`] repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletedMode {
+    /// Only records which are not deleted are read (default).
+    #[default]
+    Exclude,
+    /// Deleted and not deleted records are read.
+    Include,
+    /// Only deleted records are read.
+    Only,
+}
+
+/// A trait for repositories whose entity is soft deleted through a boolean column.
+///
+/// A soft delete repository hides deleted records from every read by default and marks records
+/// as deleted instead of removing them. The implementation must override
+/// [`Repository::get_default_filter`] with [`SoftDelete::get_deleted_filter`] and
+/// [`Repository::delete`] with [`SoftDelete::soft_delete`].
+///
+/// # Example
+/// ```rust,ignore
+/// let rep = Application::new().await.only_deleted();
+/// let deleted_app = rep.get_by_id(app_id).await?;
+/// ```
+#[async_trait]
+pub trait SoftDelete<E>: Repository<E> + Sized
+where
+    E: EntityTrait + Send + Sync,
+    E::PrimaryKey: PrimaryKeyTrait,
+    E::Model: Send + Sync + IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelTrait<Entity = E> + Send + Sync + From<E::Model>,
+{
+    /// Returns the boolean column marking a record as deleted.
+    fn get_deleted_column() -> E::Column;
+
+    /// Returns the current visibility of deleted records.
+    fn get_deleted_mode(&self) -> DeletedMode;
+
+    /// Sets the visibility of deleted records.
+    fn set_deleted_mode(&mut self, mode: DeletedMode);
+
+    /// Makes the reads of the repository return deleted and not deleted records.
+    fn with_deleted(mut self) -> Self {
+        self.set_deleted_mode(DeletedMode::Include);
+        self
+    }
+
+    /// Makes the reads of the repository return only deleted records.
+    fn only_deleted(mut self) -> Self {
+        self.set_deleted_mode(DeletedMode::Only);
+        self
+    }
+
+    /// Returns the condition matching the records visible with the current [`DeletedMode`].
+    fn get_deleted_filter(&self) -> Condition {
+        let column = Self::get_deleted_column();
+        match self.get_deleted_mode() {
+            DeletedMode::Exclude => Condition::all().add(column.eq(false)),
+            DeletedMode::Include => Condition::all(),
+            DeletedMode::Only => Condition::all().add(column.eq(true)),
+        }
+    }
+
+    /// Marks the records matching the filter as deleted.
+    async fn soft_delete(&self, filter: Condition) -> Result<(), DbErr> {
+        self.set_deleted(filter, true).await
+    }
+
+    /// Marks the deleted records matching the filter as not deleted.
+    async fn restore(&self, filter: Condition) -> Result<(), DbErr> {
+        self.set_deleted(filter, false).await
+    }
+
+    /// Removes the records matching the filter from the database, deleted or not.
+    ///
+    /// Records are removed one by one in a transaction, so the delete hooks of the entity run.
+    async fn purge(&self, filter: Condition) -> Result<(), DbErr> {
+        let (db, uow) = begin_write(self.get_db().await).await?;
+        for model in E::find().filter(filter).all(&db).await? {
+            model.into_active_model().delete(&db).await?;
+        }
+        commit_write(uow).await
+    }
+
+    /// Removes the record with the given primary key from the database, deleted or not.
+    async fn purge_by_id<Pk>(&self, ids: Pk) -> Result<(), DbErr>
+    where
+        Pk: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType> + Send + Sync + Clone,
+    {
+        self.purge(get_pk_filter::<E, Pk>(ids)).await
+    }
+
+    /// Sets the deleted column of the records matching the filter.
+    ///
+    /// Records are updated one by one in a transaction, so the save hooks of the entity run.
+    async fn set_deleted(&self, filter: Condition, is_deleted: bool) -> Result<(), DbErr> {
+        let column = Self::get_deleted_column();
+        let filter = Condition::all().add(filter).add(column.eq(!is_deleted));
+
+        let (db, uow) = begin_write(self.get_db().await).await?;
+        for model in E::find().filter(filter).all(&db).await? {
+            let mut active_model = model.into_active_model();
+            active_model.set(column, is_deleted.into());
+            active_model.update(&db).await?;
+        }
+        commit_write(uow).await
+    }
+}
+
 mod builder {
     use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QuerySelect, Select};
 
@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+pub use entity_lib::notification as notification_entity;
+use notification_entity::NotificationStatus;
+use notification_lib::{store::NotificationStore, Message};
+use sea_orm::{prelude::Expr, ActiveModelTrait, DbErr, Set, UpdateMany};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub use crate::Repository;
+use crate::{
+    builder::QueryBuilder,
+    connection::Connection,
+    delivery::{self, DeliveryEntity},
+};
+
+pub struct Notification {
+    db: Connection,
+}
+
+impl QueryBuilder<notification_entity::Entity> for Notification {}
+
+#[async_trait]
+impl Repository<notification_entity::Entity> for Notification {
+    async fn new() -> Self {
+        Self {
+            db: Connection::get_pool().await,
+        }
+    }
+
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
+    }
+}
+
+impl DeliveryEntity for notification_entity::Entity {
+    type Status = NotificationStatus;
+
+    const PENDING: NotificationStatus = NotificationStatus::Pending;
+    const SENT: NotificationStatus = NotificationStatus::Sent;
+    const FAILED: NotificationStatus = NotificationStatus::Failed;
+}
+
+#[async_trait]
+impl NotificationStore for Notification {
+    type Error = DbErr;
+
+    async fn add(&self, message: &Message) -> Result<(), DbErr> {
+        let notification_model = notification_entity::ActiveModel {
+            id: Set(message.id),
+            template: Set(message.template.to_owned()),
+            locale: Set(message.locale.to_owned()),
+            recipient: Set(message.recipient.to_owned()),
+            subject: Set(message.subject.to_owned()),
+            body_text: Set(message.body_text.to_owned()),
+            body_html: Set(message.body_html.to_owned()),
+            ..Default::default()
+        };
+        notification_model.insert(self.get_db().await).await?;
+        Ok(())
+    }
+
+    async fn claim_due(&self, limit: u64, lease_sec: u64) -> Result<Vec<Message>, DbErr> {
+        let models = delivery::claim::<notification_entity::Entity, _>(
+            self.get_db().await,
+            limit,
+            lease_sec,
+        )
+        .await?;
+        Ok(models
+            .into_iter()
+            .map(|model| Message {
+                id: model.id,
+                template: model.template,
+                locale: model.locale,
+                recipient: model.recipient,
+                subject: model.subject,
+                body_text: model.body_text,
+                body_html: model.body_html,
+                attempts: model.attempts.max(0) as u32,
+            })
+            .collect())
+    }
+
+    async fn mark_sent(&self, id: Uuid) -> Result<(), DbErr> {
+        // The bodies can hold tokens, they are not kept once sent
+        clear_bodies(delivery::get_mark_sent_query::<notification_entity::Entity>(id))
+            .exec(self.get_db().await)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), DbErr> {
+        let query =
+            delivery::get_mark_failed_query::<notification_entity::Entity>(id, error, retry_at);
+        match retry_at {
+            Some(_) => query,
+            None => clear_bodies(query),
+        }
+        .exec(self.get_db().await)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_sent(&self, before: OffsetDateTime) -> Result<u64, DbErr> {
+        delivery::delete_sent::<notification_entity::Entity, _>(self.get_db().await, before).await
+    }
+}
+
+/// Adds the removal of the bodies of the message to the query.
+fn clear_bodies(
+    query: UpdateMany<notification_entity::Entity>,
+) -> UpdateMany<notification_entity::Entity> {
+    query
+        .col_expr(notification_entity::Column::BodyText, Expr::value(""))
+        .col_expr(
+            notification_entity::Column::BodyHtml,
+            Expr::value(Option::<String>::None),
+        )
+}
@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+pub use entity_lib::outbox as outbox_entity;
+use outbox_entity::OutboxStatus;
+use repository_amqp_lib::outbox::{OutboxMessage, OutboxStore};
+use sea_orm::DbErr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub use crate::Repository;
+use crate::{
+    builder::QueryBuilder,
+    connection::Connection,
+    delivery::{self, DeliveryEntity},
+};
+
+pub struct Outbox {
+    db: Connection,
+}
+
+impl QueryBuilder<outbox_entity::Entity> for Outbox {}
+
+#[async_trait]
+impl Repository<outbox_entity::Entity> for Outbox {
+    async fn new() -> Self {
+        Self {
+            db: Connection::get_pool().await,
+        }
+    }
+
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
+    }
+}
+
+impl DeliveryEntity for outbox_entity::Entity {
+    type Status = OutboxStatus;
+
+    const PENDING: OutboxStatus = OutboxStatus::Pending;
+    const SENT: OutboxStatus = OutboxStatus::Sent;
+    const FAILED: OutboxStatus = OutboxStatus::Failed;
+}
+
+#[async_trait]
+impl OutboxStore for Outbox {
+    type Error = DbErr;
+
+    async fn claim_pending(&self, limit: u64, lease_sec: u64) -> Result<Vec<OutboxMessage>, DbErr> {
+        let models =
+            delivery::claim::<outbox_entity::Entity, _>(self.get_db().await, limit, lease_sec)
+                .await?;
+        Ok(models
+            .into_iter()
+            .map(|model| OutboxMessage {
+                id: model.id,
+                queue: model.queue,
+                event: model.event,
+                payload: model.payload,
+                attempts: model.attempts.max(0) as u32,
+            })
+            .collect())
+    }
+
+    async fn mark_sent(&self, id: Uuid) -> Result<(), DbErr> {
+        delivery::get_mark_sent_query::<outbox_entity::Entity>(id)
+            .exec(self.get_db().await)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), DbErr> {
+        delivery::get_mark_failed_query::<outbox_entity::Entity>(id, error, retry_at)
+            .exec(self.get_db().await)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_sent(&self, before: OffsetDateTime) -> Result<u64, DbErr> {
+        delivery::delete_sent::<outbox_entity::Entity, _>(self.get_db().await, before).await
+    }
+}
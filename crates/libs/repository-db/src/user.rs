@@ -1,39 +1,122 @@
-use adapter_lib::db;
 use async_trait::async_trait;
 pub use entity_lib::user as user_entity;
-use sea_orm::{prelude::Expr, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use sea_orm::{
+    sea_query::{Expr, Func},
+    ColumnTrait, Condition, DbErr, EntityTrait, QueryFilter,
+};
+use uuid::Uuid;
 
-use crate::builder::QueryBuilder;
-pub use crate::Repository;
+use crate::{builder::QueryBuilder, connection::Connection};
+pub use crate::{DeletedMode, Repository, SoftDelete};
 
 pub struct User {
-    db: &'static DatabaseConnection,
+    db: Connection,
+    deleted_mode: DeletedMode,
 }
 
 impl QueryBuilder<user_entity::Entity> for User {}
 
+impl User {
+    /// Returns the filter of the users with the email, compared case-insensitively like the
+    /// unique index of the emails.
+    pub fn get_email_filter(email: &str) -> Condition {
+        Condition::all().add(
+            Expr::expr(Func::lower(Expr::col(user_entity::Column::Email)))
+                .eq(email.trim().to_lowercase()),
+        )
+    }
+
+    /// Replaces the password hash of the user, for a rehash with the actual algorithm.
+    ///
+    /// The hash is written as is and the hooks of the save don't run: the password doesn't
+    /// change, so its tokens must not be revoked.
+    pub async fn update_password_hash(
+        &self,
+        user_id: Uuid,
+        password_hash: String,
+    ) -> Result<(), DbErr> {
+        user_entity::Entity::update_many()
+            .col_expr(user_entity::Column::Password, Expr::value(password_hash))
+            .filter(user_entity::Column::Id.eq(user_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Removes the hash of a used recovery code of the user, if the user still has it.
+    ///
+    /// The check and the removal are one update, so a code used concurrently is removed once.
+    /// The hooks of the save don't run: the recovery codes are hidden from the events and the
+    /// access of the user doesn't change.
+    ///
+    /// # Returns
+    /// `true` if the code was removed by this call.
+    pub async fn remove_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<bool, DbErr> {
+        let result = user_entity::Entity::update_many()
+            .col_expr(
+                user_entity::Column::MfaRecoveryCodes,
+                Expr::cust_with_exprs(
+                    "array_remove($1, $2::VARCHAR)",
+                    [
+                        Expr::col(user_entity::Column::MfaRecoveryCodes).into(),
+                        Expr::val(code_hash).into(),
+                    ],
+                ),
+            )
+            .filter(user_entity::Column::Id.eq(user_id))
+            .filter(Expr::cust_with_exprs(
+                "$1::VARCHAR = ANY($2)",
+                [
+                    Expr::val(code_hash).into(),
+                    Expr::col(user_entity::Column::MfaRecoveryCodes).into(),
+                ],
+            ))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
+}
+
 #[async_trait]
 impl Repository<user_entity::Entity> for User {
     async fn new() -> Self {
         Self {
-            db: db::get_connection().await,
+            db: Connection::get_pool().await,
+            deleted_mode: DeletedMode::default(),
         }
     }
 
-    async fn get_db(&self) -> &'static DatabaseConnection {
-        self.db
+    async fn get_db(&self) -> &Connection {
+        &self.db
+    }
+
+    fn set_db(&mut self, db: Connection) {
+        self.db = db;
+    }
+
+    fn get_default_filter(&self) -> Condition {
+        self.get_deleted_filter()
     }
 
     async fn delete(&self, filter: Condition) -> Result<(), DbErr> {
-        let db = self.get_db().await;
-        match user_entity::Entity::update_many()
-            .col_expr(user_entity::Column::IsDeleted, Expr::value(true))
-            .filter(filter)
-            .exec(db)
-            .await
-        {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
-        }
+        self.soft_delete(filter).await
+    }
+}
+
+impl SoftDelete<user_entity::Entity> for User {
+    fn get_deleted_column() -> user_entity::Column {
+        user_entity::Column::IsDeleted
+    }
+
+    fn get_deleted_mode(&self) -> DeletedMode {
+        self.deleted_mode
+    }
+
+    fn set_deleted_mode(&mut self, mode: DeletedMode) {
+        self.deleted_mode = mode;
     }
 }
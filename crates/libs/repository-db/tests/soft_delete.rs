@@ -0,0 +1,67 @@
+use repository_db_lib::{
+    application::{application_entity, Application as ApplicationRep},
+    DeletedMode, Repository, SoftDelete,
+};
+use sea_orm::{ColumnTrait, Condition, Set};
+use uuid::Uuid;
+
+#[test]
+fn deleted_records_are_hidden_by_default() {
+    assert_eq!(DeletedMode::default(), DeletedMode::Exclude);
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL (docker-compose up -d)"]
+async fn deleted_records_are_read_by_mode() {
+    migration::init().await;
+    let rep = ApplicationRep::new().await;
+    let application = rep
+        .create(application_entity::ActiveModel {
+            name: Set(Uuid::new_v4().to_string()),
+            description: Set(String::new()),
+            redirect_uris: Set(Vec::new()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let filter = Condition::all().add(application_entity::Column::Id.eq(application.id));
+    assert!(ApplicationRep::new()
+        .await
+        .only_deleted()
+        .get_by_id(application.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    rep.delete_by_id(application.id).await.unwrap();
+    assert!(rep.get_by_id(application.id).await.unwrap().is_none());
+    assert!(!rep.is_exist(Some(filter.to_owned())).await.unwrap());
+    let application = ApplicationRep::new()
+        .await
+        .with_deleted()
+        .get_by_id(application.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(application.is_deleted);
+    assert!(ApplicationRep::new()
+        .await
+        .only_deleted()
+        .get_by_id(application.id)
+        .await
+        .unwrap()
+        .is_some());
+
+    rep.restore(filter.to_owned()).await.unwrap();
+    assert!(rep.get_by_id(application.id).await.unwrap().is_some());
+
+    rep.purge(filter).await.unwrap();
+    assert!(ApplicationRep::new()
+        .await
+        .with_deleted()
+        .get_by_id(application.id)
+        .await
+        .unwrap()
+        .is_none());
+}
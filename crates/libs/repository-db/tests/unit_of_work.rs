@@ -0,0 +1,79 @@
+use repository_db_lib::{
+    application::{application_entity, Application as ApplicationRep},
+    Repository, SoftDelete, UnitOfWork,
+};
+use sea_orm::{ColumnTrait, Condition, Set};
+use uuid::Uuid;
+
+fn get_application() -> application_entity::ActiveModel {
+    application_entity::ActiveModel {
+        name: Set(Uuid::new_v4().to_string()),
+        description: Set(String::new()),
+        redirect_uris: Set(Vec::new()),
+        ..Default::default()
+    }
+}
+
+async fn is_exist(application_id: Uuid) -> bool {
+    ApplicationRep::new()
+        .await
+        .with_deleted()
+        .get_by_id(application_id)
+        .await
+        .unwrap()
+        .is_some()
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL (docker-compose up -d)"]
+async fn operations_are_committed_or_rolled_back_together() {
+    migration::init().await;
+    committed_operations_are_kept().await;
+    rolled_back_operations_are_discarded().await;
+    dropped_unit_of_work_is_rolled_back().await;
+}
+
+async fn committed_operations_are_kept() {
+    let uow = UnitOfWork::begin().await.unwrap();
+    let rep = ApplicationRep::new().await.with_txn(&uow);
+    let first = rep.create(get_application()).await.unwrap();
+    let second = rep.create(get_application()).await.unwrap();
+    // A soft delete joins the transaction instead of beginning its own
+    rep.soft_delete(Condition::all().add(application_entity::Column::Id.eq(second.id)))
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    assert!(is_exist(first.id).await);
+    assert!(ApplicationRep::new()
+        .await
+        .only_deleted()
+        .get_by_id(second.id)
+        .await
+        .unwrap()
+        .is_some());
+    // The connection of a closed unit of work can't be used anymore
+    assert!(rep.create(get_application()).await.is_err());
+}
+
+async fn rolled_back_operations_are_discarded() {
+    let uow = UnitOfWork::begin().await.unwrap();
+    let rep = ApplicationRep::new().await.with_txn(&uow);
+    let first = rep.create(get_application()).await.unwrap();
+    let second = rep.create(get_application()).await.unwrap();
+    assert!(rep.get_by_id(first.id).await.unwrap().is_some());
+    uow.rollback().await.unwrap();
+
+    assert!(!is_exist(first.id).await);
+    assert!(!is_exist(second.id).await);
+}
+
+async fn dropped_unit_of_work_is_rolled_back() {
+    let application_id = {
+        let uow = UnitOfWork::begin().await.unwrap();
+        let rep = ApplicationRep::new().await.with_txn(&uow);
+        rep.create(get_application()).await.unwrap().id
+    };
+    assert!(!is_exist(application_id).await);
+}
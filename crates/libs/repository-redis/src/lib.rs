@@ -1,6 +1,13 @@
 use adapter_lib::redis::get_connection;
-use redis::{AsyncCommands, AsyncIter, FromRedisValue, JsonAsyncCommands, ToRedisArgs};
+use redis::{
+    AsyncCommands, AsyncIter, ExistenceCheck, FromRedisValue, JsonAsyncCommands, SetExpiry,
+    SetOptions, ToRedisArgs,
+};
 use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of keys `SCAN` looks at per call.
+const SCAN_COUNT: usize = 100;
 
 /// Retrieves the value of a key from Redis.
 ///
@@ -16,6 +23,19 @@ pub async fn get<V: FromRedisValue>(key: String) -> Option<V> {
     con.get(key).await.unwrap()
 }
 
+/// Retrieves the value of a key from Redis and deletes the key.
+///
+/// The key is read and deleted atomically (`GETDEL`), so only one caller gets the value.
+///
+/// # Example
+/// ```rust,ignore
+/// let result: Option<String> = get_del("my_key".to_string()).await;
+/// ```
+pub async fn get_del<V: FromRedisValue>(key: String) -> Option<V> {
+    let mut con = get_connection().await;
+    con.get_del(key).await.unwrap()
+}
+
 /// Sets a value for a given key in Redis, with an optional expiration time.
 ///
 /// This function sets the value of the given `key` to `value`, with an optional expiration time
@@ -41,6 +61,97 @@ pub async fn set<'a, V: ToRedisArgs + Send + Sync + 'a>(
     }
 }
 
+/// Sets a value for a given key in Redis only if the key does not exist, with an expiration time.
+///
+/// The check and the write are atomic (`SET NX EX`), so only one caller sets the key. Returns
+/// `true` if the key was set.
+///
+/// # Example
+/// ```rust,ignore
+/// let is_set = set_nx("my_key".to_string(), "some_value", 60).await;
+/// ```
+pub async fn set_nx<'a, V: ToRedisArgs + Send + Sync + 'a>(
+    key: String,
+    value: V,
+    ex_sec: u64,
+) -> bool {
+    let mut con = get_connection().await;
+    let options = SetOptions::default()
+        .conditional_set(ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(ex_sec));
+    let result: Option<String> = con.set_options(key, value, options).await.unwrap();
+    result.is_some()
+}
+
+/// Sets a value for a given key in Redis only if the key exists, its expiration time is kept.
+///
+/// The check and the write are atomic (`SET XX KEEPTTL`), so a key deleted meanwhile is not
+/// written back. Returns `true` if the key was set.
+///
+/// # Example
+/// ```rust,ignore
+/// let is_set = set_xx("my_key".to_string(), "some_value").await;
+/// ```
+pub async fn set_xx<'a, V: ToRedisArgs + Send + Sync + 'a>(key: String, value: V) -> bool {
+    let mut con = get_connection().await;
+    let options = SetOptions::default()
+        .conditional_set(ExistenceCheck::XX)
+        .with_expiration(SetExpiry::KEEPTTL);
+    let result: Option<String> = con.set_options(key, value, options).await.unwrap();
+    result.is_some()
+}
+
+/// Increments the counter of a given key and returns its new value.
+///
+/// The increment is atomic (`INCR`), a missing key counts from 0. The key expires after
+/// `ex_sec`, set again on every increment.
+///
+/// # Example
+/// ```rust,ignore
+/// let attempts = incr("my_key".to_string(), 300).await;
+/// ```
+pub async fn incr(key: String, ex_sec: u64) -> u64 {
+    let mut con = get_connection().await;
+    let (count,): (u64,) = redis::pipe()
+        .atomic()
+        .incr(&key, 1)
+        .expire(&key, ex_sec as i64)
+        .ignore()
+        .query_async(&mut con)
+        .await
+        .unwrap();
+    count
+}
+
+/// Adds an event to a sliding window and returns the number of events in the window.
+///
+/// The window is a sorted set of the event times in milliseconds, the events older than
+/// `window_sec` are removed before counting. Everything runs in one transaction and the key
+/// expires with the window.
+///
+/// # Example
+/// ```rust,ignore
+/// let attempts = sliding_window_add("my_key".to_string(), 900).await;
+/// ```
+pub async fn sliding_window_add(key: String, window_sec: u64) -> u64 {
+    let mut con = get_connection().await;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let now_ms = now.as_millis() as u64;
+    let (count,): (u64,) = redis::pipe()
+        .atomic()
+        .zrembyscore(&key, 0, now_ms.saturating_sub(window_sec * 1000))
+        .ignore()
+        .zadd(&key, now.as_nanos().to_string(), now_ms)
+        .ignore()
+        .zcard(&key)
+        .expire(&key, window_sec as i64)
+        .ignore()
+        .query_async(&mut con)
+        .await
+        .unwrap();
+    count
+}
+
 /// Checks if the specified key exists in Redis.
 ///
 /// This function returns `true` if the key exists, or `false` if it does not.
@@ -87,7 +198,9 @@ pub async fn get_keys(pattern: String) -> Vec<String> {
 
 /// Deletes all keys in Redis that match the given pattern.
 ///
-/// This function scans for keys matching the provided pattern and deletes them.
+/// The keys are iterated with a `SCAN` cursor, never with `KEYS`, so Redis isn't blocked on a
+/// large keyspace. Every page of matching keys is deleted with one `DEL` before the next page is
+/// scanned.
 ///
 /// # Example
 /// ```rust
@@ -95,9 +208,24 @@ pub async fn get_keys(pattern: String) -> Vec<String> {
 /// ```
 pub async fn del_keys(pattern: String) {
     let mut con = get_connection().await;
-    let mut scan_res: AsyncIter<String> = con.scan_match(pattern).await.unwrap();
-    while let Some(key) = scan_res.next_item().await {
-        del(key).await;
+    let mut cursor: u64 = 0;
+    loop {
+        let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query_async(&mut con)
+            .await
+            .unwrap();
+        if !keys.is_empty() {
+            let _: () = con.del(keys).await.unwrap();
+        }
+        if next_cursor == 0 {
+            break;
+        }
+        cursor = next_cursor;
     }
 }
 
@@ -2,8 +2,8 @@ use darling::{ast::NestedMeta, Error, FromMeta};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    parse_macro_input, parse_quote, parse_str, Expr, ExprArray, FnArg, Ident, ItemFn, Pat,
-    PatIdent, PatType, Type, TypePath,
+    parse_macro_input, parse_quote, parse_str, Expr, ExprArray, FnArg, Ident, ItemFn, LitStr,
+    Pat, PatIdent, PatType, Path, Type, TypePath,
 };
 use util_lib::string::snake_to_camel;
 
@@ -21,8 +21,25 @@ struct GuardPermissionArgs {
     any_perms: Option<ExprArray>,
     #[darling(default)]
     all_perms: Option<ExprArray>,
+    /// Route parameter (path or query) holding the identifier of the resource to check.
+    #[darling(default)]
+    resource: Option<Ident>,
+    /// Type implementing `rocket_util_lib::PermissionResolver` for the guard and resource.
+    #[darling(default)]
+    resolver: Option<Path>,
 }
 
+/// Location of the resource identifier in the route URI.
+enum ResourceParam {
+    /// Index of the segment after the mount point (see `rocket::Request::param`).
+    Path(usize),
+    /// Name of the query field (see `rocket::Request::query_value`).
+    Query(String),
+}
+
+/// Methods of the Rocket route attributes the resource parameter can be found in.
+const ROUTE_ATTRS: [&str; 7] = ["get", "put", "post", "delete", "patch", "head", "options"];
+
 pub fn impl_guard_permission(args: TokenStream, item: TokenStream) -> TokenStream {
     let mut item_fn = parse_macro_input!(item as ItemFn);
 
@@ -55,11 +72,39 @@ pub fn impl_guard_permission(args: TokenStream, item: TokenStream) -> TokenStrea
 
     let (any_perms, all_perms) = get_perms(&macro_args);
 
+    let perms_def = match (&macro_args.resource, &macro_args.resolver) {
+        (None, None) => quote! {
+            let perms = guard.get_permissions().await;
+        },
+        (Some(resource), Some(resolver)) => {
+            match gen_resource_perms(
+                &item_fn,
+                &guard_ident,
+                resource,
+                resolver,
+                &macro_args.error_ty,
+                &macro_args.perm_error,
+            ) {
+                Ok(v) => v,
+                Err(e) => {
+                    return TokenStream::from(e.write_errors());
+                }
+            }
+        }
+        _ => {
+            return TokenStream::from(
+                Error::custom("Arguments `resource` and `resolver` must be set together")
+                    .write_errors(),
+            );
+        }
+    };
+
     let handle_def = gen_handle_guard_struct(
         &handle_ident,
         &guard_ident,
         &macro_args.error_ty,
         &macro_args.perm_error,
+        &perms_def,
         &any_perms,
         &all_perms,
     );
@@ -93,6 +138,103 @@ fn get_perms(guard_perm_args: &GuardPermissionArgs) -> (Vec<Expr>, Vec<Expr>) {
     (any_perms, all_perms)
 }
 
+/// Generates the resolution of the permissions the guard holds on the resource.
+///
+/// The resource identifier is parsed from the route parameter into the type of the handler
+/// argument with the same name, then passed with the guard to the resolver. A resource the
+/// resolver doesn't find fails with `Forbidden` like a resource the guard has no access to, so
+/// the caller can't learn whether it exists.
+fn gen_resource_perms(
+    item_fn: &ItemFn,
+    guard_ident: &Ident,
+    resource: &Ident,
+    resolver: &Path,
+    error_type: &Ident,
+    permission_error: &Ident,
+) -> Result<proc_macro2::TokenStream, Error> {
+    let resource_ty = match get_arg_type(item_fn, resource) {
+        Some(v) => v,
+        None => {
+            return Err(Error::custom(format!("Argument {} not found", resource)));
+        }
+    };
+    let resource_value = match find_resource_param(item_fn, resource)? {
+        ResourceParam::Path(idx) => quote! {
+            request.param::<#resource_ty>(#idx).and_then(|v| v.ok())
+        },
+        ResourceParam::Query(name) => quote! {
+            request.query_value::<#resource_ty>(#name).and_then(|v| v.ok())
+        },
+    };
+    Ok(quote! {
+        let resource = match #resource_value {
+            Some(v) => v,
+            None => {
+                return rocket::outcome::Outcome::Forward(rocket::http::Status::NotFound);
+            }
+        };
+        let perms = match <#resolver as rocket_util_lib::PermissionResolver<#guard_ident, #resource_ty>>::resolve(&guard, &resource).await {
+            Some(v) => v,
+            None => {
+                return rocket::outcome::Outcome::Error((rocket::http::Status::Forbidden, #error_type::#permission_error));
+            }
+        };
+    })
+}
+
+/// Finds where the resource parameter is declared in the Rocket route attribute of the handler.
+fn find_resource_param(item_fn: &ItemFn, resource: &Ident) -> Result<ResourceParam, Error> {
+    let param = format!("<{}>", resource);
+    for attr in item_fn.attrs.iter() {
+        let is_route = ROUTE_ATTRS
+            .iter()
+            .any(|method| attr.path().is_ident(method));
+        if !is_route {
+            continue;
+        }
+        // The route uri is the first argument of the attribute, e.g. `#[get("/<id>?<query>")]`
+        let uri = match attr.parse_args_with(|input: syn::parse::ParseStream| {
+            let uri: LitStr = input.parse()?;
+            let _: proc_macro2::TokenStream = input.parse()?;
+            Ok(uri)
+        }) {
+            Ok(v) => v.value(),
+            Err(e) => return Err(Error::from(e)),
+        };
+        let (path, query) = uri.split_once('?').unwrap_or((uri.as_str(), ""));
+        if let Some(idx) = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .position(|segment| segment == param)
+        {
+            return Ok(ResourceParam::Path(idx));
+        }
+        if query.split('&').any(|field| field == param) {
+            return Ok(ResourceParam::Query(resource.to_string()));
+        }
+        return Err(Error::custom(format!(
+            "Parameter {} not found in route {:?}",
+            param, uri
+        )));
+    }
+    Err(Error::custom(
+        "Route attribute not found, `#[guard_permission]` must be placed above it",
+    ))
+}
+
+fn get_arg_type(item_fn: &ItemFn, arg_ident: &Ident) -> Option<Type> {
+    for input in item_fn.sig.inputs.iter() {
+        if let FnArg::Typed(PatType { pat, ty, .. }) = input {
+            if let Pat::Ident(PatIdent { ident, .. }) = pat.as_ref() {
+                if ident == arg_ident {
+                    return Some(ty.as_ref().to_owned());
+                }
+            }
+        }
+    }
+    None
+}
+
 fn update_arg_type(item_fn: &mut ItemFn, arg_ident: &Ident, new_type: &Ident) -> Option<Ident> {
     let mut arg_old_type_opt: Option<Ident> = None;
     let mut arg_idx: usize = 0;
@@ -128,6 +270,7 @@ fn gen_handle_guard_struct(
     guard_ident: &Ident,
     error_type: &Ident,
     permission_error: &Ident,
+    perms_def: &proc_macro2::TokenStream,
     any_perms: &Vec<Expr>,
     all_perms: &Vec<Expr>,
 ) -> proc_macro2::TokenStream {
@@ -142,7 +285,7 @@ fn gen_handle_guard_struct(
                 let guard = rocket::outcome::try_outcome!(request.guard::<#guard_ident>().await);
                 let all_perms = vec![#(#all_perms),*];
                 let any_perms = vec![#(#any_perms),*];
-                let perms = guard.get_permissions().await;
+                #perms_def
                 if all_perms.len() > 0 {
                     if !all_perms.iter().all(|perm| perms.contains(perm)) {
                         return rocket::outcome::Outcome::Error((rocket::http::Status::Forbidden, #error_type::#permission_error));
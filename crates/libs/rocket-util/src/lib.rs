@@ -1 +1,4 @@
+pub mod permission;
+
+pub use permission::PermissionResolver;
 pub use rocket_util_proc_macro::guard_permission;
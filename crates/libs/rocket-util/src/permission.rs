@@ -0,0 +1,66 @@
+use rocket::async_trait;
+
+/// Pluggable async lookup of the permissions a guard holds on a specific resource.
+///
+/// Used by `#[guard_permission]` in resource-scoped mode (`resource = ...`, `resolver = ...`):
+/// instead of checking the global permissions returned by `guard.get_permissions()`, the
+/// generated guard extracts the resource identifier from the route (path or query parameter)
+/// and asks the resolver which permissions the guard holds on that resource. The usual
+/// `all_perms`/`any_perms` semantics are then applied to the resolved permissions. A missing
+/// resource fails with `Forbidden` and the `perm_error` of the macro, like a resource the guard
+/// has no access to, so the existence of the resources isn't leaked to the callers without
+/// access.
+///
+/// The resolver is a type of its own (not the guard), so the same guard can be checked against
+/// different kinds of resources sharing the same identifier type (e.g. `Uuid`).
+///
+/// # Type Parameters
+/// - `G`: The request guard type (e.g. an authenticated user).
+/// - `R`: The type of the resource identifier, parsed from the route parameter.
+///
+/// # Example
+/// ```rust,ignore
+/// pub struct ApplicationPermission;
+///
+/// #[rocket::async_trait]
+/// impl PermissionResolver<UserStaff, Uuid> for ApplicationPermission {
+///     type Permission = AppStaffPermissions;
+///
+///     async fn resolve(guard: &UserStaff, application_id: &Uuid) -> Option<Vec<Self::Permission>> {
+///         Application::new().await.get_by_id(*application_id).await.unwrap()?;
+///         Some(
+///             AppStaff::new()
+///                 .await
+///                 .get_permissions(*application_id, guard.user.claims.id)
+///                 .await
+///                 .unwrap()
+///                 .unwrap_or_default(),
+///         )
+///     }
+/// }
+///
+/// #[guard_permission(
+///     error_ty = GuardError,
+///     perm_error = MissingPermission,
+///     resource = application_id,
+///     resolver = ApplicationPermission,
+///     all_perms = [AppStaffPermissions::UpdateKey]
+/// )]
+/// #[put("/<application_id>/key/<key_id>", data = "<key>")]
+/// pub async fn update(guard: UserStaff, application_id: Uuid, key_id: Uuid, key: Json<UpdateKey>) {}
+/// ```
+#[async_trait]
+pub trait PermissionResolver<G, R>
+where
+    G: Send + Sync,
+    R: Send + Sync,
+{
+    /// The permission type compared against `all_perms`/`any_perms`.
+    type Permission: PartialEq + Send + Sync;
+
+    /// Returns the permissions `guard` holds on the resource `resource`, `None` if the resource
+    /// doesn't exist.
+    ///
+    /// `None` and an empty vector both make any permission check fail with `Forbidden`.
+    async fn resolve(guard: &G, resource: &R) -> Option<Vec<Self::Permission>>;
+}
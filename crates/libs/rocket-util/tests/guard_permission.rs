@@ -0,0 +1,157 @@
+use rocket::{
+    async_trait, get,
+    http::{Header, Status},
+    local::blocking::Client,
+    outcome::Outcome,
+    put,
+    request::{self, FromRequest, Request},
+    routes, Build, Rocket,
+};
+use rocket_util_lib::{guard_permission, PermissionResolver};
+
+#[derive(Debug)]
+pub enum TestError {
+    MissingUser,
+    MissingPermission,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Perm {
+    Read,
+    Update,
+}
+
+/// User of the `X-User` header.
+pub struct User(String);
+
+#[async_trait]
+impl<'r> FromRequest<'r> for User {
+    type Error = TestError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        match request.headers().get_one("X-User") {
+            Some(v) => Outcome::Success(Self(v.to_string())),
+            None => Outcome::Error((Status::Unauthorized, TestError::MissingUser)),
+        }
+    }
+}
+
+/// Only the document 1 exists, the reader can read it and the editor can update it too.
+pub struct DocumentPermission;
+
+#[async_trait]
+impl PermissionResolver<User, u32> for DocumentPermission {
+    type Permission = Perm;
+
+    async fn resolve(guard: &User, document_id: &u32) -> Option<Vec<Self::Permission>> {
+        if *document_id != 1 {
+            return None;
+        }
+        Some(match guard.0.as_str() {
+            "reader" => vec![Perm::Read],
+            "editor" => vec![Perm::Read, Perm::Update],
+            _ => Vec::new(),
+        })
+    }
+}
+
+#[guard_permission(error_ty = TestError, perm_error = MissingPermission, resource = document_id, resolver = DocumentPermission, all_perms = [Perm::Update])]
+#[put("/<document_id>")]
+async fn update(guard: User, document_id: u32) -> String {
+    format!("{} {}", guard.0, document_id)
+}
+
+#[guard_permission(error_ty = TestError, perm_error = MissingPermission, resource = document_id, resolver = DocumentPermission, any_perms = [Perm::Read])]
+#[get("/<kind>/<document_id>/detail")]
+async fn get_detail(_guard: User, kind: &str, document_id: u32) -> String {
+    format!("{} {}", kind, document_id)
+}
+
+#[guard_permission(error_ty = TestError, perm_error = MissingPermission, resource = document_id, resolver = DocumentPermission, any_perms = [Perm::Read])]
+#[get("/?<page>&<document_id>")]
+async fn get_multiple(_guard: User, page: Option<u32>, document_id: u32) -> String {
+    format!("{} {}", page.unwrap_or(0), document_id)
+}
+
+fn get_client() -> Client {
+    let rocket: Rocket<Build> =
+        rocket::build().mount("/document", routes![update, get_detail, get_multiple]);
+    Client::tracked(rocket).unwrap()
+}
+
+fn get_status(client: &Client, method: &str, uri: &str, user: &str) -> Status {
+    let request = match method {
+        "put" => client.put(uri.to_string()),
+        _ => client.get(uri.to_string()),
+    };
+    request
+        .header(Header::new("X-User", user.to_string()))
+        .dispatch()
+        .status()
+}
+
+#[test]
+fn resource_is_read_from_path() {
+    let client = get_client();
+    let response = client
+        .put("/document/1")
+        .header(Header::new("X-User", "editor"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().unwrap(), "editor 1");
+    assert_eq!(
+        get_status(&client, "put", "/document/1", "reader"),
+        Status::Forbidden
+    );
+    assert_eq!(
+        get_status(&client, "put", "/document/2", "editor"),
+        Status::Forbidden
+    );
+}
+
+#[test]
+fn resource_is_read_from_path_segment_after_mount_point() {
+    let client = get_client();
+    let response = client
+        .get("/document/report/1/detail")
+        .header(Header::new("X-User", "reader"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().unwrap(), "report 1");
+    assert_eq!(
+        get_status(&client, "get", "/document/report/1/detail", "other"),
+        Status::Forbidden
+    );
+    assert_eq!(
+        get_status(&client, "get", "/document/report/2/detail", "reader"),
+        Status::Forbidden
+    );
+}
+
+#[test]
+fn resource_is_read_from_query() {
+    let client = get_client();
+    let response = client
+        .get("/document?page=3&document_id=1")
+        .header(Header::new("X-User", "reader"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().unwrap(), "3 1");
+    assert_eq!(
+        get_status(&client, "get", "/document?document_id=1", "other"),
+        Status::Forbidden
+    );
+    assert_eq!(
+        get_status(&client, "get", "/document?document_id=2", "reader"),
+        Status::Forbidden
+    );
+}
+
+#[test]
+fn guard_error_comes_before_resource() {
+    let client = get_client();
+    assert_eq!(
+        client.put("/document/2").dispatch().status(),
+        Status::Unauthorized
+    );
+}
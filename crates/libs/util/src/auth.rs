@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 
 pub mod basic;
 pub mod jwt;
+pub mod oidc;
+pub mod pkce;
+pub mod totp;
 
 /// Enum representing different token types.
 ///
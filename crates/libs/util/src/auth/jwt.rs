@@ -1,4 +1,5 @@
 use super::TokenType;
+use crate::settings::SETTINGS;
 use rocket::form::{FromForm, FromFormField};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -9,13 +10,25 @@ pub const OAUTH2_ACCEESS_LIFE_SEC: u32 = 900;
 pub const OAUTH2_REFRESH_LIFE_SEC: u32 = 1_296_000;
 
 /// Enum representing the two types of OAuth2 tokens: AccessToken and RefreshToken.
-#[derive(Debug, Serialize, Deserialize, JsonSchema, FromFormField)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, FromFormField)]
 #[serde(rename_all = "snake_case")]
 pub enum Oauth2TokenType {
     AccessToken,
     RefreshToken,
 }
 
+/// Enum representing the supported OAuth2 grant types of the token endpoint.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, FromFormField)]
+#[serde(rename_all = "snake_case")]
+pub enum Oauth2GrantType {
+    #[field(value = "refresh_token")]
+    RefreshToken,
+    #[field(value = "client_credentials")]
+    ClientCredentials,
+    #[field(value = "authorization_code")]
+    AuthorizationCode,
+}
+
 /// Base struct representing the claims within an OAuth2 token.
 ///
 /// # Example
@@ -28,13 +41,15 @@ pub enum Oauth2TokenType {
 ///     oauth2_claims: Oauth2TokenClaims,
 /// }
 /// ```
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Oauth2TokenClaims {
+    pub iss: String,
     pub iat: u64,
     pub nbf: u64,
     pub exp: u64,
     pub jti: Uuid,
     pub sub_jti: Uuid,
+    pub sid: Uuid,
     pub oauth_token_type: Oauth2TokenType,
 }
 
@@ -50,30 +65,70 @@ impl Oauth2TokenClaims {
     ///
     /// This method generates new claims for both an access token and a refresh token,
     /// including setting their respective expiration times and unique identifiers.
+    /// Both tokens are bound to a newly started session (`sid`).
     pub fn new_claims() -> (Self, Self) {
+        Self::new_session_claims(Uuid::new_v4())
+    }
+
+    /// Creates a new pair of access and refresh token claims for an existing session.
+    ///
+    /// Used on refresh token rotation: the new pair gets fresh `jti`/`sub_jti` identifiers
+    /// and lifetimes, but keeps the session identifier (`sid`) of the rotated pair, so every
+    /// token issued since the login can be found (and revoked) as one session.
+    ///
+    /// # Example
+    /// ```rust
+    /// let (access, refresh) = Oauth2TokenClaims::new_claims();
+    /// let (new_access, new_refresh) = Oauth2TokenClaims::new_session_claims(refresh.sid);
+    /// assert_eq!(new_access.sid, access.sid);
+    /// ```
+    pub fn new_session_claims(sid: Uuid) -> (Self, Self) {
         let access_id = Uuid::new_v4();
         let refresh_id = Uuid::new_v4();
         let now_unix = OffsetDateTime::now_utc().unix_timestamp() as u64;
         (
             Oauth2TokenClaims {
+                iss: SETTINGS.jwt.issuer.to_owned(),
                 iat: now_unix,
                 nbf: now_unix,
                 sub_jti: refresh_id,
                 jti: access_id,
+                sid,
                 exp: now_unix + OAUTH2_ACCEESS_LIFE_SEC as u64,
                 oauth_token_type: Oauth2TokenType::AccessToken,
             },
             Oauth2TokenClaims {
+                iss: SETTINGS.jwt.issuer.to_owned(),
                 iat: now_unix,
                 nbf: now_unix,
                 sub_jti: access_id,
                 jti: refresh_id,
+                sid,
                 exp: now_unix + OAUTH2_REFRESH_LIFE_SEC as u64,
                 oauth_token_type: Oauth2TokenType::RefreshToken,
             },
         )
     }
 
+    /// Creates the claims of a single access token, issued without a refresh token.
+    ///
+    /// Used by the `client_credentials` grant: the client requests a new token with its
+    /// credentials once the previous one expired. `sub_jti` points to the token itself.
+    pub fn new_access_claims() -> Self {
+        let access_id = Uuid::new_v4();
+        let now_unix = OffsetDateTime::now_utc().unix_timestamp() as u64;
+        Oauth2TokenClaims {
+            iss: SETTINGS.jwt.issuer.to_owned(),
+            iat: now_unix,
+            nbf: now_unix,
+            sub_jti: access_id,
+            jti: access_id,
+            sid: Uuid::new_v4(),
+            exp: now_unix + OAUTH2_ACCEESS_LIFE_SEC as u64,
+            oauth_token_type: Oauth2TokenType::AccessToken,
+        }
+    }
+
     /// Validates whether the token's date range is valid.
     ///
     /// Checks if the token is not yet active (based on the `nbf` field) and if it has not expired
@@ -94,6 +149,12 @@ impl Oauth2TokenClaims {
         self.exp - self.nbf
     }
 
+    /// Returns the number of seconds left until the token expires (`0` if already expired).
+    pub fn get_remaining_life_sec(&self) -> u64 {
+        let utc_now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+        self.exp.saturating_sub(utc_now)
+    }
+
     /// Checks if the token is an access token.
     pub fn is_access(&self) -> bool {
         matches!(self.oauth_token_type, Oauth2TokenType::AccessToken)
@@ -123,40 +184,99 @@ pub fn extract_bearer_token(header: &str) -> Result<String, ExtractError> {
 }
 
 /// Struct representing the result of an OAuth2 login.
+///
+/// `refresh_token` is not issued for the `client_credentials` grant.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Oauth2LoginResult {
     pub token_type: TokenType,
     pub expires_in: u64,
     pub access_token: String,
-    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Space separated scopes granted to the token (`client_credentials` and
+    /// `authorization_code` grants).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// OpenID Connect ID token, issued for the `authorization_code` grant with the `openid` scope.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
 }
 
-/// Struct representing the result of an OAuth2 token introspection.
+/// Struct representing the result of an OAuth2 token introspection (RFC 7662).
+///
+/// Only `active` is returned for an inactive token.
 #[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 pub struct IntrospectResult {
     pub active: bool,
+    /// Space separated scopes of the token.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scope: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_id: Option<String>,
+    /// Human readable identifier of the owner of the token.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub username: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub token_type: Option<String>,
+    pub token_type: Option<TokenType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exp: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub iat: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nbf: Option<u64>,
+    /// Identifier of the owner of the token.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub sub: Option<u64>,
+    pub sub: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub aud: Option<u64>,
+    pub aud: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub iss: Option<u64>,
+    pub iss: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub jti: Option<u64>,
+    pub jti: Option<String>,
+}
+
+impl IntrospectResult {
+    /// Creates the result of an inactive (unknown, expired or revoked) token.
+    pub fn inactive() -> Self {
+        Self {
+            active: false,
+            ..Default::default()
+        }
+    }
+
+    /// Creates the result of an active token, filled with its claims.
+    pub fn from_claims(claims: &Oauth2TokenClaims, sub: String) -> Self {
+        Self {
+            active: true,
+            token_type: Some(TokenType::Bearer),
+            exp: Some(claims.exp),
+            iat: Some(claims.iat),
+            nbf: Some(claims.nbf),
+            sub: Some(sub),
+            iss: Some(claims.iss.to_owned()),
+            jti: Some(claims.jti.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Struct representing the input of the OAuth2 token endpoint.
+///
+/// Which of the optional fields are required depends on the `grant_type`:
+/// - `refresh_token`: `refresh_token` must be set.
+/// - `client_credentials`: `client_id` and `client_secret` must be set, unless sent with the
+///   HTTP Basic authorization header; `scope` (space separated) is optional.
+/// - `authorization_code`: `code`, `redirect_uri`, `client_id` and `code_verifier` must be set.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, FromForm)]
+pub struct TokenInput {
+    pub grant_type: Oauth2GrantType,
+    pub refresh_token: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub scope: Option<String>,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
 }
 
 /// Struct representing the input for token introspection.
@@ -166,6 +286,13 @@ pub struct IntrospectInput {
     pub token_type_hint: Option<Oauth2TokenType>,
 }
 
+/// Struct representing the input for token revocation (RFC 7009).
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema, FromForm)]
+pub struct RevokeInput {
+    pub token: String,
+    pub token_type_hint: Option<Oauth2TokenType>,
+}
+
 impl IntrospectInput {
     /// Determines whether the token is an access token.
     pub fn is_access(&self) -> bool {
@@ -0,0 +1,80 @@
+use jsonwebtoken::Algorithm;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::jwt::OAUTH2_ACCEESS_LIFE_SEC;
+use crate::settings::SETTINGS;
+
+/// Scope requesting an ID token.
+pub const SCOPE_OPENID: &str = "openid";
+/// Scope requesting the `name` claim.
+pub const SCOPE_PROFILE: &str = "profile";
+/// Scope requesting the `email` claim.
+pub const SCOPE_EMAIL: &str = "email";
+/// Scopes the applications can request.
+pub const SCOPES_SUPPORTED: [&str; 3] = [SCOPE_OPENID, SCOPE_PROFILE, SCOPE_EMAIL];
+
+/// Standard claims of the user, returned by the userinfo endpoint and in the ID token.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct UserInfo {
+    /// Identifier of the user.
+    pub sub: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// Claims of an OpenID Connect ID token.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    /// Identifier of the client (application) the token is issued to.
+    pub aud: String,
+    pub iat: u64,
+    pub exp: u64,
+    /// Time the user authenticated.
+    pub auth_time: u64,
+    /// Value of the authentication request, echoed to mitigate replay attacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(flatten)]
+    pub user_info: UserInfo,
+}
+
+impl IdTokenClaims {
+    /// Creates the claims of an ID token living as long as an access token.
+    pub fn new(user_info: UserInfo, aud: String, auth_time: u64, nonce: Option<String>) -> Self {
+        let now_unix = OffsetDateTime::now_utc().unix_timestamp() as u64;
+        Self {
+            iss: SETTINGS.jwt.issuer.to_owned(),
+            aud,
+            iat: now_unix,
+            exp: now_unix + OAUTH2_ACCEESS_LIFE_SEC as u64,
+            auth_time,
+            nonce,
+            user_info,
+        }
+    }
+}
+
+/// Metadata of the OpenID provider (OpenID Connect Discovery 1.0).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenIdConfiguration {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub jwks_uri: String,
+    pub revocation_endpoint: String,
+    pub introspection_endpoint: String,
+    pub response_types_supported: Vec<String>,
+    pub grant_types_supported: Vec<String>,
+    pub subject_types_supported: Vec<String>,
+    pub id_token_signing_alg_values_supported: Vec<Algorithm>,
+    pub scopes_supported: Vec<String>,
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    pub code_challenge_methods_supported: Vec<String>,
+    pub claims_supported: Vec<String>,
+}
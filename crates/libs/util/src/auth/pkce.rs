@@ -0,0 +1,55 @@
+use base64::prelude::*;
+use rocket::form::FromFormField;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::constant_time_eq;
+
+/// Transformation of the code verifier into the code challenge (RFC 7636).
+///
+/// Only `S256` is supported, `plain` gives no protection once the challenge leaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, FromFormField)]
+pub enum CodeChallengeMethod {
+    #[field(value = "S256")]
+    S256,
+}
+
+/// Checks the code verifier has 43 to 128 unreserved characters.
+pub fn is_valid_code_verifier(code_verifier: &str) -> bool {
+    (43..=128).contains(&code_verifier.len())
+        && code_verifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'))
+}
+
+/// Returns the code challenge of the code verifier.
+///
+/// # Example
+/// ```rust
+/// use util_lib::auth::pkce::{get_code_challenge, CodeChallengeMethod};
+///
+/// let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+/// let challenge = get_code_challenge(verifier, CodeChallengeMethod::S256);
+/// assert_eq!(challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+/// ```
+pub fn get_code_challenge(code_verifier: &str, method: CodeChallengeMethod) -> String {
+    match method {
+        CodeChallengeMethod::S256 => {
+            BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+        }
+    }
+}
+
+/// Checks the code verifier matches the code challenge sent on the authorization request.
+pub fn verify_code_challenge(
+    code_verifier: &str,
+    code_challenge: &str,
+    method: CodeChallengeMethod,
+) -> bool {
+    is_valid_code_verifier(code_verifier)
+        && constant_time_eq(
+            get_code_challenge(code_verifier, method).as_bytes(),
+            code_challenge.as_bytes(),
+        )
+}
@@ -0,0 +1,106 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+
+use crate::crypto::constant_time_eq;
+
+/// Length of the generated secrets in bytes (the size of the SHA-1 output, RFC 4226).
+pub const SECRET_LEN: usize = 20;
+/// Number of digits of a code.
+pub const DIGITS: u32 = 6;
+/// Lifetime of a code in seconds.
+pub const STEP_SEC: u64 = 30;
+
+/// Returns a new random secret.
+pub fn gen_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_LEN];
+    rand::rng().fill_bytes(&mut secret);
+    secret
+}
+
+/// Encodes the secret in base32 without padding, the format of authenticator apps.
+pub fn encode_secret(secret: &[u8]) -> String {
+    BASE32_NOPAD.encode(secret)
+}
+
+/// Decodes a base32 secret, spaces and lowercase letters are accepted.
+pub fn decode_secret(secret: &str) -> Option<Vec<u8>> {
+    let secret: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    BASE32_NOPAD
+        .decode(secret.trim_end_matches('=').as_bytes())
+        .ok()
+}
+
+/// Returns the time step of the unix time.
+pub fn get_step(unix_time: u64) -> u64 {
+    unix_time / STEP_SEC
+}
+
+/// Returns the code of the time step (HOTP of RFC 4226 with the step as counter).
+///
+/// # Example
+/// ```rust
+/// use util_lib::auth::totp::{get_code, get_step};
+///
+/// // Test vector of RFC 6238 appendix B (last 6 digits)
+/// let code = get_code(b"12345678901234567890", get_step(59));
+/// assert_eq!(code, "287082");
+/// ```
+pub fn get_code(secret: &[u8], step: u64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).unwrap();
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    // Dynamic truncation
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        value % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// Checks the code against the steps around the unix time and returns the matching step.
+///
+/// `skew` is the number of steps accepted before and after the current one, to allow for clock
+/// drift. The caller should reject steps already used to prevent the replay of a code.
+pub fn verify(secret: &[u8], code: &str, unix_time: u64, skew: u64) -> Option<u64> {
+    let step = get_step(unix_time);
+    (step.saturating_sub(skew)..=step + skew)
+        .find(|v| constant_time_eq(get_code(secret, *v).as_bytes(), code.as_bytes()))
+}
+
+/// Returns the `otpauth://` URI to enrol the secret in an authenticator app (often as QR code).
+pub fn get_provisioning_uri(secret: &[u8], issuer: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        encode_secret(secret),
+        percent_encode(issuer),
+        DIGITS,
+        STEP_SEC
+    )
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(
+            |b| match b.is_ascii_alphanumeric() || b"-._~@".contains(&b) {
+                true => (b as char).to_string(),
+                false => format!("%{:02X}", b),
+            },
+        )
+        .collect()
+}
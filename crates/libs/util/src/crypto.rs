@@ -1,11 +1,166 @@
+pub mod argon2;
 pub mod bcrypt;
 
+pub use argon2::Argon2id;
 pub use bcrypt::Bcrypt;
 
+use std::fmt;
+
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::settings::{Hashing, SETTINGS};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ErrorVerify {
+    /// The hash is malformed or made with another algorithm than the one of the hasher.
+    InvalidHash,
+    /// The algorithm of the hash isn't supported.
+    UnsupportedAlgorithm,
+}
+
+/// Error to get the algorithm of the new hashes from the settings.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ErrorAlgorithm {
+    /// `HASHING_ALGORITHM` is not `argon2id` or `bcrypt`.
+    UnsupportedAlgorithm(String),
+}
+
+impl fmt::Display for ErrorAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedAlgorithm(v) => write!(
+                f,
+                "unsupported HASHING_ALGORITHM {}, expected argon2id or bcrypt",
+                v
+            ),
+        }
+    }
+}
+
 pub trait Hasher {
     /// Hash the input string and return the hashed value.
     fn hash(&self, input: &str) -> String;
 
     /// Verify whether the given input matches the hashed value.
-    fn verify(&self, input: &str, hash: &str) -> bool;
+    fn verify(&self, input: &str, hash: &str) -> Result<bool, ErrorVerify>;
+
+    /// Check whether the hash was made with other parameters (or algorithm) than the hasher's.
+    fn needs_rehash(&self, hash: &str) -> bool;
+}
+
+/// Algorithm of a hash, detected from its prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Argon2id,
+    Bcrypt,
+}
+
+impl Algorithm {
+    pub fn from_hash(hash: &str) -> Option<Self> {
+        if hash.starts_with("$argon2id$") {
+            return Some(Self::Argon2id);
+        }
+        if ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|v| hash.starts_with(v))
+        {
+            return Some(Self::Bcrypt);
+        }
+        None
+    }
+
+    /// Returns the algorithm of the new hashes of the settings, called once at startup so an
+    /// unsupported algorithm stops the service.
+    pub fn from_settings(settings: &Hashing) -> Result<Self, ErrorAlgorithm> {
+        match settings.algorithm.to_lowercase().as_str() {
+            "argon2id" => Ok(Self::Argon2id),
+            "bcrypt" => Ok(Self::Bcrypt),
+            v => Err(ErrorAlgorithm::UnsupportedAlgorithm(v.to_string())),
+        }
+    }
+
+    /// Returns the algorithm of the new hashes (`HASHING_ALGORITHM`).
+    ///
+    /// # Panics
+    /// If the algorithm of the settings isn't supported, which [`Algorithm::from_settings`]
+    /// checks at startup.
+    pub fn get_default() -> Self {
+        match Self::from_settings(&SETTINGS.hashing) {
+            Ok(v) => v,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// Returns the hasher of the algorithm with the parameters of the settings.
+pub fn get_hasher(algorithm: Algorithm) -> Box<dyn Hasher + Send + Sync> {
+    match algorithm {
+        Algorithm::Argon2id => Box::new(Argon2id::new()),
+        Algorithm::Bcrypt => Box::new(Bcrypt::new()),
+    }
+}
+
+/// Returns the hasher of the new hashes.
+pub fn get_default_hasher() -> Box<dyn Hasher + Send + Sync> {
+    get_hasher(Algorithm::get_default())
+}
+
+/// Verifies the input against a hash of any supported algorithm.
+///
+/// # Example
+/// ```rust
+/// use util_lib::crypto::{verify, Bcrypt, Hasher};
+///
+/// let hash = Bcrypt::with_cost(4).hash("password");
+/// assert_eq!(verify("password", &hash), Ok(true));
+/// assert_eq!(verify("wrong", &hash), Ok(false));
+/// ```
+pub fn verify(input: &str, hash: &str) -> Result<bool, ErrorVerify> {
+    match Algorithm::from_hash(hash) {
+        Some(v) => get_hasher(v).verify(input, hash),
+        None => Err(ErrorVerify::UnsupportedAlgorithm),
+    }
+}
+
+/// Checks the hash isn't made with the algorithm and the parameters of the new hashes.
+pub fn needs_rehash(hash: &str) -> bool {
+    let algorithm = Algorithm::get_default();
+    Algorithm::from_hash(hash) != Some(algorithm) || get_hasher(algorithm).needs_rehash(hash)
+}
+
+/// Compares two byte strings in a time depending only on their length.
+///
+/// Used to compare secrets without leaking the position of the first difference.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Generates a random URL-safe token of `len` random bytes.
+pub fn gen_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::rng().fill_bytes(&mut bytes);
+    BASE64URL_NOPAD.encode(&bytes)
+}
+
+/// Returns the SHA-256 hex digest of a token.
+///
+/// Meant for random tokens only: they have enough entropy for a fast hash, and the digest can be
+/// used as a lookup key. Passwords must be hashed with a [`Hasher`].
+///
+/// # Example
+/// ```rust
+/// use util_lib::crypto::get_token_digest;
+///
+/// assert_eq!(
+///     get_token_digest("abc"),
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+/// );
+/// ```
+pub fn get_token_digest(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
 }
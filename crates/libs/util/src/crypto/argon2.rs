@@ -0,0 +1,86 @@
+use super::{ErrorVerify, Hasher};
+use crate::settings::SETTINGS;
+use argon2::{
+    password_hash::{rand_core::OsRng, Error as PasswordHashError, PasswordHash, SaltString},
+    Algorithm, Argon2, Params, PasswordHasher, PasswordVerifier, Version,
+};
+
+/// Argon2id hasher, the hashes are in PHC string format
+/// (`$argon2id$v=19$m=<memory>,t=<iterations>,p=<parallelism>$<salt>$<hash>`).
+pub struct Argon2id {
+    params: Params,
+}
+
+impl Argon2id {
+    /// Returns the hasher with the parameters of the settings (`HASHING_ARGON2_*`).
+    pub fn new() -> Self {
+        Self::with_params(
+            SETTINGS.hashing.argon2_memory_kib,
+            SETTINGS.hashing.argon2_iterations,
+            SETTINGS.hashing.argon2_parallelism,
+        )
+    }
+
+    /// Returns the hasher with the given memory cost (KiB), iterations and parallelism.
+    ///
+    /// # Panics
+    /// If the parameters are out of the ranges of Argon2.
+    pub fn with_params(memory_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        Self {
+            params: Params::new(memory_kib, iterations, parallelism, None).unwrap(),
+        }
+    }
+
+    fn get_argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
+
+impl Default for Argon2id {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Argon2id {
+    fn hash(&self, input: &str) -> String {
+        let salt = SaltString::generate(&mut OsRng);
+        self.get_argon2()
+            .hash_password(input.as_bytes(), &salt)
+            .unwrap()
+            .to_string()
+    }
+
+    fn verify(&self, input: &str, hash: &str) -> Result<bool, ErrorVerify> {
+        let hash = PasswordHash::new(hash).map_err(|_| ErrorVerify::InvalidHash)?;
+        if hash.algorithm != Algorithm::Argon2id.ident() || hash.hash.is_none() {
+            return Err(ErrorVerify::InvalidHash);
+        }
+        // The parameters are read from the hash
+        match Argon2::default().verify_password(input.as_bytes(), &hash) {
+            Ok(_) => Ok(true),
+            Err(PasswordHashError::Password) => Ok(false),
+            Err(_) => Err(ErrorVerify::InvalidHash),
+        }
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        let hash = match PasswordHash::new(hash) {
+            Ok(v) => v,
+            Err(_) => return true,
+        };
+        if hash.algorithm != Algorithm::Argon2id.ident()
+            || hash.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+        match Params::try_from(&hash) {
+            Ok(v) => {
+                v.m_cost() != self.params.m_cost()
+                    || v.t_cost() != self.params.t_cost()
+                    || v.p_cost() != self.params.p_cost()
+            }
+            Err(_) => true,
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use super::Hasher;
+use super::{ErrorVerify, Hasher};
+use crate::settings::SETTINGS;
 use bcrypt::{hash, verify};
 
 pub struct Bcrypt {
@@ -6,8 +7,19 @@ pub struct Bcrypt {
 }
 
 impl Bcrypt {
+    /// Returns the hasher with the cost of the settings (`HASHING_BCRYPT_COST`).
     pub fn new() -> Self {
-        Self { cost: 10 }
+        Self::with_cost(SETTINGS.hashing.bcrypt_cost)
+    }
+
+    pub fn with_cost(cost: u32) -> Self {
+        Self { cost }
+    }
+}
+
+impl Default for Bcrypt {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -16,7 +28,12 @@ impl Hasher for Bcrypt {
         hash(input, self.cost).unwrap()
     }
 
-    fn verify(&self, input: &str, hash: &str) -> bool {
-        verify(input, hash).unwrap()
+    fn verify(&self, input: &str, hash: &str) -> Result<bool, ErrorVerify> {
+        verify(input, hash).map_err(|_| ErrorVerify::InvalidHash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        // Modular crypt format: `$2b$<cost>$<salt and hash>`
+        hash.split('$').nth(2).and_then(|v| v.parse::<u32>().ok()) != Some(self.cost)
     }
 }
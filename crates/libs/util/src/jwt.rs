@@ -1,15 +1,18 @@
-use crate::settings::SETTINGS;
+pub mod key;
+
 pub use jsonwebtoken::errors::Result as JWTResult;
 use jsonwebtoken::{
-    decode as jwt_decode, encode as jwt_encode, DecodingKey, EncodingKey, Header, TokenData,
-    Validation,
+    decode as jwt_decode, decode_header, encode as jwt_encode, errors::ErrorKind, Header,
+    TokenData, Validation,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
+use key::KEYS;
+
 /// Encodes the provided claims into a JWT token.
 ///
-/// This function is used to create a JWT token using the secret key from settings. It takes a struct that is serializable to JSON,
-/// and encodes it into a string using the HMAC algorithm with the secret key from the settings.
+/// This function is used to create a JWT token using the signing key from settings (see [`key::KeyStore`]). It takes a struct
+/// that is serializable to JSON, and encodes it into a string with the configured algorithm and the `kid` of the signing key.
 ///
 /// # Parameters
 /// - `claims`: A serializable structure that will be used as the payload (claims) of the token.
@@ -23,17 +26,15 @@ use serde::{de::DeserializeOwned, Serialize};
 /// let token = encode(&claims).unwrap();
 /// ```
 pub fn encode<ClaimsType: Serialize>(claims: &ClaimsType) -> JWTResult<String> {
-    jwt_encode(
-        &Header::default(),
-        claims,
-        &EncodingKey::from_secret(SETTINGS.jwt.secret.as_ref()),
-    )
+    let mut header = Header::new(KEYS.get_algorithm());
+    header.kid = KEYS.get_kid().map(|v| v.to_string());
+    jwt_encode(&header, claims, KEYS.get_signing_key())
 }
 
 /// Decodes the provided JWT token into claims.
 ///
-/// This function is used to decode a JWT token into the claims of the specified type using the secret key from settings.
-/// It validates the token using the default validation settings.
+/// This function is used to decode a JWT token into the claims of the specified type using the verification key matching
+/// the `kid` of the token header. Tokens signed with another algorithm than the configured one are refused.
 ///
 /// # Parameters
 /// - `token`: The JWT token to decode.
@@ -46,10 +47,17 @@ pub fn encode<ClaimsType: Serialize>(claims: &ClaimsType) -> JWTResult<String> {
 /// let token = "jwt_token_here".to_string();
 /// let decoded = decode::<MyClaims>(&token).unwrap();
 /// ```
-pub fn decode<Claims: DeserializeOwned>(token: &String) -> JWTResult<TokenData<Claims>> {
-    jwt_decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(SETTINGS.jwt.secret.as_ref()),
-        &Validation::default(),
-    )
+pub fn decode<Claims: DeserializeOwned>(token: &str) -> JWTResult<TokenData<Claims>> {
+    let header = decode_header(token)?;
+    if header.alg != KEYS.get_algorithm() {
+        return Err(ErrorKind::InvalidAlgorithm.into());
+    }
+    let key = match KEYS.get_verifying_key(header.kid.as_deref()) {
+        Some(v) => v,
+        None => return Err(ErrorKind::InvalidToken.into()),
+    };
+    // The audience of the tokens issued to an application is checked by the callers
+    let mut validation = Validation::new(header.alg);
+    validation.validate_aud = false;
+    jwt_decode::<Claims>(token, key, &validation)
 }
@@ -0,0 +1,274 @@
+use std::{fmt, fs, str::FromStr};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+pub use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey,
+};
+use once_cell::sync::Lazy;
+use simple_asn1::{from_der, ASN1Block, BigUint, OID};
+
+use crate::settings::{JWT, SETTINGS};
+
+/// Keys of the service, loaded once from the `JWT_*` settings.
+pub static KEYS: Lazy<KeyStore> = Lazy::new(|| KeyStore::from_settings(&SETTINGS.jwt).unwrap());
+
+/// Error to load the keys from the settings.
+#[derive(Debug)]
+pub enum ErrorKey {
+    /// `JWT_ALGORITHM` is not `HS256`, `RS256` or `EdDSA`.
+    UnsupportedAlgorithm(String),
+    /// A setting required by the algorithm is empty.
+    MissingSetting(&'static str),
+    /// An entry of `JWT_PUBLIC_KEYS` is not `<kid>=<path>`.
+    InvalidPublicKeys(String),
+    /// The key file can't be read.
+    Io(String, std::io::Error),
+    /// The key file is not a PEM key of the algorithm.
+    InvalidKey(String),
+    /// No public key of `JWT_PUBLIC_KEYS` matches `JWT_KID`.
+    MissingSigningPublicKey(String),
+}
+
+impl fmt::Display for ErrorKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedAlgorithm(v) => write!(f, "unsupported jwt algorithm: {}", v),
+            Self::MissingSetting(v) => write!(f, "missing setting: {}", v),
+            Self::InvalidPublicKeys(v) => write!(f, "invalid public key entry: {}", v),
+            Self::Io(path, e) => write!(f, "can't read key {}: {}", path, e),
+            Self::InvalidKey(path) => write!(f, "invalid key: {}", path),
+            Self::MissingSigningPublicKey(kid) => {
+                write!(f, "missing public key of the signing key: {}", kid)
+            }
+        }
+    }
+}
+
+/// Key used to verify the tokens with its identifier.
+pub struct VerifyingKey {
+    pub kid: Option<String>,
+    pub key: DecodingKey,
+}
+
+/// Signing key and verification keys of the tokens.
+///
+/// With `HS256` the tokens are signed and verified with the shared `JWT_SECRET`. With `RS256` or
+/// `EdDSA` the tokens are signed with the private key `JWT_PRIVATE_KEY` and carry its `JWT_KID` in
+/// their header. They are verified with the public key of the same `kid` from `JWT_PUBLIC_KEYS`,
+/// which are published as a JWK set.
+///
+/// To rotate the signing key, add the public key of the new key to `JWT_PUBLIC_KEYS`, switch
+/// `JWT_PRIVATE_KEY` and `JWT_KID` to the new key, and remove the old public key once the tokens
+/// signed with it are expired.
+pub struct KeyStore {
+    algorithm: Algorithm,
+    kid: Option<String>,
+    signing_key: EncodingKey,
+    verifying_keys: Vec<VerifyingKey>,
+    jwk_set: JwkSet,
+}
+
+impl KeyStore {
+    /// Loads the keys from the settings.
+    ///
+    /// # Settings
+    /// * `JWT_ALGORITHM` - `HS256` (default), `RS256` or `EdDSA`.
+    /// * `JWT_SECRET` - Shared secret of `HS256`.
+    /// * `JWT_KID` - Identifier of the signing key (required by `RS256` and `EdDSA`).
+    /// * `JWT_PRIVATE_KEY` - Path of the PEM private key signing the tokens.
+    /// * `JWT_PUBLIC_KEYS` - Comma separated `<kid>=<path>` of the PEM public keys verifying the
+    ///   tokens, the public key of the signing key included.
+    pub fn from_settings(settings: &JWT) -> Result<Self, ErrorKey> {
+        let algorithm = Algorithm::from_str(&settings.algorithm)
+            .map_err(|_| ErrorKey::UnsupportedAlgorithm(settings.algorithm.to_owned()))?;
+        let kid = match settings.kid.is_empty() {
+            true => None,
+            false => Some(settings.kid.to_owned()),
+        };
+
+        // Symmetric secret is never published
+        if algorithm == Algorithm::HS256 {
+            return Ok(Self {
+                algorithm,
+                kid: kid.to_owned(),
+                signing_key: EncodingKey::from_secret(settings.secret.as_ref()),
+                verifying_keys: vec![VerifyingKey {
+                    kid,
+                    key: DecodingKey::from_secret(settings.secret.as_ref()),
+                }],
+                jwk_set: JwkSet { keys: Vec::new() },
+            });
+        }
+        if !matches!(algorithm, Algorithm::RS256 | Algorithm::EdDSA) {
+            return Err(ErrorKey::UnsupportedAlgorithm(
+                settings.algorithm.to_owned(),
+            ));
+        }
+        let kid = kid.ok_or(ErrorKey::MissingSetting("JWT_KID"))?;
+        if settings.private_key.is_empty() {
+            return Err(ErrorKey::MissingSetting("JWT_PRIVATE_KEY"));
+        }
+        if settings.public_keys.is_empty() {
+            return Err(ErrorKey::MissingSetting("JWT_PUBLIC_KEYS"));
+        }
+
+        let private_pem = read_key(&settings.private_key)?;
+        let signing_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&private_pem),
+            _ => EncodingKey::from_ed_pem(&private_pem),
+        }
+        .map_err(|_| ErrorKey::InvalidKey(settings.private_key.to_owned()))?;
+
+        let mut verifying_keys = Vec::<VerifyingKey>::new();
+        let mut jwk_set = JwkSet { keys: Vec::new() };
+        for entry in settings.public_keys.split(',') {
+            let (public_kid, path) = match entry.trim().split_once('=') {
+                Some((k, p)) if !k.trim().is_empty() && !p.trim().is_empty() => {
+                    (k.trim(), p.trim())
+                }
+                _ => return Err(ErrorKey::InvalidPublicKeys(entry.to_owned())),
+            };
+            let jwk = get_public_jwk(algorithm, public_kid, &read_key(path)?)
+                .ok_or(ErrorKey::InvalidKey(path.to_owned()))?;
+            let key =
+                DecodingKey::from_jwk(&jwk).map_err(|_| ErrorKey::InvalidKey(path.to_owned()))?;
+            verifying_keys.push(VerifyingKey {
+                kid: Some(public_kid.to_owned()),
+                key,
+            });
+            jwk_set.keys.push(jwk);
+        }
+        if jwk_set.find(&kid).is_none() {
+            return Err(ErrorKey::MissingSigningPublicKey(kid));
+        }
+
+        Ok(Self {
+            algorithm,
+            kid: Some(kid),
+            signing_key,
+            verifying_keys,
+            jwk_set,
+        })
+    }
+
+    /// Returns the algorithm of the tokens.
+    pub fn get_algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Checks the tokens are signed with the shared secret (`HS256`), only the service can verify
+    /// them.
+    pub fn is_symmetric(&self) -> bool {
+        self.algorithm == Algorithm::HS256
+    }
+
+    /// Returns the identifier of the signing key.
+    pub fn get_kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// Returns the key signing the tokens.
+    pub fn get_signing_key(&self) -> &EncodingKey {
+        &self.signing_key
+    }
+
+    /// Returns the key verifying a token signed by the key `kid`.
+    ///
+    /// A token without `kid` is accepted only if a single verification key is configured.
+    pub fn get_verifying_key(&self, kid: Option<&str>) -> Option<&DecodingKey> {
+        match kid {
+            Some(kid) => self
+                .verifying_keys
+                .iter()
+                .find(|v| v.kid.as_deref() == Some(kid))
+                .map(|v| &v.key),
+            None => match self.verifying_keys.as_slice() {
+                [v] => Some(&v.key),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the public keys verifying the tokens, empty with `HS256`.
+    pub fn get_jwk_set(&self) -> &JwkSet {
+        &self.jwk_set
+    }
+}
+
+fn read_key(path: &str) -> Result<Vec<u8>, ErrorKey> {
+    fs::read(path).map_err(|e| ErrorKey::Io(path.to_owned(), e))
+}
+
+/// Converts a PEM public key (SPKI, or PKCS#1 for RSA) into a JWK of the algorithm.
+fn get_public_jwk(algorithm: Algorithm, kid: &str, public_pem: &[u8]) -> Option<Jwk> {
+    let pem = pem::parse(public_pem).ok()?;
+    let algorithm_params = match (algorithm, pem.tag()) {
+        (Algorithm::RS256, "RSA PUBLIC KEY") => get_rsa_params(pem.contents())?,
+        (Algorithm::RS256, "PUBLIC KEY") => get_rsa_params(&get_spki_key(
+            pem.contents(),
+            &[1, 2, 840, 113_549, 1, 1, 1],
+        )?)?,
+        (Algorithm::EdDSA, "PUBLIC KEY") => {
+            AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(get_spki_key(pem.contents(), &[1, 3, 101, 112])?),
+            })
+        }
+        _ => return None,
+    };
+    let key_algorithm = match algorithm {
+        Algorithm::RS256 => KeyAlgorithm::RS256,
+        _ => KeyAlgorithm::EdDSA,
+    };
+    Some(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_owned()),
+            ..Default::default()
+        },
+        algorithm: algorithm_params,
+    })
+}
+
+/// Returns the key of a DER `SubjectPublicKeyInfo` if its algorithm matches `oid`.
+fn get_spki_key(der: &[u8], oid: &[u64]) -> Option<Vec<u8>> {
+    let expected_oid = OID::new(oid.iter().map(|v| BigUint::from(*v)).collect());
+    match from_der(der).ok()?.as_slice() {
+        [ASN1Block::Sequence(_, blocks)] => match blocks.as_slice() {
+            [ASN1Block::Sequence(_, algorithm), ASN1Block::BitString(_, _, key)] => {
+                match algorithm.first() {
+                    Some(ASN1Block::ObjectIdentifier(_, v)) if *v == expected_oid => {
+                        Some(key.to_owned())
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the JWK parameters of a DER `RSAPublicKey` (PKCS#1).
+fn get_rsa_params(der: &[u8]) -> Option<AlgorithmParameters> {
+    match from_der(der).ok()?.as_slice() {
+        [ASN1Block::Sequence(_, blocks)] => match blocks.as_slice() {
+            [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] => {
+                Some(AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(n.to_biguint()?.to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(e.to_biguint()?.to_bytes_be()),
+                }))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
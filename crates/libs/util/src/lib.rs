@@ -3,5 +3,6 @@ pub mod bool;
 pub mod crypto;
 pub mod date;
 pub mod jwt;
+pub mod retry;
 pub mod settings;
 pub mod string;
@@ -0,0 +1,39 @@
+use time::{Duration, OffsetDateTime};
+
+/// Retry policy of the messages sent by a background task (the outbox relay and the notification
+/// dispatcher): a failed message is retried with an exponential backoff, and given up after
+/// `max_attempts` attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt, doubled on every next failure.
+    pub backoff_base_sec: u64,
+    pub backoff_max_sec: u64,
+}
+
+impl RetryPolicy {
+    /// Returns the delay before the next attempt after the failed attempts, doubled on every
+    /// failure up to the maximum.
+    pub fn get_backoff_sec(&self, attempts: u32) -> u64 {
+        let factor = 2u64.saturating_pow(attempts.saturating_sub(1).min(63));
+        self.backoff_base_sec
+            .saturating_mul(factor)
+            .min(self.backoff_max_sec)
+    }
+
+    /// Returns the time of the next attempt after a failed attempt, `None` if the message is
+    /// given up.
+    ///
+    /// # Arguments
+    /// * `attempts` - Number of failed attempts, the last one included.
+    /// * `is_retryable` - Whether the error can be fixed by a later attempt.
+    pub fn get_retry_at(&self, attempts: u32, is_retryable: bool) -> Option<OffsetDateTime> {
+        match is_retryable && attempts < self.max_attempts {
+            true => Some(
+                OffsetDateTime::now_utc()
+                    + Duration::seconds(self.get_backoff_sec(attempts) as i64),
+            ),
+            false => None,
+        }
+    }
+}
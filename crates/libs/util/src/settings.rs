@@ -3,10 +3,12 @@ use once_cell::sync::Lazy;
 
 pub static SETTINGS: Lazy<Settings> = Lazy::new(|| Settings {
     jwt: JWT::from_env().unwrap(),
+    hashing: Hashing::from_env().unwrap(),
 });
 
 pub struct Settings {
     pub jwt: JWT,
+    pub hashing: Hashing,
 }
 
 #[derive(EnvSettings)]
@@ -14,4 +16,35 @@ pub struct Settings {
 pub struct JWT {
     #[env_settings(default = "secret")]
     pub secret: String,
+    /// Issuer (`iss`) of the tokens, the public base URL of the server for OpenID Connect
+    /// (e.g. `https://auth.example.com`).
+    #[env_settings(default = "rbca")]
+    pub issuer: String,
+    #[env_settings(default = "HS256")]
+    pub algorithm: String,
+    #[env_settings(default = "")]
+    pub kid: String,
+    /// Path of the PEM private key (`RS256`, `EdDSA`).
+    #[env_settings(default = "")]
+    pub private_key: String,
+    /// Comma separated `<kid>=<path>` of the PEM public keys (`RS256`, `EdDSA`).
+    #[env_settings(default = "")]
+    pub public_keys: String,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "HASHING_")]
+pub struct Hashing {
+    /// Algorithm of the new hashes (`argon2id` or `bcrypt`), older hashes are replaced on login.
+    #[env_settings(default = "argon2id")]
+    pub algorithm: String,
+    /// Memory cost of Argon2id in KiB.
+    #[env_settings(default = "19456")]
+    pub argon2_memory_kib: u32,
+    #[env_settings(default = "2")]
+    pub argon2_iterations: u32,
+    #[env_settings(default = "1")]
+    pub argon2_parallelism: u32,
+    #[env_settings(default = "12")]
+    pub bcrypt_cost: u32,
 }
@@ -63,6 +63,26 @@ pub mod validate {
         v.len() <= 2048
     }
 
+    /// Checks the string is an email address (`local@domain.tld`) of at most 255 characters.
+    ///
+    /// Only the shape is checked: one `@`, a local part of at most 64 characters and a domain of
+    /// non-empty dot separated labels, without whitespace.
+    pub fn is_email(v: &str) -> bool {
+        if !is_str_1_255(v) || v.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return false;
+        }
+        let (local, domain) = match v.split_once('@') {
+            Some(v) => v,
+            None => return false,
+        };
+        !local.is_empty()
+            && local.len() <= 64
+            && domain.contains('.')
+            && domain
+                .split('.')
+                .all(|label| !label.is_empty() && !label.contains('@'))
+    }
+
     /// Validates that a deserialized string has a length between 1 and 255 characters.
     ///
     /// This function uses the `validate::is_str_1_255` utility to enforce the constraint.
@@ -278,4 +298,27 @@ pub mod validate {
             Err(e) => Err(e),
         }
     }
+
+    /// Validates that a deserialized string is an email address.
+    ///
+    /// This function uses the `validate::is_email` utility to enforce the constraint.
+    ///
+    /// # Example
+    /// ```
+    /// #[derive(Deserialize)]
+    /// struct MyStruct {
+    ///     #[serde(deserialize_with = "email")]
+    ///     email: String,
+    /// }
+    /// ```
+    pub fn email<'de, D>(d: D) -> Result<String, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let v = String::deserialize(d)?;
+        if !validate::is_email(v.as_str()) {
+            return Err(D::Error::custom("invalid email address"));
+        }
+        Ok(v)
+    }
 }
@@ -0,0 +1,95 @@
+use util_lib::{
+    crypto::{
+        gen_token, get_token_digest, verify, Algorithm, Argon2id, Bcrypt, ErrorAlgorithm,
+        ErrorVerify, Hasher,
+    },
+    settings::Hashing,
+};
+
+// Small parameters, the tests check the format and not the cost
+fn argon2id() -> Argon2id {
+    Argon2id::with_params(1024, 1, 1)
+}
+
+#[test]
+fn argon2id_hash_is_phc_string() {
+    let hash = argon2id().hash("password");
+    assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+    assert_eq!(Algorithm::from_hash(&hash), Some(Algorithm::Argon2id));
+    assert_eq!(argon2id().verify("password", &hash), Ok(true));
+    assert_eq!(argon2id().verify("wrong", &hash), Ok(false));
+}
+
+#[test]
+fn algorithm_is_detected_on_verify() {
+    let argon2id_hash = argon2id().hash("password");
+    let bcrypt_hash = Bcrypt::with_cost(4).hash("password");
+    assert_eq!(Algorithm::from_hash(&bcrypt_hash), Some(Algorithm::Bcrypt));
+    assert_eq!(verify("password", &argon2id_hash), Ok(true));
+    assert_eq!(verify("password", &bcrypt_hash), Ok(true));
+    assert_eq!(verify("wrong", &bcrypt_hash), Ok(false));
+}
+
+#[test]
+fn malformed_hash_is_an_error() {
+    assert_eq!(
+        verify("password", "plain"),
+        Err(ErrorVerify::UnsupportedAlgorithm)
+    );
+    assert_eq!(
+        verify("password", "$argon2id$v=19$broken"),
+        Err(ErrorVerify::InvalidHash)
+    );
+    assert_eq!(
+        verify("password", "$2b$04$short"),
+        Err(ErrorVerify::InvalidHash)
+    );
+    let bcrypt_hash = Bcrypt::with_cost(4).hash("password");
+    assert_eq!(
+        argon2id().verify("password", &bcrypt_hash),
+        Err(ErrorVerify::InvalidHash)
+    );
+}
+
+#[test]
+fn rehash_is_needed_on_other_parameters() {
+    let hash = argon2id().hash("password");
+    assert!(!argon2id().needs_rehash(&hash));
+    assert!(Argon2id::with_params(2048, 1, 1).needs_rehash(&hash));
+    assert!(Argon2id::with_params(1024, 2, 1).needs_rehash(&hash));
+    assert!(argon2id().needs_rehash(&Bcrypt::with_cost(4).hash("password")));
+
+    let hash = Bcrypt::with_cost(4).hash("password");
+    assert!(!Bcrypt::with_cost(4).needs_rehash(&hash));
+    assert!(Bcrypt::with_cost(5).needs_rehash(&hash));
+}
+
+#[test]
+fn tokens_are_random_and_digested() {
+    let token = gen_token(32);
+    assert_eq!(token.len(), 43);
+    assert_ne!(token, gen_token(32));
+    assert_eq!(get_token_digest(&token), get_token_digest(&token));
+    assert_ne!(get_token_digest(&token), get_token_digest(&gen_token(32)));
+    assert_eq!(
+        get_token_digest(""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn unsupported_algorithm_is_an_error() {
+    let mut settings = Hashing {
+        algorithm: "Bcrypt".to_string(),
+        argon2_memory_kib: 1024,
+        argon2_iterations: 1,
+        argon2_parallelism: 1,
+        bcrypt_cost: 4,
+    };
+    assert_eq!(Algorithm::from_settings(&settings), Ok(Algorithm::Bcrypt));
+    settings.algorithm = "md5".to_string();
+    assert_eq!(
+        Algorithm::from_settings(&settings),
+        Err(ErrorAlgorithm::UnsupportedAlgorithm("md5".to_string()))
+    );
+}
@@ -0,0 +1,29 @@
+use serde_json::json;
+use util_lib::auth::jwt::{IntrospectResult, Oauth2TokenClaims};
+
+#[test]
+fn inactive_result_only_has_active() {
+    assert_eq!(
+        serde_json::to_value(IntrospectResult::inactive()).unwrap(),
+        json!({"active": false})
+    );
+}
+
+#[test]
+fn active_result_has_claims() {
+    let (access, _) = Oauth2TokenClaims::new_claims();
+    let mut result = IntrospectResult::from_claims(&access, "user".to_string());
+    result.scope = Some("openid email".to_string());
+    let value = serde_json::to_value(&result).unwrap();
+    assert_eq!(value["active"], true);
+    assert_eq!(value["token_type"], "bearer");
+    assert_eq!(value["sub"], "user");
+    assert_eq!(value["scope"], "openid email");
+    assert_eq!(value["exp"], access.exp);
+    assert_eq!(value["iat"], access.iat);
+    assert_eq!(value["nbf"], access.nbf);
+    assert_eq!(value["iss"], access.iss);
+    assert_eq!(value["jti"], access.jti.to_string());
+    assert!(value.get("client_id").is_none());
+    assert!(value.get("aud").is_none());
+}
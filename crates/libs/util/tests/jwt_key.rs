@@ -0,0 +1,110 @@
+use jsonwebtoken::{decode, encode, Header, Validation};
+use serde::{Deserialize, Serialize};
+use util_lib::{jwt::key::KeyStore, settings::JWT};
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    exp: u64,
+}
+
+fn get_key_path(name: &str) -> String {
+    format!("{}/tests/key/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn get_settings(algorithm: &str, kid: &str, private_key: &str, public_keys: &str) -> JWT {
+    JWT {
+        secret: "secret".to_string(),
+        issuer: "rbca".to_string(),
+        algorithm: algorithm.to_string(),
+        kid: kid.to_string(),
+        private_key: match private_key.is_empty() {
+            true => String::new(),
+            false => get_key_path(private_key),
+        },
+        public_keys: public_keys
+            .split(',')
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                let (kid, name) = v.split_once('=').unwrap();
+                format!("{}={}", kid, get_key_path(name))
+            })
+            .collect::<Vec<String>>()
+            .join(","),
+    }
+}
+
+fn assert_sign_and_verify(keys: &KeyStore, kid: &str) {
+    let mut header = Header::new(keys.get_algorithm());
+    header.kid = keys.get_kid().map(|v| v.to_string());
+    let claims = Claims {
+        sub: "user".to_string(),
+        exp: u64::MAX / 2,
+    };
+    let token = encode(&header, &claims, keys.get_signing_key()).unwrap();
+    let key = keys.get_verifying_key(Some(kid)).unwrap();
+    let decoded = decode::<Claims>(&token, key, &Validation::new(keys.get_algorithm())).unwrap();
+    assert_eq!(decoded.claims.sub, "user");
+}
+
+#[test]
+fn hs256_keys_are_not_published() {
+    let keys = KeyStore::from_settings(&get_settings("HS256", "", "", "")).unwrap();
+    assert!(keys.get_jwk_set().keys.is_empty());
+    assert!(keys.get_verifying_key(None).is_some());
+    assert!(keys.is_symmetric());
+}
+
+#[test]
+fn rs256_signs_with_kid_and_publishes_public_keys() {
+    let keys = KeyStore::from_settings(&get_settings(
+        "RS256",
+        "new",
+        "rsa.pem",
+        "new=rsa.pub.pem,old=rsa.pkcs1.pub.pem",
+    ))
+    .unwrap();
+    assert_eq!(keys.get_kid(), Some("new"));
+    assert!(!keys.is_symmetric());
+    assert_eq!(keys.get_jwk_set().keys.len(), 2);
+    assert!(keys.get_jwk_set().find("old").is_some());
+    assert_sign_and_verify(&keys, "new");
+    // Same key published under another kid during rotation
+    assert_sign_and_verify(&keys, "old");
+    assert!(keys.get_verifying_key(Some("unknown")).is_none());
+    assert!(keys.get_verifying_key(None).is_none());
+}
+
+#[test]
+fn eddsa_signs_and_verifies() {
+    let keys = KeyStore::from_settings(&get_settings(
+        "EdDSA",
+        "ed",
+        "ed25519.pem",
+        "ed=ed25519.pub.pem",
+    ))
+    .unwrap();
+    assert_sign_and_verify(&keys, "ed");
+}
+
+#[test]
+fn signing_key_must_be_published() {
+    let keys = KeyStore::from_settings(&get_settings(
+        "EdDSA",
+        "missing",
+        "ed25519.pem",
+        "ed=ed25519.pub.pem",
+    ));
+    assert!(keys.is_err());
+}
+
+#[test]
+fn public_key_must_match_algorithm() {
+    let keys = KeyStore::from_settings(&get_settings(
+        "RS256",
+        "ed",
+        "rsa.pem",
+        "ed=ed25519.pub.pem",
+    ));
+    assert!(keys.is_err());
+}
@@ -0,0 +1,48 @@
+use util_lib::auth::jwt::{
+    Oauth2TokenClaims, Oauth2TokenType, OAUTH2_ACCEESS_LIFE_SEC, OAUTH2_REFRESH_LIFE_SEC,
+};
+
+#[test]
+fn access_and_refresh_claims_point_to_each_other() {
+    let (access, refresh) = Oauth2TokenClaims::new_claims();
+    assert!(matches!(
+        access.oauth_token_type,
+        Oauth2TokenType::AccessToken
+    ));
+    assert!(matches!(
+        refresh.oauth_token_type,
+        Oauth2TokenType::RefreshToken
+    ));
+    assert_eq!(access.sub_jti, refresh.jti);
+    assert_eq!(refresh.sub_jti, access.jti);
+    assert_ne!(access.jti, refresh.jti);
+    assert_eq!(access.sid, refresh.sid);
+    assert_eq!(access.get_life_sec(), OAUTH2_ACCEESS_LIFE_SEC as u64);
+    assert_eq!(refresh.get_life_sec(), OAUTH2_REFRESH_LIFE_SEC as u64);
+    assert!(access.validate_date_range().is_ok());
+    assert!(refresh.validate_date_range().is_ok());
+}
+
+#[test]
+fn rotated_claims_keep_session() {
+    let (access, refresh) = Oauth2TokenClaims::new_claims();
+    let (new_access, new_refresh) = Oauth2TokenClaims::new_session_claims(refresh.sid);
+    assert_eq!(new_access.sid, access.sid);
+    assert_eq!(new_refresh.sid, access.sid);
+    assert_ne!(new_access.jti, access.jti);
+    assert_ne!(new_refresh.jti, refresh.jti);
+    assert_eq!(new_refresh.sub_jti, new_access.jti);
+
+    // A new login starts a new session
+    let (other_access, _) = Oauth2TokenClaims::new_claims();
+    assert_ne!(other_access.sid, access.sid);
+}
+
+#[test]
+fn expired_claims_are_refused() {
+    let (mut access, _) = Oauth2TokenClaims::new_claims();
+    access.exp = access.nbf - 1;
+    access.nbf -= 10;
+    assert!(access.validate_date_range().is_err());
+    assert_eq!(access.get_remaining_life_sec(), 0);
+}
@@ -0,0 +1,36 @@
+use serde_json::{json, Value};
+use util_lib::auth::oidc::{IdTokenClaims, UserInfo};
+
+#[test]
+fn user_info_skips_missing_claims() {
+    let user_info = UserInfo {
+        sub: "user".to_string(),
+        name: None,
+        email: Some("user@example.com".to_string()),
+    };
+    assert_eq!(
+        serde_json::to_value(&user_info).unwrap(),
+        json!({"sub": "user", "email": "user@example.com"})
+    );
+}
+
+#[test]
+fn id_token_claims_are_flat() {
+    let user_info = UserInfo {
+        sub: "user".to_string(),
+        name: Some("User".to_string()),
+        email: None,
+    };
+    let claims = IdTokenClaims::new(user_info, "app".to_string(), 100, None);
+    let value = serde_json::to_value(&claims).unwrap();
+    assert_eq!(value["sub"], "user");
+    assert_eq!(value["name"], "User");
+    assert_eq!(value["aud"], "app");
+    assert_eq!(value["auth_time"], 100);
+    assert_eq!(
+        value["exp"].as_u64().unwrap() - value["iat"].as_u64().unwrap(),
+        900
+    );
+    assert_eq!(value.get("nonce"), None::<&Value>);
+    assert_eq!(value.get("email"), None::<&Value>);
+}
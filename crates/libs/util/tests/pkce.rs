@@ -0,0 +1,39 @@
+use util_lib::auth::pkce::{
+    get_code_challenge, is_valid_code_verifier, verify_code_challenge, CodeChallengeMethod,
+};
+
+// Example of RFC 7636 appendix B
+const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+#[test]
+fn s256_challenge_matches_rfc_example() {
+    assert_eq!(
+        get_code_challenge(VERIFIER, CodeChallengeMethod::S256),
+        CHALLENGE
+    );
+    assert!(verify_code_challenge(
+        VERIFIER,
+        CHALLENGE,
+        CodeChallengeMethod::S256
+    ));
+}
+
+#[test]
+fn wrong_verifier_is_rejected() {
+    let other = "eBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    assert!(!verify_code_challenge(
+        other,
+        CHALLENGE,
+        CodeChallengeMethod::S256
+    ));
+}
+
+#[test]
+fn verifier_length_and_charset_are_checked() {
+    assert!(is_valid_code_verifier(VERIFIER));
+    assert!(!is_valid_code_verifier(&"a".repeat(42)));
+    assert!(is_valid_code_verifier(&"a".repeat(128)));
+    assert!(!is_valid_code_verifier(&"a".repeat(129)));
+    assert!(!is_valid_code_verifier(&format!("{}+", "a".repeat(42))));
+}
@@ -0,0 +1,29 @@
+use time::{Duration, OffsetDateTime};
+use util_lib::retry::RetryPolicy;
+
+const POLICY: RetryPolicy = RetryPolicy {
+    max_attempts: 3,
+    backoff_base_sec: 30,
+    backoff_max_sec: 3600,
+};
+
+#[test]
+fn backoff_doubles_up_to_max() {
+    assert_eq!(POLICY.get_backoff_sec(0), 30);
+    assert_eq!(POLICY.get_backoff_sec(1), 30);
+    assert_eq!(POLICY.get_backoff_sec(2), 60);
+    assert_eq!(POLICY.get_backoff_sec(4), 240);
+    assert_eq!(POLICY.get_backoff_sec(8), 3600);
+    assert_eq!(POLICY.get_backoff_sec(u32::MAX), 3600);
+}
+
+#[test]
+fn message_is_retried_until_max_attempts() {
+    let before = OffsetDateTime::now_utc();
+    let retry_at = POLICY.get_retry_at(2, true).unwrap();
+    assert!(retry_at >= before + Duration::seconds(60));
+    assert!(retry_at <= OffsetDateTime::now_utc() + Duration::seconds(60));
+
+    assert_eq!(POLICY.get_retry_at(3, true), None);
+    assert_eq!(POLICY.get_retry_at(1, false), None);
+}
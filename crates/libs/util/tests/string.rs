@@ -0,0 +1,18 @@
+use util_lib::string::validate::is_email;
+
+#[test]
+fn email_shape_is_checked() {
+    assert!(is_email("user@example.com"));
+    assert!(is_email("first.last+tag@mail.example.org"));
+
+    assert!(!is_email(""));
+    assert!(!is_email("user"));
+    assert!(!is_email("@example.com"));
+    assert!(!is_email("user@localhost"));
+    assert!(!is_email("user@example..com"));
+    assert!(!is_email("user@example.com."));
+    assert!(!is_email("user@@example.com"));
+    assert!(!is_email("us er@example.com"));
+    assert!(!is_email(&format!("{}@example.com", "a".repeat(65))));
+    assert!(!is_email(&format!("user@{}.com", "a".repeat(250))));
+}
@@ -0,0 +1,57 @@
+use util_lib::auth::totp::{
+    decode_secret, encode_secret, gen_secret, get_code, get_provisioning_uri, get_step, verify,
+    SECRET_LEN,
+};
+
+// Secret of RFC 6238 appendix B for SHA-1
+const SECRET: &[u8] = b"12345678901234567890";
+
+#[test]
+fn codes_match_rfc_vectors() {
+    // Last 6 digits of the 8 digits codes of the RFC
+    let vectors = [
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+        (20000000000, "353130"),
+    ];
+    for (unix_time, code) in vectors {
+        assert_eq!(get_code(SECRET, get_step(unix_time)), code);
+    }
+}
+
+#[test]
+fn verify_accepts_skew_and_returns_step() {
+    let unix_time = 1111111109;
+    let step = get_step(unix_time);
+    let previous = get_code(SECRET, step - 1);
+    assert_eq!(verify(SECRET, &previous, unix_time, 1), Some(step - 1));
+    assert_eq!(verify(SECRET, &previous, unix_time, 0), None);
+    assert_eq!(verify(SECRET, "000000", unix_time, 1), None);
+    assert_eq!(verify(SECRET, "12345", unix_time, 1), None);
+}
+
+#[test]
+fn secret_roundtrips_through_base32() {
+    let secret = gen_secret();
+    assert_eq!(secret.len(), SECRET_LEN);
+    let encoded = encode_secret(&secret);
+    assert_eq!(decode_secret(&encoded), Some(secret.to_owned()));
+    assert_eq!(
+        decode_secret(&encoded.to_lowercase()),
+        Some(secret.to_owned())
+    );
+    assert_eq!(decode_secret("not base32!"), None);
+}
+
+#[test]
+fn provisioning_uri_contains_label_and_secret() {
+    let uri = get_provisioning_uri(SECRET, "My App", "user@example.com");
+    assert_eq!(
+        uri,
+        "otpauth://totp/My%20App:user@example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+         &issuer=My%20App&algorithm=SHA1&digits=6&period=30"
+    );
+}
@@ -1,3 +1,7 @@
+pub mod app_client;
+pub mod application;
+pub mod client;
+pub mod client_auth;
 pub mod staff;
 pub mod user;
 
@@ -8,4 +12,7 @@ pub enum GuardError {
     MissingToken,
     MissingUser,
     MissingPermission,
+    MissingMfa,
+    MissingClientCredentials,
+    WrongClientCredentials,
 }
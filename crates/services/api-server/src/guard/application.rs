@@ -0,0 +1,106 @@
+use rocket::{
+    outcome::Outcome,
+    request::{self, FromRequest, Request},
+};
+use rocket_okapi::{
+    gen::OpenApiGenerator,
+    okapi::openapi3::{Object, SecurityRequirement, SecurityScheme, SecuritySchemeData},
+    request::{OpenApiFromRequest, RequestHeaderInput},
+};
+use rocket_util_lib::PermissionResolver;
+use uuid::Uuid;
+
+use super::{
+    app_client::AppClient,
+    staff::{application::ApplicationPermission, user::UserStaff},
+    GuardError,
+};
+use crate::{
+    schema::application::ApplicationPermissions, usecase::application as application_usecase,
+};
+use repository_db_lib::app_staff::app_staff_entity::AppStaffPermissions;
+
+/// Actor of an application: a staff user or a client of the application.
+#[derive(Debug)]
+pub enum ApplicationActor {
+    User(UserStaff),
+    Client(AppClient),
+}
+
+impl ApplicationActor {
+    /// Returns the user acting, the creator of the client for a client.
+    pub fn get_user_id(&self) -> Uuid {
+        match self {
+            Self::User(v) => v.user.claims.id,
+            Self::Client(v) => v.claims.created_by_user_id,
+        }
+    }
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for ApplicationActor {
+    type Error = GuardError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        if let Outcome::Success(client) = request.guard::<AppClient>().await {
+            return Outcome::Success(Self::Client(client));
+        }
+        match request.guard::<UserStaff>().await {
+            Outcome::Success(v) => Outcome::Success(Self::User(v)),
+            Outcome::Error(e) => Outcome::Error(e),
+            Outcome::Forward(f) => Outcome::Forward(f),
+        }
+    }
+}
+
+#[async_trait]
+impl PermissionResolver<ApplicationActor, Uuid> for ApplicationPermission {
+    type Permission = AppStaffPermissions;
+
+    async fn resolve(
+        guard: &ApplicationActor,
+        application_id: &Uuid,
+    ) -> Option<Vec<Self::Permission>> {
+        match guard {
+            ApplicationActor::User(v) => {
+                <Self as PermissionResolver<UserStaff, Uuid>>::resolve(v, application_id).await
+            }
+            ApplicationActor::Client(v) => {
+                // A client only acts on its own application
+                if v.claims.application_id != *application_id {
+                    return Some(Vec::new());
+                }
+                application_usecase::get(*application_id).await.ok()?;
+                Some(ApplicationPermissions::to_models(&v.claims.scope))
+            }
+        }
+    }
+}
+
+// Implementing OpenApiFromRequest to document the API security requirements in OpenAPI format
+impl<'a> OpenApiFromRequest<'a> for ApplicationActor {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        let security_scheme = SecurityScheme {
+            description: Some(
+                "Requires an Bearer token of a staff user or an application client to access"
+                    .to_owned(),
+            ),
+            data: SecuritySchemeData::Http {
+                scheme: "bearer".to_owned(),
+                bearer_format: Some("bearer".to_owned()),
+            },
+            extensions: Object::default(),
+        };
+        let mut security_req = SecurityRequirement::new();
+        security_req.insert("Authorization".to_owned(), Vec::new());
+        Ok(RequestHeaderInput::Security(
+            "Authorization".to_owned(),
+            security_scheme,
+            security_req,
+        ))
+    }
+}
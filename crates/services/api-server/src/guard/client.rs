@@ -0,0 +1,68 @@
+use std::convert::Infallible;
+
+use rocket::{
+    outcome::Outcome,
+    request::{self, FromRequest, Request},
+};
+use rocket_okapi::{
+    gen::OpenApiGenerator,
+    request::{OpenApiFromRequest, RequestHeaderInput},
+};
+use util_lib::auth::basic::extract_basic_credentials;
+
+/// Information about the client sending the request, always available.
+#[derive(Debug, Clone)]
+pub struct Client {
+    /// Remote address of the connection, or the value of the `ip_header` of the Rocket config
+    /// (disabled by default, only trusted behind a reverse proxy overwriting it).
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Credentials (`client_id`, `client_secret`) of the HTTP Basic authorization header.
+    pub credentials: Option<(String, String)>,
+    /// Preferred locale, the first language of the `Accept-Language` header.
+    pub locale: Option<String>,
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for Client {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(Self {
+            ip: request.client_ip().map(|v| v.to_string()),
+            user_agent: request
+                .headers()
+                .get_one("user-agent")
+                .map(|v| v.to_string()),
+            credentials: request
+                .headers()
+                .get_one("authorization")
+                .and_then(|v| extract_basic_credentials(v).ok()),
+            locale: request
+                .headers()
+                .get_one("accept-language")
+                .and_then(get_locale),
+        })
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for Client {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
+
+/// Returns the first language of an `Accept-Language` header, e.g. `en-US` for
+/// `en-US,en;q=0.9`.
+fn get_locale(accept_language: &str) -> Option<String> {
+    accept_language
+        .split(',')
+        .filter_map(|v| v.split(';').next())
+        .map(|v| v.trim())
+        .find(|v| !v.is_empty() && *v != "*")
+        .map(|v| v.to_string())
+}
@@ -0,0 +1,63 @@
+use rocket::{
+    http::Status,
+    outcome::Outcome,
+    request::{self, FromRequest, Request},
+};
+use rocket_okapi::{
+    gen::OpenApiGenerator,
+    okapi::openapi3::{Object, SecurityRequirement, SecurityScheme, SecuritySchemeData},
+    request::{OpenApiFromRequest, RequestHeaderInput},
+};
+use util_lib::auth::basic::extract_basic_credentials;
+
+use crate::usecase::auth as auth_usecase;
+
+use super::GuardError;
+
+/// Client authenticated with its credentials (HTTP Basic `client_id:client_secret`).
+#[derive(Debug)]
+pub struct ClientAuth {
+    pub client_id: String,
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for ClientAuth {
+    type Error = GuardError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        if let Some(header_value) = request.headers().get_one("authorization") {
+            if let Ok((client_id, client_secret)) = extract_basic_credentials(header_value) {
+                if auth_usecase::authenticate_client(&client_id, &client_secret).await {
+                    return Outcome::Success(Self { client_id });
+                }
+            }
+            return Outcome::Error((Status::Unauthorized, GuardError::WrongClientCredentials));
+        }
+        Outcome::Error((Status::Unauthorized, GuardError::MissingClientCredentials))
+    }
+}
+
+// Implementing OpenApiFromRequest to document the API security requirements in OpenAPI format
+impl<'a> OpenApiFromRequest<'a> for ClientAuth {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        let security_scheme = SecurityScheme {
+            description: Some("Requires the client credentials to access".to_owned()),
+            data: SecuritySchemeData::Http {
+                scheme: "basic".to_owned(),
+                bearer_format: None,
+            },
+            extensions: Object::default(),
+        };
+        let mut security_req = SecurityRequirement::new();
+        security_req.insert("ClientCredentials".to_owned(), Vec::new());
+        Ok(RequestHeaderInput::Security(
+            "ClientCredentials".to_owned(),
+            security_scheme,
+            security_req,
+        ))
+    }
+}
@@ -0,0 +1,19 @@
+use rocket_util_lib::PermissionResolver;
+use uuid::Uuid;
+
+use super::user::UserStaff;
+use crate::usecase::application as application_usecase;
+use repository_db_lib::app_staff::app_staff_entity::AppStaffPermissions;
+
+/// Resolves the permissions a staff user holds on the application from the route.
+#[derive(Debug)]
+pub struct ApplicationPermission;
+
+#[async_trait]
+impl PermissionResolver<UserStaff, Uuid> for ApplicationPermission {
+    type Permission = AppStaffPermissions;
+
+    async fn resolve(guard: &UserStaff, application_id: &Uuid) -> Option<Vec<Self::Permission>> {
+        application_usecase::get_permissions(guard.user.claims.id, *application_id).await
+    }
+}
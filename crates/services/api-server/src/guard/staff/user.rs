@@ -32,7 +32,12 @@ impl<'r> FromRequest<'r> for UserStaff {
 
     async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
         let user = try_outcome!(request.guard::<User>().await);
-        if user.claims.is_staff {
+        // A token of an application never has the staff access of the user
+        if user.claims.is_staff && !user.claims.is_application_token() {
+            // MFA enforced by staff is needed for the staff permissions
+            if !user.claims.is_mfa_satisfied() {
+                return Outcome::Error((Status::Forbidden, GuardError::MissingMfa));
+            }
             return Outcome::Success(Self { user });
         }
         Outcome::Error((Status::Forbidden, GuardError::MissingUser))
@@ -1,6 +1,6 @@
 use rocket::{
     http::Status,
-    outcome::Outcome,
+    outcome::{try_outcome, Outcome},
     request::{self, FromRequest, Request},
 };
 use rocket_okapi::{
@@ -14,6 +14,7 @@ use crate::{schema::auth as auth_schema, usecase::auth as auth_usecase};
 
 use super::GuardError;
 
+/// User of a session of the service, the tokens issued to applications are refused.
 #[derive(Debug)]
 pub struct User {
     pub claims: auth_schema::SelfUserTokenClaims,
@@ -24,30 +25,59 @@ impl<'r> FromRequest<'r> for User {
     type Error = GuardError;
 
     async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
-        if let Some(header_value) = request.headers().get_one("authorization") {
-            if let Ok(token_str) = auth_jwt::extract_bearer_token(header_value) {
-                // Parse the token into a SelfUserTokenClaims object
-                if let Ok(user_claims) = auth_schema::SelfUserTokenClaims::from_jwt(&token_str) {
-                    // Ensure the OAuth2 claims are valid (access type)
-                    if user_claims.oauth2_claims.is_access() {
-                        if let Ok(_) = user_claims.oauth2_claims.validate_date_range() {
-                            if auth_usecase::token_is_exist(&user_claims).await {
-                                return Outcome::Success(Self {
-                                    claims: user_claims,
-                                });
+        let claims = try_outcome!(get_claims(request).await);
+        // A token of an application only gives access to the claims of the user
+        if claims.is_application_token() {
+            return Outcome::Error((Status::Forbidden, GuardError::WrongToken));
+        }
+        Outcome::Success(Self { claims })
+    }
+}
+
+/// User of a session of the service or of a token issued to an application (e.g. for the
+/// OpenID Connect userinfo).
+#[derive(Debug)]
+pub struct UserOrApplication {
+    pub claims: auth_schema::SelfUserTokenClaims,
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for UserOrApplication {
+    type Error = GuardError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let claims = try_outcome!(get_claims(request).await);
+        Outcome::Success(Self { claims })
+    }
+}
+
+/// Returns the claims of the bearer access token if it is not revoked.
+async fn get_claims(
+    request: &Request<'_>,
+) -> request::Outcome<auth_schema::SelfUserTokenClaims, GuardError> {
+    if let Some(header_value) = request.headers().get_one("authorization") {
+        if let Ok(token_str) = auth_jwt::extract_bearer_token(header_value) {
+            // Parse the token into a SelfUserTokenClaims object
+            if let Ok(user_claims) = auth_schema::SelfUserTokenClaims::from_jwt(&token_str) {
+                // Ensure the OAuth2 claims are valid (access type)
+                if user_claims.oauth2_claims.is_access() {
+                    if let Ok(_) = user_claims.oauth2_claims.validate_date_range() {
+                        if let Some(cached_token) =
+                            auth_usecase::get_cached_token(&user_claims).await
+                        {
+                            if auth_usecase::token_version_is_actual(&user_claims).await {
+                                auth_usecase::touch_session(&user_claims, cached_token).await;
+                                return Outcome::Success(user_claims);
                             }
-                            return Outcome::Error((
-                                Status::Unauthorized,
-                                GuardError::MissingToken,
-                            ));
                         }
+                        return Outcome::Error((Status::Unauthorized, GuardError::MissingToken));
                     }
                 }
             }
-            return Outcome::Error((Status::Unauthorized, GuardError::WrongToken));
         }
-        Outcome::Error((Status::Unauthorized, GuardError::MissingAuthToken))
+        return Outcome::Error((Status::Unauthorized, GuardError::WrongToken));
     }
+    Outcome::Error((Status::Unauthorized, GuardError::MissingAuthToken))
 }
 
 // Implementing OpenApiFromRequest to document the API security requirements in OpenAPI format
@@ -74,3 +104,27 @@ impl<'a> OpenApiFromRequest<'a> for User {
         ))
     }
 }
+
+impl<'a> OpenApiFromRequest<'a> for UserOrApplication {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        let security_scheme = SecurityScheme {
+            description: Some("Requires an Bearer token to access".to_owned()),
+            data: SecuritySchemeData::Http {
+                scheme: "bearer".to_owned(),
+                bearer_format: Some("bearer".to_owned()),
+            },
+            extensions: Object::default(),
+        };
+        let mut security_req = SecurityRequirement::new();
+        security_req.insert("Authorization".to_owned(), Vec::new());
+        Ok(RequestHeaderInput::Security(
+            "Authorization".to_owned(),
+            security_scheme,
+            security_req,
+        ))
+    }
+}
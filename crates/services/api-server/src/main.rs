@@ -1,41 +1,34 @@
-mod guard;
-mod query;
-mod route;
-mod schema;
-mod usecase;
-
+use api_server::{route, usecase::oauth as oauth_usecase};
 use migration::init as init_migration;
-
-#[macro_use]
-extern crate rocket;
-
-/// Macro to merge Rocket routes with OpenAPI and/or without.
-///
-/// - With only OpenAPI routes:
-///   ```rust
-///   merdge_mulit_routes!(settings, [route_with_openapi1, route_with_openapi2]);
-///   ```
-///
-/// - With both OpenAPI routes and additional standard Rocket routes:
-///   ```rust
-///   merdge_mulit_routes!(settings, [route_with_openapi1, route_with_openapi2], [regular_route1, regular_route2]);
-///   ```
-#[macro_export]
-macro_rules! merdge_mulit_routes {
-    ($settings:ident,[ $( $route_with_openapi:expr ),* ] ) => {{
-        rocket_okapi::openapi_get_routes_spec![$settings: $($route_with_openapi),*]
-    }};
-    ($settings:ident, [ $( $route_with_openapi:expr ),* ], [ $( $route:expr ),* ] ) => {{
-        let default_routes = rocket::routes![$($route),*];
-        let (openapi_routes, openapi_struct) = rocket_okapi::openapi_get_routes_spec![$settings: $($route_with_openapi),*];
-        ([default_routes, openapi_routes].concat(), openapi_struct)
-    }};
-}
+use notification_lib::{transport::get_default_transport, Dispatcher};
+use repository_amqp_lib::outbox::Relay as OutboxRelay;
+use repository_db_lib::{
+    notification::Notification as NotificationRep, outbox::Outbox as OutboxRep, Repository,
+};
+use util_lib::{crypto::Algorithm, settings::SETTINGS as UTIL_SETTINGS};
 
 #[rocket::main]
 pub async fn main() -> Result<(), rocket::Error> {
+    // Refuse to advertise an OpenID provider whose tokens can't be verified
+    if let Err(e) = oauth_usecase::check_openid_configuration() {
+        panic!("OpenID Connect: {}", e);
+    }
+    // An unsupported algorithm of the passwords would fail the first login or sign up
+    if let Err(e) = Algorithm::from_settings(&UTIL_SETTINGS.hashing) {
+        panic!("Hashing: {}", e);
+    }
+    // A missing or unknown transport of the notifications stops the service
+    let transport = match get_default_transport() {
+        Ok(v) => v,
+        Err(e) => panic!("Notification: {}", e),
+    };
     init_migration().await;
 
+    // Publish the events written into the outbox
+    tokio::spawn(OutboxRelay::new(OutboxRep::new().await).run());
+    // Send the notifications and retry the failed ones
+    tokio::spawn(Dispatcher::new(NotificationRep::new().await, transport).run());
+
     let mut _rocket = rocket::build();
 
     _rocket = route::init_routes(_rocket);
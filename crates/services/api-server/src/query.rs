@@ -1,3 +1,6 @@
 pub mod application;
+pub mod key;
+pub mod login_lockout;
+pub mod oauth;
 pub mod user;
 
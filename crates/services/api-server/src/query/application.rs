@@ -24,3 +24,36 @@ pub struct Application {
     #[field(default = Some(LIMIT_DEFAULT))]
     pub limit: Option<i64>,
 }
+
+#[derive(JsonSchema, FromForm, EntityFilterable)]
+pub struct ApplicationStaff {
+    pub user_id: Option<Uuid>,
+    #[filter(rule = "gte", value_prepare = "v.to_time()", column = "created_at")]
+    pub created_start: Option<OffsetDateTimeForm>,
+    #[filter(rule = "lt", value_prepare = "v.to_time()", column = "created_at")]
+    pub created_end: Option<OffsetDateTimeForm>,
+    #[filter(ignore)]
+    #[field(default = Some(OFFSET_DEFAULT))]
+    pub offset: Option<u64>,
+    #[filter(ignore)]
+    #[field(default = Some(LIMIT_DEFAULT))]
+    pub limit: Option<i64>,
+}
+
+#[derive(JsonSchema, FromForm, EntityFilterable)]
+pub struct ApplicationClient {
+    pub id: Option<Uuid>,
+    #[filter(rule = "like")]
+    pub name: Option<String>,
+    pub created_by_user_id: Option<Uuid>,
+    #[filter(rule = "gte", value_prepare = "v.to_time()", column = "created_at")]
+    pub created_start: Option<OffsetDateTimeForm>,
+    #[filter(rule = "lt", value_prepare = "v.to_time()", column = "created_at")]
+    pub created_end: Option<OffsetDateTimeForm>,
+    #[filter(ignore)]
+    #[field(default = Some(OFFSET_DEFAULT))]
+    pub offset: Option<u64>,
+    #[filter(ignore)]
+    #[field(default = Some(LIMIT_DEFAULT))]
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,11 @@
+use rocket::form::FromForm;
+use schemars::JsonSchema;
+
+use crate::schema::login_lockout::LockoutKind;
+
+#[derive(JsonSchema, FromForm)]
+pub struct LoginLockout {
+    pub kind: LockoutKind,
+    /// Email or IP address of the client.
+    pub subject: String,
+}
@@ -0,0 +1,25 @@
+use rocket::form::FromForm;
+use schemars::JsonSchema;
+use util_lib::auth::pkce::CodeChallengeMethod;
+use uuid::Uuid;
+
+use crate::schema::oauth::ResponseType;
+
+/// Parameters of the OAuth2 authorization request (RFC 6749 4.1.1, RFC 7636 4.3).
+#[derive(JsonSchema, FromForm)]
+pub struct Authorize {
+    /// Only `code` is accepted, checked on parsing.
+    #[allow(dead_code)]
+    pub response_type: ResponseType,
+    /// Identifier of the application.
+    pub client_id: Uuid,
+    pub redirect_uri: String,
+    /// Space separated scopes.
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    /// OpenID Connect value echoed in the ID token.
+    pub nonce: Option<String>,
+    pub code_challenge: String,
+    #[field(default = CodeChallengeMethod::S256)]
+    pub code_challenge_method: CodeChallengeMethod,
+}
@@ -8,11 +8,11 @@ use util_lib::date::OffsetDateTimeForm;
 
 #[derive(JsonSchema, FromForm, EntityFilterable)]
 pub struct User {
-    id: Option<Uuid>,
+    pub id: Option<Uuid>,
     #[filter(rule = "like")]
-    name: Option<String>,
+    pub name: Option<String>,
     #[filter(rule = "like")]
-    email: Option<String>,
+    pub email: Option<String>,
     #[filter(rule = "gte", value_prepare = "v.to_time()", column = "created_at")]
     pub created_start: Option<OffsetDateTimeForm>,
     #[filter(rule = "lt", value_prepare = "v.to_time()", column = "created_at")]
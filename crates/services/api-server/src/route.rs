@@ -1,5 +1,6 @@
 mod api;
 mod core;
+mod oauth;
 
 use rocket::{Build, Rocket};
 
@@ -13,6 +14,7 @@ use rocket_okapi::{
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
     get_nested_endpoints_and_docs! {
         "/api" => api::get_routes_and_docs(settings),
+        "/oauth" => oauth::get_routes_and_docs(settings),
         "/" => core::get_routes_and_docs(settings),
     }
 }
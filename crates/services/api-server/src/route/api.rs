@@ -1,5 +1,7 @@
 mod application;
-mod auth;
+pub(super) mod auth;
+mod key;
+mod login_lockout;
 mod self_user;
 mod user;
 mod user_staff;
@@ -14,6 +16,8 @@ pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, O
         "/self-user" => self_user::get_routes_and_docs(settings),
         "/auth" => auth::get_routes_and_docs(settings),
         "/user-staff" => user_staff::get_routes_and_docs(settings),
-        "/application" => application::get_routes_and_docs(settings)
+        "/application" => application::get_routes_and_docs(settings),
+        "/key" => key::get_routes_and_docs(settings),
+        "/login-lockout" => login_lockout::get_routes_and_docs(settings)
     }
 }
@@ -1,15 +1,24 @@
+mod client;
+mod key;
+mod staff;
+
 use crate::{
-    guard::{staff::user::UserStaff as GuardUserStaff, GuardError},
+    guard::{
+        staff::{application::ApplicationPermission, user::UserStaff as GuardUserStaff},
+        GuardError,
+    },
     merdge_mulit_routes,
     query::application as application_query,
     schema::{self, application as application_schema, user as user_schema},
     usecase::application as application_usecase,
 };
+use repository_db_lib::app_staff::app_staff_entity::AppStaffPermissions;
 use rocket::{http::Status, serde::json::Json};
 use rocket_okapi::{
     get_nested_endpoints_and_docs, okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings,
 };
 use rocket_util_lib::guard_permission;
+use uuid::Uuid;
 
 #[openapi(tag = "Application")]
 #[guard_permission(error_ty = GuardError, perm_error = MissingPermission, all_perms = [user_schema::StaffPermission::CreateApplication])]
@@ -32,6 +41,15 @@ pub async fn create(
                     err_detail: None,
                 })),
             ),
+            application_usecase::ErrorCreate::InvalidRedirectUri => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "redirect uri must be an absolute http(s) uri without fragment"
+                        .to_string(),
+                    err_detail: None,
+                })),
+            ),
             application_usecase::ErrorCreate::AddCreatorIntoNewApplication => (
                 Status::InternalServerError,
                 Err(Json(schema::ErrorResult {
@@ -56,8 +74,126 @@ pub async fn get_multiple(
     )
 }
 
+#[openapi(tag = "Application")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::ReadApplication])]
+#[get("/<application_id>")]
+pub async fn get(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+) -> (
+    Status,
+    Result<Json<application_schema::Application>, Json<schema::ErrorResult>>,
+) {
+    match application_usecase::get(application_id).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            application_usecase::ErrorGet::ApplicationNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "application doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::UpdateApplication])]
+#[put("/<application_id>", data = "<application>")]
+pub async fn update(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    application: Json<application_schema::UpdateApplication>,
+) -> (
+    Status,
+    Result<Json<application_schema::Application>, Json<schema::ErrorResult>>,
+) {
+    match application_usecase::update(application_id, &application.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            application_usecase::ErrorUpdate::ApplicationNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "application doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            application_usecase::ErrorUpdate::ApplicationNameAllreadyExist => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "application name allready exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            application_usecase::ErrorUpdate::InvalidRedirectUri => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "redirect uri must be an absolute http(s) uri without fragment"
+                        .to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::DeleteApplication])]
+#[delete("/<application_id>")]
+pub async fn delete(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match application_usecase::delete(application_id).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            application_usecase::ErrorDelete::ApplicationNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "application doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, all_perms = [user_schema::StaffPermission::RestoreApplication])]
+#[post("/<application_id>/restore")]
+pub async fn restore(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+) -> (
+    Status,
+    Result<Json<application_schema::Application>, Json<schema::ErrorResult>>,
+) {
+    match application_usecase::restore(application_id).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            application_usecase::ErrorRestore::ApplicationNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "deleted application doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
     get_nested_endpoints_and_docs! {
-        "/" => merdge_mulit_routes![settings, [create, get_multiple]],
+        "/" => merdge_mulit_routes![settings, [create, get_multiple, get, update, delete, restore]],
+        "/" => client::get_routes_and_docs(settings),
+        "/" => key::get_routes_and_docs(settings),
+        "/" => staff::get_routes_and_docs(settings),
     }
 }
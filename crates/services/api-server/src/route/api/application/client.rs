@@ -0,0 +1,191 @@
+use crate::{
+    guard::{
+        staff::{application::ApplicationPermission, user::UserStaff as GuardUserStaff},
+        GuardError,
+    },
+    merdge_mulit_routes,
+    query::application as application_query,
+    schema::{self, app_client as app_client_schema},
+    usecase::app_client as app_client_usecase,
+};
+use repository_db_lib::app_staff::app_staff_entity::AppStaffPermissions;
+use rocket::{http::Status, serde::json::Json};
+use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
+use rocket_util_lib::guard_permission;
+use uuid::Uuid;
+
+#[openapi(tag = "Application Client")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::CreateClient])]
+#[post("/<application_id>/client", data = "<new_client>")]
+pub async fn create(
+    guard: GuardUserStaff,
+    application_id: Uuid,
+    new_client: Json<app_client_schema::CreateApplicationClient>,
+) -> (
+    Status,
+    Result<Json<app_client_schema::ApplicationClientSecret>, Json<schema::ErrorResult>>,
+) {
+    match app_client_usecase::create(guard.user.claims.id, application_id, &new_client.0).await {
+        Ok(v) => (Status::Created, Ok(Json(v))),
+        Err(e) => match e {
+            app_client_usecase::ErrorCreate::InvalidPermissions => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "client can only hold key permissions".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            app_client_usecase::ErrorCreate::MissingPermissions => (
+                Status::Forbidden,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Forbidden,
+                    err_msg: "can't grant permissions you don't hold".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Client")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::ReadClient])]
+#[get("/<application_id>/client?<req_query..>")]
+pub async fn get_multiple(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    req_query: application_query::ApplicationClient,
+) -> (Status, Json<app_client_schema::ApplicationClientList>) {
+    (
+        Status::Ok,
+        Json(app_client_usecase::get_all(application_id, &req_query).await),
+    )
+}
+
+#[openapi(tag = "Application Client")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::ReadClient])]
+#[get("/<application_id>/client/<client_id>")]
+pub async fn get(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    client_id: Uuid,
+) -> (
+    Status,
+    Result<Json<app_client_schema::ApplicationClient>, Json<schema::ErrorResult>>,
+) {
+    match app_client_usecase::get(application_id, client_id).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            app_client_usecase::ErrorGet::ClientNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "client doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Client")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::UpdateClient])]
+#[put("/<application_id>/client/<client_id>", data = "<client>")]
+pub async fn update(
+    guard: GuardUserStaff,
+    application_id: Uuid,
+    client_id: Uuid,
+    client: Json<app_client_schema::UpdateApplicationClient>,
+) -> (
+    Status,
+    Result<Json<app_client_schema::ApplicationClient>, Json<schema::ErrorResult>>,
+) {
+    match app_client_usecase::update(guard.user.claims.id, application_id, client_id, &client.0)
+        .await
+    {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            app_client_usecase::ErrorUpdate::ClientNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "client doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            app_client_usecase::ErrorUpdate::InvalidPermissions => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "client can only hold key permissions".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            app_client_usecase::ErrorUpdate::MissingPermissions => (
+                Status::Forbidden,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Forbidden,
+                    err_msg: "can't grant permissions you don't hold".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+/// Replaces the secret of the client, tokens issued with the old secret are revoked.
+#[openapi(tag = "Application Client")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::UpdateClient])]
+#[post("/<application_id>/client/<client_id>/secret")]
+pub async fn rotate_secret(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    client_id: Uuid,
+) -> (
+    Status,
+    Result<Json<app_client_schema::ApplicationClientSecret>, Json<schema::ErrorResult>>,
+) {
+    match app_client_usecase::rotate_secret(application_id, client_id).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            app_client_usecase::ErrorRotateSecret::ClientNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "client doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Client")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::DeleteClient])]
+#[delete("/<application_id>/client/<client_id>")]
+pub async fn delete(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    client_id: Uuid,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match app_client_usecase::delete(application_id, client_id).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            app_client_usecase::ErrorDelete::ClientNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "client doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
+    merdge_mulit_routes![
+        settings,
+        [create, get_multiple, get, update, rotate_secret, delete]
+    ]
+}
@@ -0,0 +1,167 @@
+use crate::{
+    guard::{
+        application::ApplicationActor as GuardApplicationActor,
+        staff::application::ApplicationPermission, GuardError,
+    },
+    merdge_mulit_routes,
+    query::key as key_query,
+    schema::{self, key as key_schema},
+    usecase::key as key_usecase,
+};
+use repository_db_lib::app_staff::app_staff_entity::AppStaffPermissions;
+use rocket::{http::Status, serde::json::Json};
+use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
+use rocket_util_lib::guard_permission;
+use uuid::Uuid;
+
+#[openapi(tag = "Application Key")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::CreateKey])]
+#[post("/<application_id>/key", data = "<new_key>")]
+pub async fn create(
+    guard: GuardApplicationActor,
+    application_id: Uuid,
+    new_key: Json<key_schema::CreateKey>,
+) -> (
+    Status,
+    Result<Json<key_schema::KeyDetail>, Json<schema::ErrorResult>>,
+) {
+    match key_usecase::create(guard.get_user_id(), application_id, &new_key.0).await {
+        Ok(v) => (Status::Created, Ok(Json(v))),
+        Err(e) => match e {
+            key_usecase::ErrorCreate::UserNotFound => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            key_usecase::ErrorCreate::InvalidLifetime => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "lifetime must be gte 0".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            key_usecase::ErrorCreate::ValueAllreadyExist => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "key value allready exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Key")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::ReadKey])]
+#[get("/<application_id>/key?<req_query..>")]
+pub async fn get_multiple(
+    _guard: GuardApplicationActor,
+    application_id: Uuid,
+    req_query: key_query::Key,
+) -> (Status, Json<key_schema::KeyList>) {
+    (
+        Status::Ok,
+        Json(key_usecase::get_all(application_id, &req_query).await),
+    )
+}
+
+#[openapi(tag = "Application Key")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::ReadKeyDetail])]
+#[get("/<application_id>/key/<key_id>")]
+pub async fn get(
+    _guard: GuardApplicationActor,
+    application_id: Uuid,
+    key_id: Uuid,
+) -> (
+    Status,
+    Result<Json<key_schema::KeyDetail>, Json<schema::ErrorResult>>,
+) {
+    match key_usecase::get(application_id, key_id).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            key_usecase::ErrorGet::KeyNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "key doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Key")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::UpdateKey])]
+#[put("/<application_id>/key/<key_id>", data = "<key>")]
+pub async fn update(
+    _guard: GuardApplicationActor,
+    application_id: Uuid,
+    key_id: Uuid,
+    key: Json<key_schema::UpdateKey>,
+) -> (
+    Status,
+    Result<Json<key_schema::KeyDetail>, Json<schema::ErrorResult>>,
+) {
+    match key_usecase::update(application_id, key_id, &key.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            key_usecase::ErrorUpdate::KeyNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "key doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            key_usecase::ErrorUpdate::UserNotFound => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            key_usecase::ErrorUpdate::InvalidLifetime => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "lifetime must be gte 0".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Key")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::DeleteKey])]
+#[delete("/<application_id>/key/<key_id>")]
+pub async fn delete(
+    _guard: GuardApplicationActor,
+    application_id: Uuid,
+    key_id: Uuid,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match key_usecase::delete(application_id, key_id).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            key_usecase::ErrorDelete::KeyNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "key doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
+    merdge_mulit_routes![settings, [create, get_multiple, get, update, delete]]
+}
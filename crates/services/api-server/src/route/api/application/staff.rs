@@ -0,0 +1,141 @@
+use crate::{
+    guard::{
+        staff::{application::ApplicationPermission, user::UserStaff as GuardUserStaff},
+        GuardError,
+    },
+    merdge_mulit_routes,
+    query::application as application_query,
+    schema::{self, application as application_schema},
+    usecase::app_staff as app_staff_usecase,
+};
+use repository_db_lib::app_staff::app_staff_entity::AppStaffPermissions;
+use rocket::{http::Status, serde::json::Json};
+use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
+use rocket_util_lib::guard_permission;
+use uuid::Uuid;
+
+#[openapi(tag = "Application Staff")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::ReadStaff])]
+#[get("/<application_id>/staff?<req_query..>")]
+pub async fn get_multiple(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    req_query: application_query::ApplicationStaff,
+) -> (Status, Json<application_schema::ApplicationStaffList>) {
+    (
+        Status::Ok,
+        Json(app_staff_usecase::get_all(application_id, &req_query).await),
+    )
+}
+
+#[openapi(tag = "Application Staff")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::CreateStaff])]
+#[post("/<application_id>/staff", data = "<new_staff>")]
+pub async fn create(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    new_staff: Json<application_schema::CreateApplicationStaff>,
+) -> (
+    Status,
+    Result<Json<application_schema::ApplicationStaff>, Json<schema::ErrorResult>>,
+) {
+    match app_staff_usecase::create(application_id, &new_staff.0).await {
+        Ok(v) => (Status::Created, Ok(Json(v))),
+        Err(e) => match e {
+            app_staff_usecase::ErrorCreate::UserNotFound => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "staff user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            app_staff_usecase::ErrorCreate::StaffAllreadyExist => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "user is allready application staff".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            app_staff_usecase::ErrorCreate::AddStaffFailed => (
+                Status::InternalServerError,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Unknown,
+                    err_msg: "error to add application staff".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Staff")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::UpdateStaff])]
+#[put("/<application_id>/staff/<user_id>", data = "<staff>")]
+pub async fn update(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    user_id: Uuid,
+    staff: Json<application_schema::UpdateApplicationStaff>,
+) -> (
+    Status,
+    Result<Json<application_schema::ApplicationStaff>, Json<schema::ErrorResult>>,
+) {
+    match app_staff_usecase::update(application_id, user_id, &staff.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            app_staff_usecase::ErrorUpdate::StaffNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "application staff doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            app_staff_usecase::ErrorUpdate::LastFullPermissionsStaff => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "application must keep a staff with all permissions".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Application Staff")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, resource = application_id, resolver = ApplicationPermission, all_perms = [AppStaffPermissions::DeleteStaff])]
+#[delete("/<application_id>/staff/<user_id>")]
+pub async fn delete(
+    _guard: GuardUserStaff,
+    application_id: Uuid,
+    user_id: Uuid,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match app_staff_usecase::delete(application_id, user_id).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            app_staff_usecase::ErrorDelete::StaffNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "application staff doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            app_staff_usecase::ErrorDelete::LastFullPermissionsStaff => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "application must keep a staff with all permissions".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
+    merdge_mulit_routes![settings, [get_multiple, create, update, delete]]
+}
@@ -1,22 +1,31 @@
 use crate::{
-    guard::user as user_guard,
+    guard::{client::Client, client_auth::ClientAuth, user as user_guard},
     merdge_mulit_routes,
-    schema::{self, auth as auth_schema, user as user_schema},
-    usecase::auth as auth_usecase,
+    schema::{
+        self, auth as auth_schema, email_verification as email_verification_schema,
+        password_reset as password_reset_schema, user as user_schema,
+    },
+    usecase::{
+        auth as auth_usecase, email_verification as email_verification_usecase,
+        password_reset as password_reset_usecase,
+    },
 };
 use rocket::{form::Form, http::Status, serde::json::Json};
 use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
-use util_lib::auth::jwt::{IntrospectInput, IntrospectResult, Oauth2LoginResult};
+use util_lib::auth::jwt::{
+    IntrospectInput, IntrospectResult, Oauth2LoginResult, RevokeInput, TokenInput,
+};
 
 #[openapi(tag = "Auth")]
 #[post("/register", data = "<user_reg>")]
 pub async fn register(
+    client: Client,
     user_reg: Json<auth_schema::Register>,
 ) -> (
     Status,
     Result<Json<user_schema::User>, Json<schema::ErrorResult>>,
 ) {
-    match auth_usecase::registration(&user_reg.0).await {
+    match auth_usecase::registration(&user_reg.0, &client).await {
         Ok(v) => (Status::Created, Ok(Json(v))),
         Err(e) => match e {
             auth_usecase::ErrorRegister::EmailAllreadyExist => (
@@ -31,30 +40,146 @@ pub async fn register(
     }
 }
 
+/// Returns the tokens, or an MFA challenge if the user has MFA enabled.
 #[openapi(tag = "Auth")]
 #[post("/login", data = "<user_login>")]
 pub async fn login(
+    client: Client,
     user_login: Json<auth_schema::Login>,
+) -> (
+    Status,
+    Result<Json<auth_schema::LoginResult>, Json<schema::ErrorResult>>,
+) {
+    match auth_usecase::login(&user_login.0, &client).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            auth_usecase::ErrorLogin::InvalidCredentials => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "invalid credentials".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            auth_usecase::ErrorLogin::TooManyAttempts { retry_after } => (
+                Status::TooManyRequests,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::TooManyRequests,
+                    err_msg: "too many login attempts".to_string(),
+                    err_detail: Some(schema::get_retry_after_detail(retry_after)),
+                })),
+            ),
+            auth_usecase::ErrorLogin::EmailNotVerified => (
+                Status::Forbidden,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Forbidden,
+                    err_msg: "email is not verified".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+/// Exchanges the MFA challenge of the login and a TOTP or recovery code for the tokens.
+#[openapi(tag = "Auth")]
+#[post("/login/mfa", data = "<login_mfa>")]
+pub async fn login_mfa(
+    client: Client,
+    login_mfa: Json<auth_schema::LoginMfa>,
 ) -> (
     Status,
     Result<Json<Oauth2LoginResult>, Json<schema::ErrorResult>>,
 ) {
-    match auth_usecase::login(&user_login.0).await {
+    match auth_usecase::login_mfa(&login_mfa.0, &client).await {
         Ok(v) => (Status::Ok, Ok(Json(v))),
         Err(e) => match e {
-            auth_usecase::ErrorLogin::UserNotFound => (
+            auth_usecase::ErrorLoginMfa::InvalidMfaToken => (
                 Status::BadRequest,
                 Err(Json(schema::ErrorResult {
-                    err_type: schema::ErrorType::NotFound,
-                    err_msg: "email doesn't exist".to_string(),
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "mfa token is invalid or expired".to_string(),
                     err_detail: None,
                 })),
             ),
-            auth_usecase::ErrorLogin::InvalidPassword => (
+            auth_usecase::ErrorLoginMfa::InvalidCode => (
                 Status::BadRequest,
                 Err(Json(schema::ErrorResult {
                     err_type: schema::ErrorType::InvalidInput,
-                    err_msg: "incorrect password".to_string(),
+                    err_msg: "incorrect code".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            auth_usecase::ErrorLoginMfa::TooManyAttempts { retry_after } => (
+                Status::TooManyRequests,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::TooManyRequests,
+                    err_msg: "too many login attempts".to_string(),
+                    err_detail: Some(schema::get_retry_after_detail(retry_after)),
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Auth")]
+#[post("/token", data = "<token_input>")]
+pub async fn token(
+    client: Client,
+    token_input: Form<TokenInput>,
+) -> (
+    Status,
+    Result<Json<Oauth2LoginResult>, Json<schema::ErrorResult>>,
+) {
+    match auth_usecase::token(&token_input.into_inner(), &client).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            auth_usecase::ErrorToken::MissingRefreshToken => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "refresh_token is required".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            auth_usecase::ErrorToken::InvalidGrant => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "invalid grant".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            auth_usecase::ErrorToken::RefreshTokenReused => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "refresh token was already used, session revoked".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            auth_usecase::ErrorToken::InvalidClient => (
+                Status::Unauthorized,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "invalid client".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            auth_usecase::ErrorToken::InvalidScope => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "invalid scope".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            auth_usecase::ErrorToken::MissingAuthorizationCode => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "code, redirect_uri, client_id and code_verifier are required"
+                        .to_string(),
                     err_detail: None,
                 })),
             ),
@@ -71,10 +196,115 @@ pub async fn logout(user: user_guard::User) -> Status {
 
 #[openapi(tag = "Auth")]
 #[post("/introspect", data = "<token_intro>")]
-pub async fn introspect(token_intro: Form<IntrospectInput>) -> Json<IntrospectResult> {
+pub async fn introspect(
+    _client: ClientAuth,
+    token_intro: Form<IntrospectInput>,
+) -> Json<IntrospectResult> {
     Json(auth_usecase::introspect(&token_intro.into_inner()).await)
 }
 
+/// Revokes an access or refresh token (RFC 7009) issued to the authenticated client
+/// (`INTROSPECTION_CLIENTS`), always answers 200.
+///
+/// The users revoke their own session with `/logout`.
+#[openapi(tag = "Auth")]
+#[post("/revoke", data = "<token_revoke>")]
+pub async fn revoke(client: ClientAuth, token_revoke: Form<RevokeInput>) -> Status {
+    auth_usecase::revoke(&client.client_id, &token_revoke.into_inner()).await;
+    Status::Ok
+}
+
+/// Sends a reset token to the email if it belongs to a user, always answers 202.
+#[openapi(tag = "Auth")]
+#[post("/password-reset/request", data = "<reset_request>")]
+pub async fn password_reset_request(
+    client: Client,
+    reset_request: Json<password_reset_schema::PasswordResetRequest>,
+) -> Status {
+    password_reset_usecase::request(&reset_request.0, &client);
+    Status::Accepted
+}
+
+/// Sets the new password with a reset token, every session of the user is revoked.
+#[openapi(tag = "Auth")]
+#[post("/password-reset/confirm", data = "<reset_confirm>")]
+pub async fn password_reset_confirm(
+    reset_confirm: Json<password_reset_schema::PasswordResetConfirm>,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match password_reset_usecase::confirm(&reset_confirm.0).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            password_reset_usecase::ErrorConfirm::InvalidToken => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "reset token is invalid or expired".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+/// Sends a new verification token to the email if it isn't verified yet, always answers 202.
+#[openapi(tag = "Auth")]
+#[post("/email-verification/request", data = "<verification_request>")]
+pub async fn email_verification_request(
+    client: Client,
+    verification_request: Json<email_verification_schema::EmailVerificationRequest>,
+) -> Status {
+    email_verification_usecase::request(&verification_request.0, &client);
+    Status::Accepted
+}
+
+/// Verifies the email of the token, applying the change of email it was sent for.
+#[openapi(tag = "Auth")]
+#[post("/email-verification/confirm", data = "<verification_confirm>")]
+pub async fn email_verification_confirm(
+    verification_confirm: Json<email_verification_schema::EmailVerificationConfirm>,
+) -> (
+    Status,
+    Result<Json<user_schema::User>, Json<schema::ErrorResult>>,
+) {
+    match email_verification_usecase::confirm(&verification_confirm.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            email_verification_usecase::ErrorConfirm::InvalidToken => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "verification token is invalid or expired".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            email_verification_usecase::ErrorConfirm::EmailAllreadyExist => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "email allready exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+/// Returns the seconds to wait before the next login, as detail of the error.
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
-    merdge_mulit_routes![settings, [register, login, logout, introspect]]
+    merdge_mulit_routes![
+        settings,
+        [
+            register,
+            login,
+            login_mfa,
+            token,
+            logout,
+            introspect,
+            revoke,
+            password_reset_request,
+            password_reset_confirm,
+            email_verification_request,
+            email_verification_confirm
+        ]
+    ]
 }
@@ -0,0 +1,60 @@
+use crate::{
+    guard::client::Client,
+    merdge_mulit_routes,
+    schema::{self, key as key_schema},
+    usecase::key as key_usecase,
+};
+use rocket::{http::Status, serde::json::Json};
+use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
+
+#[openapi(tag = "Key")]
+#[post("/verify", data = "<key>")]
+pub async fn verify(
+    client: Client,
+    key: Json<key_schema::VerifyKey>,
+) -> (
+    Status,
+    Result<Json<key_schema::KeyVerification>, Json<schema::ErrorResult>>,
+) {
+    match key_usecase::verify(&key.0, &client).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            key_usecase::ErrorVerify::NotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "key doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            key_usecase::ErrorVerify::Bunned => (
+                Status::Forbidden,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Forbidden,
+                    err_msg: "key is bunned".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            key_usecase::ErrorVerify::Expired => (
+                Status::Forbidden,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Forbidden,
+                    err_msg: "key is expired".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            key_usecase::ErrorVerify::TooManyAttempts { retry_after } => (
+                Status::TooManyRequests,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::TooManyRequests,
+                    err_msg: "too many key verifications".to_string(),
+                    err_detail: Some(schema::get_retry_after_detail(retry_after)),
+                })),
+            ),
+        },
+    }
+}
+
+pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
+    merdge_mulit_routes![settings, [verify]]
+}
@@ -0,0 +1,45 @@
+use crate::{
+    guard::{staff::user::UserStaff as GuardUserStaff, GuardError},
+    merdge_mulit_routes,
+    query::login_lockout as login_lockout_query,
+    schema::{self, login_lockout as login_lockout_schema, user as user_schema},
+    usecase::login_throttle as login_throttle_usecase,
+};
+use rocket::{http::Status, serde::json::Json};
+use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
+use rocket_util_lib::guard_permission;
+
+/// Returns the emails and client IPs locked out after too many failed logins.
+#[openapi(tag = "Login Lockout")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission)]
+#[get("/")]
+pub async fn get_multiple(_guard: GuardUserStaff) -> Json<login_lockout_schema::LoginLockoutList> {
+    Json(login_throttle_usecase::get_all().await)
+}
+
+/// Lifts the lockout of an email or of a client IP before it expires.
+#[openapi(tag = "Login Lockout")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, all_perms = [user_schema::StaffPermission::ClearLoginLockouts])]
+#[delete("/?<req_query..>")]
+pub async fn clear(
+    _guard: GuardUserStaff,
+    req_query: login_lockout_query::LoginLockout,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match login_throttle_usecase::clear(&req_query).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            login_throttle_usecase::ErrorClear::LockoutNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "lockout doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
+    merdge_mulit_routes![settings, [get_multiple, clear]]
+}
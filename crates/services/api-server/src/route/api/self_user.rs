@@ -3,9 +3,16 @@ use rocket_okapi::{
     get_nested_endpoints_and_docs, okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings,
 };
 
+use uuid::Uuid;
+
 use crate::{
-    guard::user as user_guard, merdge_mulit_routes, schema::user as user_schema,
-    usecase::user as user_usecase,
+    guard::{client::Client, user as user_guard},
+    merdge_mulit_routes,
+    schema::{self, mfa as mfa_schema, session as session_schema, user as user_schema},
+    usecase::{
+        email_verification as email_verification_usecase, mfa as mfa_usecase,
+        session as session_usecase, user as user_usecase,
+    },
 };
 
 // TODO: Add captcha chellenge for update password
@@ -24,8 +31,311 @@ pub async fn update_password(
     }
 }
 
+/// Sends a verification token to the new email, the email changes once the token is confirmed.
+#[openapi(tag = "Self User")]
+#[put("/email", data = "<update_email>")]
+pub async fn update_email(
+    user: user_guard::User,
+    client: Client,
+    update_email: Json<user_schema::UpdateUserEmail>,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match email_verification_usecase::update_email(user.claims.id, &update_email.0, &client).await {
+        Ok(_) => (Status::Accepted, Ok(())),
+        Err(e) => match e {
+            email_verification_usecase::ErrorUpdateEmail::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            email_verification_usecase::ErrorUpdateEmail::WrongPassword => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "incorrect password".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            email_verification_usecase::ErrorUpdateEmail::EmailAllreadyExist => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "email allready exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+#[openapi(tag = "Self User")]
+#[get("/sessions")]
+pub async fn get_sessions(user: user_guard::User) -> Json<session_schema::SessionList> {
+    Json(session_usecase::get_all(&user.claims).await)
+}
+
+#[openapi(tag = "Self User")]
+#[delete("/sessions/<session_id>")]
+pub async fn revoke_session(
+    user: user_guard::User,
+    session_id: Uuid,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match session_usecase::revoke(&user.claims, session_id).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            session_usecase::ErrorRevoke::SessionNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "session doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+/// Revokes every session of the user except the current one.
+#[openapi(tag = "Self User")]
+#[delete("/sessions")]
+pub async fn revoke_other_sessions(user: user_guard::User) -> Status {
+    session_usecase::revoke_others(&user.claims).await;
+    Status::NoContent
+}
+
+#[openapi(tag = "Self User")]
+#[get("/mfa")]
+pub async fn get_mfa(
+    user: user_guard::User,
+) -> (
+    Status,
+    Result<Json<mfa_schema::Mfa>, Json<schema::ErrorResult>>,
+) {
+    match mfa_usecase::get(user.claims.id).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            mfa_usecase::ErrorGet::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+/// Starts the MFA enrolment, confirmed with a code of the authenticator app on `/mfa/activate`.
+#[openapi(tag = "Self User")]
+#[post("/mfa")]
+pub async fn enrol_mfa(
+    user: user_guard::User,
+) -> (
+    Status,
+    Result<Json<mfa_schema::MfaEnrolment>, Json<schema::ErrorResult>>,
+) {
+    match mfa_usecase::enrol(user.claims.id).await {
+        Ok(v) => (Status::Created, Ok(Json(v))),
+        Err(e) => match e {
+            mfa_usecase::ErrorEnrol::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorEnrol::MfaAllreadyEnabled => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "mfa allready enabled".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
+/// Enables MFA, every session of the user is revoked.
+#[openapi(tag = "Self User")]
+#[post("/mfa/activate", data = "<mfa_code>")]
+pub async fn activate_mfa(
+    user: user_guard::User,
+    mfa_code: Json<mfa_schema::MfaCode>,
+) -> (
+    Status,
+    Result<Json<mfa_schema::Mfa>, Json<schema::ErrorResult>>,
+) {
+    match mfa_usecase::activate(user.claims.id, &mfa_code.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            mfa_usecase::ErrorActivate::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorActivate::MfaNotEnrolled => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "mfa enrolment isn't started".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorActivate::MfaAllreadyEnabled => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "mfa allready enabled".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorActivate::InvalidCode => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "incorrect code".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorActivate::TooManyAttempts { retry_after } => (
+                Status::TooManyRequests,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::TooManyRequests,
+                    err_msg: "too many mfa attempts".to_string(),
+                    err_detail: Some(schema::get_retry_after_detail(retry_after)),
+                })),
+            ),
+        },
+    }
+}
+
+/// Replaces the recovery codes, needs a TOTP code.
+#[openapi(tag = "Self User")]
+#[post("/mfa/recovery-codes", data = "<mfa_code>")]
+pub async fn regenerate_mfa_recovery_codes(
+    user: user_guard::User,
+    mfa_code: Json<mfa_schema::MfaCode>,
+) -> (
+    Status,
+    Result<Json<mfa_schema::MfaRecoveryCodes>, Json<schema::ErrorResult>>,
+) {
+    match mfa_usecase::regenerate_recovery_codes(user.claims.id, &mfa_code.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            mfa_usecase::ErrorRegenerateRecoveryCodes::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorRegenerateRecoveryCodes::MfaNotEnabled => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "mfa isn't enabled".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorRegenerateRecoveryCodes::InvalidCode => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "incorrect code".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorRegenerateRecoveryCodes::TooManyAttempts { retry_after } => (
+                Status::TooManyRequests,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::TooManyRequests,
+                    err_msg: "too many mfa attempts".to_string(),
+                    err_detail: Some(schema::get_retry_after_detail(retry_after)),
+                })),
+            ),
+        },
+    }
+}
+
+/// Disables MFA with a TOTP or recovery code, every session of the user is revoked.
+#[openapi(tag = "Self User")]
+#[post("/mfa/disable", data = "<mfa_code>")]
+pub async fn disable_mfa(
+    user: user_guard::User,
+    mfa_code: Json<mfa_schema::MfaCode>,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match mfa_usecase::disable(user.claims.id, &mfa_code.0).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            mfa_usecase::ErrorDisable::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorDisable::MfaNotEnabled => (
+                Status::Conflict,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Conflict,
+                    err_msg: "mfa isn't enabled".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorDisable::MfaRequired => (
+                Status::Forbidden,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::Forbidden,
+                    err_msg: "mfa is required for the user".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorDisable::InvalidCode => (
+                Status::BadRequest,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::InvalidInput,
+                    err_msg: "incorrect code".to_string(),
+                    err_detail: None,
+                })),
+            ),
+            mfa_usecase::ErrorDisable::TooManyAttempts { retry_after } => (
+                Status::TooManyRequests,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::TooManyRequests,
+                    err_msg: "too many mfa attempts".to_string(),
+                    err_detail: Some(schema::get_retry_after_detail(retry_after)),
+                })),
+            ),
+        },
+    }
+}
+
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
     get_nested_endpoints_and_docs! {
-        "/" => merdge_mulit_routes![settings, [update_password]],
+        "/" => merdge_mulit_routes![
+            settings,
+            [
+                update_password,
+                update_email,
+                get_sessions,
+                revoke_session,
+                revoke_other_sessions,
+                get_mfa,
+                enrol_mfa,
+                activate_mfa,
+                regenerate_mfa_recovery_codes,
+                disable_mfa
+            ]
+        ],
     }
 }
@@ -2,14 +2,15 @@ use crate::{
     guard::{staff::user::UserStaff as GuardUserStaff, GuardError},
     merdge_mulit_routes,
     query::user as user_query,
-    schema::user as user_schema,
-    usecase::user as user_usecase,
+    schema::{self, user as user_schema},
+    usecase::{session as session_usecase, user as user_usecase},
 };
-use rocket::serde::json::Json;
+use rocket::{http::Status, serde::json::Json};
 use rocket_okapi::{
     get_nested_endpoints_and_docs, okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings,
 };
 use rocket_util_lib::guard_permission;
+use uuid::Uuid;
 
 #[openapi(tag = "User")]
 #[guard_permission(error_ty = GuardError, perm_error = MissingPermission)]
@@ -23,8 +24,30 @@ pub async fn get_multiple(
     Json(user_usecase::get_all(&req_query).await)
 }
 
+#[openapi(tag = "User")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, all_perms = [user_schema::StaffPermission::RevokeUserSessions])]
+#[delete("/<user_id>/sessions")]
+pub async fn revoke_sessions(
+    _guard: GuardUserStaff,
+    user_id: Uuid,
+) -> (Status, Result<(), Json<schema::ErrorResult>>) {
+    match session_usecase::revoke_all(user_id).await {
+        Ok(_) => (Status::NoContent, Ok(())),
+        Err(e) => match e {
+            session_usecase::ErrorRevokeAll::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
     get_nested_endpoints_and_docs! {
-        "/" => merdge_mulit_routes![settings, [get_multiple]],
+        "/" => merdge_mulit_routes![settings, [get_multiple, revoke_sessions]],
     }
 }
@@ -2,8 +2,8 @@ use crate::{
     guard::{staff::user::UserStaff as GuardUserStaff, GuardError},
     merdge_mulit_routes,
     query::user as user_query,
-    schema::{self, user as user_schema},
-    usecase::user as user_usecase,
+    schema::{self, mfa as mfa_schema, user as user_schema},
+    usecase::{mfa as mfa_usecase, user as user_usecase},
 };
 use rocket::{http::Status, serde::json::Json};
 use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
@@ -100,6 +100,36 @@ pub async fn update(
     }
 }
 
+/// Enforces MFA for the staff user, its staff permissions then need a session verified with MFA.
+#[openapi(tag = "User Staff")]
+#[guard_permission(error_ty = GuardError, perm_error = MissingPermission, all_perms = [user_schema::StaffPermission::UpdateStaffUser])]
+#[put("/<user_id>/mfa", data = "<mfa_required>")]
+pub async fn update_mfa_required(
+    _guard: GuardUserStaff,
+    user_id: Uuid,
+    mfa_required: Json<mfa_schema::UpdateMfaRequired>,
+) -> (
+    Status,
+    Result<Json<user_schema::User>, Json<schema::ErrorResult>>,
+) {
+    match mfa_usecase::update_required(user_id, &mfa_required.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => match e {
+            mfa_usecase::ErrorUpdateRequired::UserNotFound => (
+                Status::NotFound,
+                Err(Json(schema::ErrorResult {
+                    err_type: schema::ErrorType::NotFound,
+                    err_msg: "user doesn't exist".to_string(),
+                    err_detail: None,
+                })),
+            ),
+        },
+    }
+}
+
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
-    merdge_mulit_routes![settings, [get_multiple, create, update]]
+    merdge_mulit_routes![
+        settings,
+        [get_multiple, create, update, update_mfa_required]
+    ]
 }
@@ -1,7 +1,11 @@
-use crate::merdge_mulit_routes;
+use crate::{merdge_mulit_routes, schema, usecase::oauth as oauth_usecase};
 
-use rocket::http::Status;
+use rocket::{http::Status, serde::json::Json};
 use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
+use util_lib::{
+    auth::oidc::OpenIdConfiguration,
+    jwt::key::{JwkSet, KEYS},
+};
 
 #[openapi(tag = "Core")]
 #[get("/healthz")]
@@ -9,6 +13,32 @@ fn health() -> Status {
     Status::Ok
 }
 
+/// Public keys verifying the tokens (RFC 7517), empty with a symmetric algorithm.
+#[get("/.well-known/jwks.json")]
+fn jwks() -> Json<JwkSet> {
+    Json(KEYS.get_jwk_set().to_owned())
+}
+
+/// Metadata of the OpenID provider (OpenID Connect Discovery 1.0), not found if OpenID Connect is
+/// disabled.
+#[get("/.well-known/openid-configuration")]
+fn openid_configuration() -> (
+    Status,
+    Result<Json<OpenIdConfiguration>, Json<schema::ErrorResult>>,
+) {
+    match oauth_usecase::get_openid_configuration() {
+        Some(v) => (Status::Ok, Ok(Json(v))),
+        None => (
+            Status::NotFound,
+            Err(Json(schema::ErrorResult {
+                err_type: schema::ErrorType::NotFound,
+                err_msg: "OpenID Connect is disabled".to_string(),
+                err_detail: None,
+            })),
+        ),
+    }
+}
+
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
-    merdge_mulit_routes![settings, [health]]
+    merdge_mulit_routes![settings, [health], [jwks, openid_configuration]]
 }
@@ -0,0 +1,119 @@
+use super::api::auth as auth_route;
+use crate::{
+    guard::{client::Client, user as user_guard},
+    merdge_mulit_routes,
+    query::oauth as oauth_query,
+    schema::{self, oauth as oauth_schema},
+    usecase::oauth as oauth_usecase,
+};
+use rocket::{form::Form, http::Status, serde::json::Json};
+use rocket_okapi::{okapi::openapi3::OpenApi, openapi, settings::OpenApiSettings};
+use util_lib::auth::{
+    jwt::{Oauth2LoginResult, TokenInput},
+    oidc::UserInfo,
+};
+
+/// Validates the authorization request (code flow with PKCE) of the logged in user.
+///
+/// Called by the login and consent page of the front end (`OIDC_AUTHORIZATION_PAGE`) with the
+/// query of the application and the session of the user, it is not the page the applications
+/// redirect to.
+#[openapi(tag = "OAuth")]
+#[get("/authorize?<req_query..>")]
+pub async fn get_authorize(
+    user: user_guard::User,
+    req_query: oauth_query::Authorize,
+) -> (
+    Status,
+    Result<Json<oauth_schema::AuthorizeInfo>, Json<schema::ErrorResult>>,
+) {
+    match oauth_usecase::get_authorize_info(&user.claims, &req_query).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => authorize_error(e),
+    }
+}
+
+/// Approves or denies the authorization request, returns the redirect to the application.
+#[openapi(tag = "OAuth")]
+#[post("/authorize?<req_query..>", data = "<decision>")]
+pub async fn authorize(
+    user: user_guard::User,
+    req_query: oauth_query::Authorize,
+    decision: Json<oauth_schema::AuthorizeDecision>,
+) -> (
+    Status,
+    Result<Json<oauth_schema::AuthorizeRedirect>, Json<schema::ErrorResult>>,
+) {
+    match oauth_usecase::authorize(&user.claims, &req_query, &decision.0).await {
+        Ok(v) => (Status::Ok, Ok(Json(v))),
+        Err(e) => authorize_error(e),
+    }
+}
+
+/// Token endpoint of the authorization server, same as `/api/auth/token`.
+#[openapi(tag = "OAuth")]
+#[post("/token", data = "<token_input>")]
+pub async fn token(
+    client: Client,
+    token_input: Form<TokenInput>,
+) -> (
+    Status,
+    Result<Json<Oauth2LoginResult>, Json<schema::ErrorResult>>,
+) {
+    auth_route::token(client, token_input).await
+}
+
+/// Returns the claims of the user of the access token (OpenID Connect userinfo).
+#[openapi(tag = "OAuth")]
+#[get("/userinfo")]
+pub async fn userinfo(
+    user: user_guard::UserOrApplication,
+) -> (Status, Result<Json<UserInfo>, Json<schema::ErrorResult>>) {
+    match oauth_usecase::get_self_user_info(&user.claims).await {
+        Some(v) => (Status::Ok, Ok(Json(v))),
+        None => (
+            Status::NotFound,
+            Err(Json(schema::ErrorResult {
+                err_type: schema::ErrorType::NotFound,
+                err_msg: "user doesn't exist".to_string(),
+                err_detail: None,
+            })),
+        ),
+    }
+}
+
+/// The errors are not redirected, the redirect URI can't be trusted.
+fn authorize_error<T>(
+    e: oauth_usecase::ErrorAuthorize,
+) -> (Status, Result<Json<T>, Json<schema::ErrorResult>>) {
+    match e {
+        oauth_usecase::ErrorAuthorize::InvalidClient => (
+            Status::BadRequest,
+            Err(Json(schema::ErrorResult {
+                err_type: schema::ErrorType::NotFound,
+                err_msg: "application doesn't exist".to_string(),
+                err_detail: None,
+            })),
+        ),
+        oauth_usecase::ErrorAuthorize::InvalidRedirectUri => (
+            Status::BadRequest,
+            Err(Json(schema::ErrorResult {
+                err_type: schema::ErrorType::InvalidInput,
+                err_msg: "redirect uri isn't registered for the application".to_string(),
+                err_detail: None,
+            })),
+        ),
+        oauth_usecase::ErrorAuthorize::InvalidScope(scope) => (
+            Status::BadRequest,
+            Err(Json(schema::ErrorResult {
+                err_type: schema::ErrorType::InvalidInput,
+                err_msg: format!("scope {} isn't supported", scope),
+                err_detail: None,
+            })),
+        ),
+    }
+}
+
+pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
+    merdge_mulit_routes![settings, [get_authorize, authorize, token, userinfo]]
+}
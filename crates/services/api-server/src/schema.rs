@@ -1,5 +1,13 @@
+pub mod app_client;
 pub mod application;
 pub mod auth;
+pub mod email_verification;
+pub mod key;
+pub mod login_lockout;
+pub mod mfa;
+pub mod oauth;
+pub mod password_reset;
+pub mod session;
 pub mod user;
 
 use std::collections::HashMap;
@@ -16,8 +24,10 @@ pub struct Result {
 #[derive(Deserialize, Serialize, JsonSchema)]
 pub enum ErrorType {
     NotFound,
+    Forbidden,
     InvalidInput,
     Conflict,
+    TooManyRequests,
     Unknown,
 }
 
@@ -29,6 +39,11 @@ pub struct ErrorResult {
     pub err_detail: Option<HashMap<String, Value>>,
 }
 
+/// Returns the detail of a `TooManyRequests` error: the seconds before the next attempt.
+pub fn get_retry_after_detail(retry_after: u64) -> HashMap<String, Value> {
+    HashMap::from([("retry_after".to_string(), Value::from(retry_after))])
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct Pagination {
     pub limit: i64,
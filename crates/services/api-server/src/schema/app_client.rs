@@ -0,0 +1,161 @@
+use repository_db_lib::app_client::app_client_entity;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_valid::Validate;
+use time::{serde::rfc3339, OffsetDateTime};
+use util_lib::{
+    auth::jwt::Oauth2TokenClaims, date::schema::date_time_rfc3339, jwt,
+    string::validate::string_1_255,
+};
+use uuid::Uuid;
+
+use super::{application::ApplicationPermissions, Pagination};
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct CreateApplicationClient {
+    #[serde(deserialize_with = "string_1_255")]
+    pub name: String,
+    pub permissions: Vec<ApplicationPermissions>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct UpdateApplicationClient {
+    #[serde(deserialize_with = "string_1_255")]
+    pub name: String,
+    pub permissions: Vec<ApplicationPermissions>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct ApplicationClient {
+    /// Identifier of the client, used as `client_id` of the `client_credentials` grant.
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub name: String,
+    pub permissions: Vec<ApplicationPermissions>,
+    pub created_by_user_id: Uuid,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub secret_rotated_at: OffsetDateTime,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl ApplicationClient {
+    pub fn from_model(model: &app_client_entity::Model) -> Self {
+        Self {
+            id: model.id,
+            application_id: model.application_id,
+            name: model.name.to_owned(),
+            permissions: ApplicationPermissions::from_models(&model.permissions),
+            created_by_user_id: model.created_by_user_id,
+            secret_rotated_at: model.secret_rotated_at,
+            updated_at: model.updated_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// Client with its secret, only returned on creation and on rotation of the secret.
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct ApplicationClientSecret {
+    pub secret: String,
+    #[serde(flatten)]
+    pub client: ApplicationClient,
+}
+
+impl ApplicationClientSecret {
+    pub fn from_model(model: &app_client_entity::Model, secret: String) -> Self {
+        Self {
+            secret,
+            client: ApplicationClient::from_model(model),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct ApplicationClientList {
+    pub clients: Vec<ApplicationClient>,
+    #[serde(flatten)]
+    pub pagination: Pagination,
+}
+
+impl ApplicationClientList {
+    pub fn from_models(
+        models: &Vec<app_client_entity::Model>,
+        limit: i64,
+        offset: u64,
+        total: u64,
+    ) -> Self {
+        let mut clients = Vec::<ApplicationClient>::new();
+        for model in models {
+            clients.push(ApplicationClient::from_model(model));
+        }
+        Self {
+            clients,
+            pagination: Pagination {
+                limit,
+                offset,
+                total,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AppClientTokenClaims {
+    pub client_id: Uuid,
+    pub application_id: Uuid,
+    /// Staff user who created the client, the author of the changes made with the token.
+    pub created_by_user_id: Uuid,
+    /// Permissions granted to the token, a subset of the permissions of the client.
+    pub scope: Vec<ApplicationPermissions>,
+    #[serde(flatten)]
+    pub oauth2_claims: Oauth2TokenClaims,
+}
+
+impl AppClientTokenClaims {
+    pub fn new(model: &app_client_entity::Model, scope: Vec<ApplicationPermissions>) -> Self {
+        Self {
+            client_id: model.id,
+            application_id: model.application_id,
+            created_by_user_id: model.created_by_user_id,
+            scope,
+            oauth2_claims: Oauth2TokenClaims::new_access_claims(),
+        }
+    }
+
+    pub fn get_key_for_cache(&self) -> String {
+        get_key_for_cache(
+            self.client_id.to_string(),
+            self.oauth2_claims.jti.to_string(),
+        )
+    }
+
+    pub fn from_jwt(token_str: &str) -> jwt::JWTResult<Self> {
+        match jwt::decode::<Self>(token_str) {
+            Ok(token) => Ok(token.claims),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the scope as a space separated string (OAuth2 `scope` format).
+    pub fn get_scope_str(&self) -> String {
+        self.scope
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+pub fn get_key_for_cache(client_id: String, jwt_id: String) -> String {
+    format!("APP_CLIENT:{}_JWT:{}", client_id, jwt_id)
+}
+
+pub fn get_prefix_key_for_cache(client_id: String) -> String {
+    format!("APP_CLIENT:{}_JWT:*", client_id)
+}
@@ -20,6 +20,9 @@ pub struct CreateApplication {
     pub name: String,
     #[serde(deserialize_with = "string_0_2048", default)]
     pub description: String,
+    /// Absolute `http(s)` URIs (without fragment) allowed on the OAuth2 authorization.
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema, Validate)]
@@ -28,6 +31,9 @@ pub struct UpdateApplication {
     pub name: String,
     #[serde(deserialize_with = "string_0_2048", default)]
     pub description: String,
+    /// Absolute `http(s)` URIs (without fragment) allowed on the OAuth2 authorization.
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema, Validate)]
@@ -37,6 +43,7 @@ pub struct Application {
     pub name: String,
     #[serde(deserialize_with = "string_0_2048", default)]
     pub description: String,
+    pub redirect_uris: Vec<String>,
     #[serde(with = "rfc3339")]
     #[schemars(schema_with = "date_time_rfc3339")]
     pub created_at: OffsetDateTime,
@@ -51,6 +58,7 @@ impl Application {
             id: model.id,
             name: model.name.to_owned(),
             description: model.description.to_owned(),
+            redirect_uris: model.redirect_uris.to_owned(),
             updated_at: model.updated_at,
             created_at: model.created_at,
         }
@@ -108,13 +116,59 @@ pub enum ApplicationPermissions {
     ReadKeyDetail,
     UpdateKey,
     DeleteKey,
+
+    CreateStaff,
+    ReadStaff,
+    UpdateStaff,
+    DeleteStaff,
+
+    CreateClient,
+    ReadClient,
+    UpdateClient,
+    DeleteClient,
+}
+
+impl ApplicationPermissions {
+    pub fn to_models(
+        permissions: &Vec<ApplicationPermissions>,
+    ) -> Vec<app_staff_entity::AppStaffPermissions> {
+        let mut result = Vec::<app_staff_entity::AppStaffPermissions>::new();
+        for perm in permissions {
+            let perm = app_staff_entity::AppStaffPermissions::from_str(&perm.to_string()).unwrap();
+            if !result.contains(&perm) {
+                result.push(perm);
+            }
+        }
+        result
+    }
+
+    pub fn from_models(
+        permissions: &Vec<app_staff_entity::AppStaffPermissions>,
+    ) -> Vec<ApplicationPermissions> {
+        let mut result = Vec::<ApplicationPermissions>::new();
+        for perm in permissions {
+            result.push(ApplicationPermissions::from_str(&perm.to_string()).unwrap());
+        }
+        result
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct CreateApplicationStaff {
+    pub user_id: Uuid,
+    pub permissions: Vec<ApplicationPermissions>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct UpdateApplicationStaff {
+    pub permissions: Vec<ApplicationPermissions>,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema, Validate)]
 pub struct ApplicationStaff {
     pub id: Uuid,
     pub application_id: Uuid,
-    pub usser_id: Uuid,
+    pub user_id: Uuid,
     pub permissions: Vec<ApplicationPermissions>,
     #[serde(with = "rfc3339")]
     #[schemars(schema_with = "date_time_rfc3339")]
@@ -126,17 +180,42 @@ pub struct ApplicationStaff {
 
 impl ApplicationStaff {
     pub fn from_model(model: &app_staff_entity::Model) -> Self {
-        let mut permissions = Vec::<ApplicationPermissions>::new();
-        for perm in &model.permissions {
-            permissions.push(ApplicationPermissions::from_str(&perm.to_string()).unwrap());
-        }
         Self {
             id: model.id,
             application_id: model.application_id,
-            usser_id: model.user_id,
-            permissions,
+            user_id: model.user_id,
+            permissions: ApplicationPermissions::from_models(&model.permissions),
             updated_at: model.updated_at,
             created_at: model.created_at,
         }
     }
 }
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct ApplicationStaffList {
+    pub staff: Vec<ApplicationStaff>,
+    #[serde(flatten)]
+    pub pagination: Pagination,
+}
+
+impl ApplicationStaffList {
+    pub fn from_models(
+        models: &Vec<app_staff_entity::Model>,
+        limit: i64,
+        offset: u64,
+        total: u64,
+    ) -> Self {
+        let mut staff = Vec::<ApplicationStaff>::new();
+        for model in models {
+            staff.push(ApplicationStaff::from_model(model));
+        }
+        Self {
+            staff,
+            pagination: Pagination {
+                limit,
+                offset,
+                total,
+            },
+        }
+    }
+}
@@ -5,9 +5,12 @@ use repository_db_lib::user::user_entity;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_valid::Validate;
-use time::Date;
+use time::{serde::rfc3339, Date, OffsetDateTime};
 use util_lib::{
-    auth::jwt::Oauth2TokenClaims, date::schema::date_rfc3339, jwt, string::validate::string_1_255,
+    auth::jwt::{Oauth2LoginResult, Oauth2TokenClaims},
+    date::schema::date_rfc3339,
+    jwt,
+    string::validate::{email, string_1_255},
 };
 use uuid::Uuid;
 
@@ -19,12 +22,53 @@ pub struct Login {
     pub password: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Result of the login, a challenge if the user has MFA enabled.
+#[derive(Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum LoginResult {
+    Token(Oauth2LoginResult),
+    MfaChallenge(MfaChallenge),
+}
+
+/// Challenge of the login, exchanged for the tokens with a TOTP or recovery code.
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct MfaChallenge {
+    /// Always `true`, tells the challenge apart from the tokens.
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_in: u64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct LoginMfa {
+    #[serde(deserialize_with = "string_1_255")]
+    pub mfa_token: String,
+    /// TOTP code or a recovery code.
+    #[serde(deserialize_with = "string_1_255")]
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SelfUserTokenClaims {
     pub id: Uuid,
     pub is_staff: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub permissions: Option<Vec<StaffPermission>>,
+    /// Token version of the user at the issue, the token is revoked once the version is bumped.
+    pub token_version: i32,
+    /// The session was verified with a second factor.
+    #[serde(default)]
+    pub mfa: bool,
+    /// MFA enforced by staff, the staff permissions need a session verified with MFA.
+    #[serde(default)]
+    pub mfa_required: bool,
+    /// Application the token was issued to (`authorization_code` grant), `None` for a session of
+    /// the service itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Space separated scopes granted to the application.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
     #[serde(flatten)]
     pub oauth2_claims: Oauth2TokenClaims,
 }
@@ -34,10 +78,6 @@ impl SelfUserTokenClaims {
         get_key_for_cache(self.id.to_string(), self.oauth2_claims.jti.to_string())
     }
 
-    pub fn get_prefix_key_for_cache(&self) -> String {
-        get_prefix_key_for_cache(self.id.to_string())
-    }
-
     pub fn from_jwt(token_str: &String) -> jwt::JWTResult<Self> {
         match jwt::decode::<Self>(&token_str) {
             Ok(token) => Ok(token.claims),
@@ -45,7 +85,7 @@ impl SelfUserTokenClaims {
         }
     }
 
-    pub fn from_model(user: &user_entity::Model, claims: Oauth2TokenClaims) -> Self {
+    pub fn from_model(user: &user_entity::Model, claims: Oauth2TokenClaims, mfa: bool) -> Self {
         let mut staff_permissions = Vec::<StaffPermission>::new();
         for permission in &user.staff_permissions {
             staff_permissions.push(StaffPermission::from_str(&permission.to_string()).unwrap());
@@ -58,20 +98,99 @@ impl SelfUserTokenClaims {
             id: user.id,
             is_staff: user.is_staff,
             permissions,
+            token_version: user.token_version,
+            mfa,
+            mfa_required: user.mfa_required,
+            aud: None,
+            scope: None,
             oauth2_claims: claims,
         }
     }
 
-    pub fn access_and_refresh_from_model(user: &user_entity::Model) -> (Self, Self) {
-        let (access_claims, refresh_claims) = Oauth2TokenClaims::new_claims();
+    /// Limits the claims to the application and its granted scopes, the token gets no staff
+    /// access.
+    pub fn for_application(mut self, application_id: String, scope: Option<String>) -> Self {
+        self.is_staff = false;
+        self.permissions = None;
+        self.aud = Some(application_id);
+        self.scope = scope;
+        self
+    }
+
+    /// Checks the token was issued to an application, it only gives access to the claims of the
+    /// user (`/oauth/userinfo`).
+    pub fn is_application_token(&self) -> bool {
+        self.aud.is_some()
+    }
 
-        let access_user_claims = Self::from_model(user, access_claims);
-        let refresh_user_claims = Self::from_model(user, refresh_claims);
+    /// Returns the granted scopes of an application token, `None` for a session of the service.
+    pub fn get_scopes(&self) -> Option<Vec<String>> {
+        self.scope
+            .as_ref()
+            .map(|v| v.split_whitespace().map(|v| v.to_string()).collect())
+    }
+
+    pub fn access_and_refresh_from_model(user: &user_entity::Model, mfa: bool) -> (Self, Self) {
+        Self::from_model_with_claims(user, Oauth2TokenClaims::new_claims(), mfa)
+    }
+
+    pub fn access_and_refresh_from_model_for_session(
+        user: &user_entity::Model,
+        sid: Uuid,
+        mfa: bool,
+    ) -> (Self, Self) {
+        Self::from_model_with_claims(user, Oauth2TokenClaims::new_session_claims(sid), mfa)
+    }
+
+    /// Checks the staff permissions apply, MFA enforced by staff needs a session verified with MFA.
+    pub fn is_mfa_satisfied(&self) -> bool {
+        !self.mfa_required || self.mfa
+    }
+
+    fn from_model_with_claims(
+        user: &user_entity::Model,
+        (access_claims, refresh_claims): (Oauth2TokenClaims, Oauth2TokenClaims),
+        mfa: bool,
+    ) -> (Self, Self) {
+        let access_user_claims = Self::from_model(user, access_claims, mfa);
+        let refresh_user_claims = Self::from_model(user, refresh_claims, mfa);
 
         (access_user_claims, refresh_user_claims)
     }
 }
 
+/// Metadata of the session a token belongs to, cached next to the token claims.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionMeta {
+    #[serde(with = "rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// Last known IP address of the client.
+    pub ip: Option<String>,
+    /// Last known user agent of the client.
+    pub user_agent: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub last_used_at: OffsetDateTime,
+}
+
+impl SessionMeta {
+    pub fn new(ip: Option<String>, user_agent: Option<String>) -> Self {
+        let now = OffsetDateTime::now_utc();
+        Self {
+            created_at: now,
+            ip,
+            user_agent,
+            last_used_at: now,
+        }
+    }
+}
+
+/// Value of an issued token in the cache.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CachedToken {
+    pub claims: SelfUserTokenClaims,
+    pub session: SessionMeta,
+}
+
 pub fn get_key_for_cache(user_id: String, jwt_id: String) -> String {
     format!("USER:{}_JWT:{}", user_id, jwt_id)
 }
@@ -80,11 +199,20 @@ pub fn get_prefix_key_for_cache(user_id: String) -> String {
     format!("USER:{}_JWT:*", user_id)
 }
 
+pub fn get_used_key_for_cache(user_id: String, jwt_id: String) -> String {
+    format!("USER:{}_JWT_USED:{}", user_id, jwt_id)
+}
+
+/// Key of the cached token version of the user.
+pub fn get_token_version_key_for_cache(user_id: String) -> String {
+    format!("USER:{}_TOKEN_VERSION", user_id)
+}
+
 #[derive(Deserialize, Serialize, JsonSchema, Validate)]
 pub struct Register {
     #[serde(deserialize_with = "string_1_255")]
     pub name: String,
-    #[serde(deserialize_with = "string_1_255")]
+    #[serde(deserialize_with = "email")]
     pub email: String,
     #[serde(deserialize_with = "string_1_255")]
     pub password: String,
@@ -0,0 +1,57 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_valid::Validate;
+use time::{serde::rfc3339, OffsetDateTime};
+use util_lib::string::validate::{email, string_1_255};
+use uuid::Uuid;
+
+/// Name of the notification sent with the verification token.
+pub const NOTIFICATION_EVENT: &str = "user-email-verification";
+/// Number of random bytes of a verification token.
+pub const TOKEN_LEN: usize = 32;
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct EmailVerificationRequest {
+    #[serde(deserialize_with = "email")]
+    pub email: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct EmailVerificationConfirm {
+    /// Verification token received with the notification.
+    #[serde(deserialize_with = "string_1_255")]
+    pub token: String,
+}
+
+/// Verification token in cache, stored under the digest of the token.
+#[derive(Deserialize, Serialize)]
+pub struct EmailVerificationCache {
+    pub user_id: Uuid,
+    /// Email the token was sent to, it becomes the email of the user on confirmation.
+    pub email: String,
+}
+
+/// Payload of the notification delivering the verification token to the email.
+#[derive(Deserialize, Serialize)]
+pub struct EmailVerificationNotification {
+    pub user_id: Uuid,
+    /// Address to verify, the new one on a change of email.
+    pub email: String,
+    pub name: String,
+    pub token: String,
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+pub fn get_key_for_cache(token_digest: String) -> String {
+    format!("EMAIL_VERIFICATION:{}", token_digest)
+}
+
+/// Key of the digest of the last verification token of the user, to void it on a new request.
+pub fn get_user_key_for_cache(user_id: String) -> String {
+    format!("USER:{}_EMAIL_VERIFICATION", user_id)
+}
+
+pub fn get_requests_key_for_cache(subject: &str) -> String {
+    format!("EMAIL_VERIFICATION_REQUESTS:{}", subject)
+}
@@ -0,0 +1,177 @@
+use repository_db_lib::key::key_entity;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_valid::Validate;
+use time::{serde::rfc3339, OffsetDateTime};
+use util_lib::{date::schema::date_time_rfc3339, jwt, string::validate::string_1_255};
+use uuid::Uuid;
+
+use super::Pagination;
+
+pub const KEY_VERDICT_LIFE_SEC: i64 = 86_400;
+/// Number of unknown keys accepted from a client IP before its verifications are locked.
+pub const VERIFY_MAX_FAILURES: u64 = 20;
+/// Lock of the verifications of a client IP after too many unknown keys, from the last one.
+pub const VERIFY_LOCK_SEC: u64 = 900;
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct CreateKey {
+    #[serde(
+        deserialize_with = "string_1_255",
+        default = "key_entity::Model::gen_value"
+    )]
+    pub value: String,
+    pub lifetime: i32,
+    #[serde(default)]
+    pub is_bunned: bool,
+    pub user_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct UpdateKey {
+    pub lifetime: i32,
+    pub is_bunned: bool,
+    pub user_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct Key {
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub user_id: Uuid,
+    pub created_by_user_id: Uuid,
+    pub lifetime: i32,
+    pub is_bunned: bool,
+    #[serde(with = "rfc3339::option")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub activated_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl Key {
+    pub fn from_model(model: &key_entity::Model) -> Self {
+        Self {
+            id: model.id,
+            application_id: model.application_id,
+            user_id: model.user_id,
+            created_by_user_id: model.created_by_user_id,
+            lifetime: model.lifetime,
+            is_bunned: model.is_bunned,
+            activated_at: model.activated_at,
+            updated_at: model.updated_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct KeyDetail {
+    pub value: String,
+    #[serde(flatten)]
+    pub key: Key,
+}
+
+impl KeyDetail {
+    pub fn from_model(model: &key_entity::Model) -> Self {
+        Self {
+            value: model.value.to_owned(),
+            key: Key::from_model(model),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct KeyList {
+    pub keys: Vec<Key>,
+    #[serde(flatten)]
+    pub pagination: Pagination,
+}
+
+impl KeyList {
+    pub fn from_models(
+        models: &Vec<key_entity::Model>,
+        limit: i64,
+        offset: u64,
+        total: u64,
+    ) -> Self {
+        let mut keys = Vec::<Key>::new();
+        for model in models {
+            keys.push(Key::from_model(model));
+        }
+        Self {
+            keys,
+            pagination: Pagination {
+                limit,
+                offset,
+                total,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct VerifyKey {
+    #[serde(deserialize_with = "string_1_255")]
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeyVerdictClaims {
+    pub key_id: Uuid,
+    pub application_id: Uuid,
+    pub user_id: Uuid,
+    pub remaining_lifetime: i64,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+impl KeyVerdictClaims {
+    pub fn from_model(model: &key_entity::Model, now: OffsetDateTime) -> Self {
+        let remaining_lifetime = model.remaining_lifetime_at(now);
+        let now_unix = now.unix_timestamp();
+        Self {
+            key_id: model.id,
+            application_id: model.application_id,
+            user_id: model.user_id,
+            remaining_lifetime,
+            iat: now_unix as u64,
+            exp: (now_unix + remaining_lifetime.min(KEY_VERDICT_LIFE_SEC)) as u64,
+        }
+    }
+
+    pub fn to_jwt(&self) -> jwt::JWTResult<String> {
+        jwt::encode(self)
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct KeyVerification {
+    pub application_id: Uuid,
+    pub remaining_lifetime: i64,
+    #[serde(with = "rfc3339::option")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub expires_at: Option<OffsetDateTime>,
+    pub verdict: String,
+}
+
+impl KeyVerification {
+    pub fn from_model(model: &key_entity::Model, now: OffsetDateTime) -> Self {
+        let verdict_claims = KeyVerdictClaims::from_model(model, now);
+        Self {
+            application_id: model.application_id,
+            remaining_lifetime: verdict_claims.remaining_lifetime,
+            expires_at: model.expires_at(),
+            verdict: verdict_claims.to_jwt().unwrap(),
+        }
+    }
+}
+
+/// Key of the counter of the unknown keys sent from the client IP.
+pub fn get_verify_failures_key_for_cache(ip: &str) -> String {
+    format!("KEY_VERIFY_FAILURES:{}", ip)
+}
@@ -0,0 +1,57 @@
+use rocket::form::FromFormField;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+use time::{serde::rfc3339, OffsetDateTime};
+use util_lib::date::schema::date_time_rfc3339;
+
+/// What the failed logins are counted for.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, FromFormField, Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LockoutKind {
+    #[field(value = "email")]
+    Email,
+    #[field(value = "ip")]
+    Ip,
+}
+
+/// Temporary lockout of the login, also the value of the lockout in the cache.
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct LoginLockout {
+    pub kind: LockoutKind,
+    /// Lowercase email or IP address of the client.
+    pub subject: String,
+    /// Failed logins in the window which caused the lockout.
+    pub failures: u64,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub locked_at: OffsetDateTime,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct LoginLockoutList {
+    pub lockouts: Vec<LoginLockout>,
+}
+
+pub fn get_failures_key_for_cache(kind: LockoutKind, subject: &str) -> String {
+    format!("LOGIN_FAILURES:{}:{}", kind, subject)
+}
+
+/// Key of the backoff of an email, its value is the unix time the next login is allowed.
+pub fn get_backoff_key_for_cache(subject: &str) -> String {
+    format!("LOGIN_BACKOFF:{}", subject)
+}
+
+pub fn get_lockout_key_for_cache(kind: LockoutKind, subject: &str) -> String {
+    format!("LOGIN_LOCKOUT:{}:{}", kind, subject)
+}
+
+pub fn get_lockout_prefix_key_for_cache() -> String {
+    "LOGIN_LOCKOUT:*".to_string()
+}
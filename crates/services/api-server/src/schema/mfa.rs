@@ -0,0 +1,90 @@
+use repository_db_lib::user::user_entity;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_valid::Validate;
+use util_lib::string::validate::string_1_255;
+use uuid::Uuid;
+
+/// Lifetime of an MFA challenge of the login.
+pub const CHALLENGE_LIFE_SEC: u64 = 300;
+/// Number of wrong codes accepted for a challenge before it is revoked.
+pub const CHALLENGE_MAX_ATTEMPTS: u64 = 5;
+/// Number of codes accepted for the changes of the MFA of a user before they are locked.
+pub const MAX_ATTEMPTS: u64 = 5;
+/// Lock of the changes of the MFA of a user after too many codes, from the last one.
+pub const ATTEMPTS_LOCK_SEC: u64 = 900;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct Mfa {
+    pub enabled: bool,
+    /// MFA enforced by staff, it can't be disabled by the user.
+    pub required: bool,
+    pub recovery_codes_left: usize,
+}
+
+impl Mfa {
+    pub fn from_model(model: &user_entity::Model) -> Self {
+        Self {
+            enabled: model.is_mfa_enabled(),
+            required: model.mfa_required,
+            recovery_codes_left: match model.is_mfa_enabled() {
+                true => model.mfa_recovery_codes.len(),
+                false => 0,
+            },
+        }
+    }
+}
+
+/// Secret to add to an authenticator app, only returned on enrolment.
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct MfaEnrolment {
+    /// Base32 TOTP secret, to type in when the provisioning URI can't be scanned.
+    pub secret: String,
+    /// `otpauth://` URI, usually shown as QR code.
+    pub provisioning_uri: String,
+    /// Single-use codes replacing a TOTP code once the authenticator is lost.
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct MfaRecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct MfaCode {
+    /// TOTP code, or a recovery code where accepted.
+    #[serde(deserialize_with = "string_1_255")]
+    pub code: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct UpdateMfaRequired {
+    pub required: bool,
+}
+
+/// Value of an MFA challenge of the login in the cache.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MfaChallengeCache {
+    pub user_id: Uuid,
+    /// Unix time of the expiration of the challenge.
+    pub expires_at: u64,
+}
+
+pub fn get_challenge_key_for_cache(mfa_token: String) -> String {
+    format!("MFA_CHALLENGE:{}", mfa_token)
+}
+
+/// Key of the counter of the wrong codes sent for the challenge.
+pub fn get_challenge_attempts_key_for_cache(mfa_token: String) -> String {
+    format!("MFA_CHALLENGE_ATTEMPTS:{}", mfa_token)
+}
+
+/// Key of the counter of the codes sent to change the MFA of the user.
+pub fn get_attempts_key_for_cache(user_id: String) -> String {
+    format!("USER:{}_MFA_ATTEMPTS", user_id)
+}
+
+pub fn get_step_key_for_cache(user_id: String, step: u64) -> String {
+    format!("USER:{}_MFA_STEP:{}", user_id, step)
+}
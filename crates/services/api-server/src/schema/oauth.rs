@@ -0,0 +1,78 @@
+use rocket::form::FromFormField;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util_lib::auth::{oidc, pkce::CodeChallengeMethod};
+use uuid::Uuid;
+
+/// Life of an authorization code, it must be exchanged right after the redirect.
+pub const AUTHORIZATION_CODE_LIFE_SEC: u64 = 60;
+
+/// Response type of the OAuth2 authorization request, only the code flow is supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, FromFormField)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseType {
+    #[field(value = "code")]
+    Code,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct AuthorizeDecision {
+    /// Whether the user grants the requested scopes to the application.
+    pub approve: bool,
+}
+
+/// Authorization request to show to the user before the decision.
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct AuthorizeInfo {
+    pub application_id: Uuid,
+    pub application_name: String,
+    pub scopes: Vec<String>,
+    /// `false` if the user already granted the requested scopes to the application.
+    pub consent_required: bool,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct AuthorizeRedirect {
+    /// Redirect URI with the code (or the error) and the state of the request.
+    pub redirect_to: String,
+}
+
+/// Authorization code in the cache, exchanged once for tokens.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuthorizationCode {
+    pub user_id: Uuid,
+    pub application_id: Uuid,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub code_challenge: String,
+    pub code_challenge_method: CodeChallengeMethod,
+    pub nonce: Option<String>,
+    /// Time the user authenticated (start of the session used on the authorization).
+    pub auth_time: u64,
+    /// The session used on the authorization was verified with MFA.
+    #[serde(default)]
+    pub mfa: bool,
+}
+
+/// Splits the space separated scopes, without duplicates.
+///
+/// # Returns
+/// The first scope that isn't supported as error.
+pub fn parse_scope(scope: Option<&String>) -> Result<Vec<String>, String> {
+    let mut scopes = Vec::<String>::new();
+    if let Some(scope) = scope {
+        for v in scope.split_whitespace() {
+            if !oidc::SCOPES_SUPPORTED.contains(&v) {
+                return Err(v.to_owned());
+            }
+            if !scopes.iter().any(|s| s == v) {
+                scopes.push(v.to_owned());
+            }
+        }
+    }
+    Ok(scopes)
+}
+
+pub fn get_key_for_cache(code: String) -> String {
+    format!("OAUTH_CODE:{}", code)
+}
@@ -0,0 +1,58 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_valid::Validate;
+use time::{serde::rfc3339, OffsetDateTime};
+use util_lib::string::validate::string_1_255;
+use uuid::Uuid;
+
+/// Name of the notification sent with the reset token.
+pub const NOTIFICATION_EVENT: &str = "user-password-reset";
+/// Number of random bytes of a reset token.
+pub const TOKEN_LEN: usize = 32;
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct PasswordResetRequest {
+    #[serde(deserialize_with = "string_1_255")]
+    pub email: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct PasswordResetConfirm {
+    /// Reset token received with the notification.
+    #[serde(deserialize_with = "string_1_255")]
+    pub token: String,
+    #[serde(deserialize_with = "string_1_255")]
+    pub new_password: String,
+}
+
+/// Reset token in cache, stored under the digest of the token.
+#[derive(Deserialize, Serialize)]
+pub struct PasswordResetCache {
+    pub user_id: Uuid,
+    /// Token version of the user at the request, the token is void once the password changed.
+    pub token_version: i32,
+}
+
+/// Payload of the notification delivering the reset token to the user.
+#[derive(Deserialize, Serialize)]
+pub struct PasswordResetNotification {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub token: String,
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+pub fn get_key_for_cache(token_digest: String) -> String {
+    format!("PASSWORD_RESET:{}", token_digest)
+}
+
+/// Key of the digest of the last reset token of the user, to void it on a new request.
+pub fn get_user_key_for_cache(user_id: String) -> String {
+    format!("USER:{}_PASSWORD_RESET", user_id)
+}
+
+pub fn get_requests_key_for_cache(subject: &str) -> String {
+    format!("PASSWORD_RESET_REQUESTS:{}", subject)
+}
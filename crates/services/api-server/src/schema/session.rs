@@ -0,0 +1,39 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use time::{serde::rfc3339, OffsetDateTime};
+use util_lib::date::schema::date_time_rfc3339;
+use uuid::Uuid;
+
+use super::auth::SessionMeta;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct Session {
+    pub id: Uuid,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub is_current: bool,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "rfc3339")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub last_used_at: OffsetDateTime,
+}
+
+impl Session {
+    pub fn from_meta(id: Uuid, meta: &SessionMeta, is_current: bool) -> Self {
+        Self {
+            id,
+            ip: meta.ip.to_owned(),
+            user_agent: meta.user_agent.to_owned(),
+            is_current,
+            created_at: meta.created_at,
+            last_used_at: meta.last_used_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct SessionList {
+    pub sessions: Vec<Session>,
+}
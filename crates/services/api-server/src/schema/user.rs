@@ -8,7 +8,7 @@ use strum_macros::{Display, EnumString, IntoStaticStr};
 use time::{serde::rfc3339, Date, OffsetDateTime};
 use util_lib::{
     date::schema::{date_rfc3339, date_time_rfc3339},
-    string::validate::string_1_255,
+    string::validate::{email, string_1_255},
 };
 use uuid::Uuid;
 
@@ -18,7 +18,7 @@ use super::Pagination;
 pub struct CreateUser {
     #[serde(deserialize_with = "string_1_255")]
     pub name: String,
-    #[serde(deserialize_with = "string_1_255")]
+    #[serde(deserialize_with = "email")]
     pub email: String,
     #[serde(skip)]
     pub is_staff: Option<bool>,
@@ -48,12 +48,15 @@ pub struct UpdateUser {
 )]
 pub enum StaffPermission {
     CreateApplication,
+    RestoreApplication,
 
     CreateStaffUser,
     DeleteStaffUser,
     UpdateStaffUser,
 
     DeleteUser,
+    RevokeUserSessions,
+    ClearLoginLockouts,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -61,8 +64,13 @@ pub struct User {
     pub id: Uuid,
     pub name: String,
     pub email: String,
+    #[serde(with = "rfc3339::option")]
+    #[schemars(schema_with = "date_time_rfc3339")]
+    pub email_verified_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub permissions: Option<Vec<StaffPermission>>,
+    pub mfa_enabled: bool,
+    pub mfa_required: bool,
     #[serde(with = "rfc3339")]
     #[schemars(schema_with = "date_time_rfc3339")]
     pub created_at: OffsetDateTime,
@@ -85,7 +93,10 @@ impl User {
             id: model.id,
             name: model.name.to_owned(),
             email: model.email.to_owned(),
+            email_verified_at: model.email_verified_at,
             permissions,
+            mfa_enabled: model.is_mfa_enabled(),
+            mfa_required: model.mfa_required,
             updated_at: model.updated_at,
             created_at: model.created_at,
         }
@@ -128,3 +139,12 @@ pub struct UpdateUserPassword {
     #[serde(deserialize_with = "string_1_255")]
     pub new_password: String,
 }
+
+/// New email of the user, set once confirmed with the token sent to it.
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct UpdateUserEmail {
+    #[serde(deserialize_with = "email")]
+    pub email: String,
+    #[serde(deserialize_with = "string_1_255")]
+    pub password: String,
+}
@@ -0,0 +1,106 @@
+use env_settings_derive::EnvSettings;
+use once_cell::sync::Lazy;
+use util_lib::bool::env::Bool;
+
+pub static SETTINGS: Lazy<Settings> = Lazy::new(|| Settings {
+    introspection: Introspection::from_env().unwrap(),
+    mfa: Mfa::from_env().unwrap(),
+    login_throttle: LoginThrottle::from_env().unwrap(),
+    password_reset: PasswordReset::from_env().unwrap(),
+    email_verification: EmailVerification::from_env().unwrap(),
+    oidc: Oidc::from_env().unwrap(),
+});
+
+pub struct Settings {
+    pub introspection: Introspection,
+    pub mfa: Mfa,
+    pub login_throttle: LoginThrottle,
+    pub password_reset: PasswordReset,
+    pub email_verification: EmailVerification,
+    pub oidc: Oidc,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "INTROSPECTION_")]
+pub struct Introspection {
+    /// Comma separated `<client_id>:<client_secret>` of the clients allowed to introspect tokens.
+    ///
+    /// A client only revokes the tokens issued to it: the id of an application or of an
+    /// application client.
+    #[env_settings(default = "")]
+    pub clients: String,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "MFA_")]
+pub struct Mfa {
+    /// Issuer shown by the authenticator apps next to the account.
+    #[env_settings(default = "RBCA")]
+    pub issuer: String,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "LOGIN_THROTTLE_")]
+pub struct LoginThrottle {
+    /// Sliding window of the failed logins counted for a lockout.
+    #[env_settings(default = "900")]
+    pub window_sec: u64,
+    /// Failed logins of an email in the window before its lockout.
+    #[env_settings(default = "10")]
+    pub email_max_attempts: u64,
+    /// Failed logins of a client IP in the window before its lockout.
+    #[env_settings(default = "100")]
+    pub ip_max_attempts: u64,
+    /// Delay after the first failed login of an email, doubled on every next failure.
+    #[env_settings(default = "1")]
+    pub backoff_base_sec: u64,
+    #[env_settings(default = "60")]
+    pub backoff_max_sec: u64,
+    #[env_settings(default = "900")]
+    pub lockout_sec: u64,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "PASSWORD_RESET_")]
+pub struct PasswordReset {
+    #[env_settings(default = "3600")]
+    pub token_life_sec: u64,
+    /// Sliding window of the reset requests counted for the limits.
+    #[env_settings(default = "3600")]
+    pub window_sec: u64,
+    /// Reset requests of an email in the window, the next ones are ignored.
+    #[env_settings(default = "3")]
+    pub email_max_requests: u64,
+    /// Reset requests of a client IP in the window, the next ones are ignored.
+    #[env_settings(default = "20")]
+    pub ip_max_requests: u64,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "EMAIL_VERIFICATION_")]
+pub struct EmailVerification {
+    #[env_settings(default = "86400")]
+    pub token_life_sec: u64,
+    /// Refuse the login of the users who didn't verify their email.
+    #[env_settings(default = 0)]
+    pub required_for_login: Bool,
+    /// Sliding window of the verification requests counted for the limits.
+    #[env_settings(default = "3600")]
+    pub window_sec: u64,
+    /// Verification requests of an email in the window, the next ones are ignored.
+    #[env_settings(default = "3")]
+    pub email_max_requests: u64,
+    /// Verification requests of a client IP in the window, the next ones are ignored.
+    #[env_settings(default = "20")]
+    pub ip_max_requests: u64,
+}
+
+#[derive(EnvSettings)]
+#[env_settings(case_insensitive, delay, prefix = "OIDC_")]
+pub struct Oidc {
+    /// Absolute URL of the login and consent page of the front end, advertised as authorization
+    /// endpoint. The page logs the user in and calls `/oauth/authorize` with its session.
+    /// OpenID Connect is disabled when empty.
+    #[env_settings(default = "")]
+    pub authorization_page: String,
+}
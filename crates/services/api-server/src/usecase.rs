@@ -1,3 +1,13 @@
+pub mod app_client;
+pub mod app_staff;
 pub mod application;
 pub mod auth;
+pub mod email_verification;
+pub mod key;
+pub mod login_throttle;
+pub mod mfa;
+pub mod notification;
+pub mod oauth;
+pub mod password_reset;
+pub mod session;
 pub mod user;
@@ -0,0 +1,320 @@
+use super::application as application_usecase;
+use crate::{
+    query::application as application_query,
+    schema::{app_client as app_client_schema, application as application_schema},
+};
+use orm_util_lib::prelude::EntityFilterableTrait;
+use repository_db_lib::{
+    app_client::{app_client_entity, AppClient as AppClientRep},
+    app_staff::app_staff_entity::AppStaffPermissions,
+    Repository,
+};
+use repository_redis_lib as redis_repository;
+use sea_orm::{ColumnTrait, Condition, Set};
+use std::str::FromStr;
+use util_lib::{
+    auth::{self, jwt as auth_jwt},
+    jwt::encode as jwt_encode,
+};
+use uuid::Uuid;
+
+pub enum ErrorCreate {
+    InvalidPermissions,
+    MissingPermissions,
+}
+
+pub enum ErrorGet {
+    ClientNotFound,
+}
+
+pub enum ErrorUpdate {
+    ClientNotFound,
+    InvalidPermissions,
+    MissingPermissions,
+}
+
+pub enum ErrorRotateSecret {
+    ClientNotFound,
+}
+
+pub enum ErrorDelete {
+    ClientNotFound,
+}
+
+pub enum ErrorIssueToken {
+    InvalidClient,
+    InvalidScope,
+}
+
+pub async fn create(
+    creator_id: Uuid,
+    application_id: Uuid,
+    new_client: &app_client_schema::CreateApplicationClient,
+) -> Result<app_client_schema::ApplicationClientSecret, ErrorCreate> {
+    let permissions =
+        application_schema::ApplicationPermissions::to_models(&new_client.permissions);
+    match check_permissions(creator_id, application_id, &permissions).await {
+        Err(ErrorCheckPermissions::Invalid) => return Err(ErrorCreate::InvalidPermissions),
+        Err(ErrorCheckPermissions::Missing) => return Err(ErrorCreate::MissingPermissions),
+        Ok(_) => (),
+    }
+
+    // Save new client (the secret is hashed on save)
+    let secret = app_client_entity::Model::gen_secret();
+    let app_client_model = app_client_entity::ActiveModel {
+        application_id: Set(application_id),
+        name: Set(new_client.name.to_owned()),
+        secret: Set(secret.to_owned()),
+        permissions: Set(permissions),
+        created_by_user_id: Set(creator_id),
+        ..Default::default()
+    };
+    let rep = AppClientRep::new().await;
+    let app_client_model = rep.create(app_client_model).await.unwrap();
+    Ok(app_client_schema::ApplicationClientSecret::from_model(
+        &app_client_model,
+        secret,
+    ))
+}
+
+pub async fn get_all(
+    application_id: Uuid,
+    query_filter: &application_query::ApplicationClient,
+) -> app_client_schema::ApplicationClientList {
+    // Get filter
+    let filter = query_filter
+        .to_condition::<app_client_entity::Entity>()
+        .add(app_client_entity::Column::ApplicationId.eq(application_id));
+    // Get Models
+    let rep = AppClientRep::new().await;
+    let (app_client_models, limit, offset, total_count) = rep
+        .get_multiple(Some(filter), query_filter.offset, query_filter.limit)
+        .await
+        .unwrap();
+    app_client_schema::ApplicationClientList::from_models(
+        &app_client_models,
+        limit,
+        offset,
+        total_count,
+    )
+}
+
+pub async fn get(
+    application_id: Uuid,
+    client_id: Uuid,
+) -> Result<app_client_schema::ApplicationClient, ErrorGet> {
+    match get_model(application_id, client_id).await {
+        Some(v) => Ok(app_client_schema::ApplicationClient::from_model(&v)),
+        None => Err(ErrorGet::ClientNotFound),
+    }
+}
+
+pub async fn update(
+    updater_id: Uuid,
+    application_id: Uuid,
+    client_id: Uuid,
+    client: &app_client_schema::UpdateApplicationClient,
+) -> Result<app_client_schema::ApplicationClient, ErrorUpdate> {
+    // Try to get client
+    let app_client_model = match get_model(application_id, client_id).await {
+        Some(v) => v,
+        None => return Err(ErrorUpdate::ClientNotFound),
+    };
+    let permissions = application_schema::ApplicationPermissions::to_models(&client.permissions);
+    match check_permissions(updater_id, application_id, &permissions).await {
+        Err(ErrorCheckPermissions::Invalid) => return Err(ErrorUpdate::InvalidPermissions),
+        Err(ErrorCheckPermissions::Missing) => return Err(ErrorUpdate::MissingPermissions),
+        Ok(_) => (),
+    }
+    // Revoke issued tokens if the scope could be narrowed
+    if app_client_model.permissions != permissions {
+        del_tokens(client_id).await;
+    }
+
+    // Convert client model into active model
+    let mut app_client_model: app_client_entity::ActiveModel = app_client_model.into();
+    app_client_model.name = Set(client.name.to_owned());
+    app_client_model.permissions = Set(permissions);
+
+    // Convert Model into Schema
+    let rep = AppClientRep::new().await;
+    let app_client_model = rep.update(app_client_model).await.unwrap();
+    Ok(app_client_schema::ApplicationClient::from_model(
+        &app_client_model,
+    ))
+}
+
+/// Replaces the secret of the client and revokes the tokens issued with the old one.
+pub async fn rotate_secret(
+    application_id: Uuid,
+    client_id: Uuid,
+) -> Result<app_client_schema::ApplicationClientSecret, ErrorRotateSecret> {
+    // Try to get client
+    let app_client_model = match get_model(application_id, client_id).await {
+        Some(v) => v,
+        None => return Err(ErrorRotateSecret::ClientNotFound),
+    };
+
+    // Save new secret (the secret is hashed on save)
+    let secret = app_client_entity::Model::gen_secret();
+    let mut app_client_model: app_client_entity::ActiveModel = app_client_model.into();
+    app_client_model.secret = Set(secret.to_owned());
+    let rep = AppClientRep::new().await;
+    let app_client_model = rep.update(app_client_model).await.unwrap();
+    del_tokens(client_id).await;
+    Ok(app_client_schema::ApplicationClientSecret::from_model(
+        &app_client_model,
+        secret,
+    ))
+}
+
+pub async fn delete(application_id: Uuid, client_id: Uuid) -> Result<(), ErrorDelete> {
+    if get_model(application_id, client_id).await.is_none() {
+        return Err(ErrorDelete::ClientNotFound);
+    }
+    let rep = AppClientRep::new().await;
+    rep.delete_by_id(client_id).await.unwrap();
+    del_tokens(client_id).await;
+    Ok(())
+}
+
+/// Issues an access token of the `client_credentials` grant.
+///
+/// Without a requested scope the token gets every permission of the client.
+pub async fn issue_token(
+    client_id: &str,
+    client_secret: &str,
+    scope: Option<&String>,
+) -> Result<auth_jwt::Oauth2LoginResult, ErrorIssueToken> {
+    // Try to get client and check its secret
+    let client_id = match Uuid::parse_str(client_id) {
+        Ok(v) => v,
+        Err(_) => return Err(ErrorIssueToken::InvalidClient),
+    };
+    let rep = AppClientRep::new().await;
+    let app_client_model = match rep.get_by_id(client_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorIssueToken::InvalidClient),
+    };
+    if !app_client_model.is_valid_secret(client_secret) {
+        return Err(ErrorIssueToken::InvalidClient);
+    }
+    // Check application of the client is not deleted
+    if application_usecase::get(app_client_model.application_id)
+        .await
+        .is_err()
+    {
+        return Err(ErrorIssueToken::InvalidClient);
+    }
+
+    // Get requested scope (must be held by the client)
+    let permissions = match scope {
+        Some(v) => {
+            let mut permissions = Vec::<AppStaffPermissions>::new();
+            for perm_str in v.split_whitespace() {
+                match AppStaffPermissions::from_str(perm_str) {
+                    Ok(perm) if app_client_model.permissions.contains(&perm) => {
+                        if !permissions.contains(&perm) {
+                            permissions.push(perm);
+                        }
+                    }
+                    _ => return Err(ErrorIssueToken::InvalidScope),
+                }
+            }
+            permissions
+        }
+        None => app_client_model.permissions.to_owned(),
+    };
+
+    // Save claims in cache, convert into jwt and return
+    let claims = app_client_schema::AppClientTokenClaims::new(
+        &app_client_model,
+        application_schema::ApplicationPermissions::from_models(&permissions),
+    );
+    save_token(&claims).await;
+    Ok(auth_jwt::Oauth2LoginResult {
+        access_token: jwt_encode(&claims).unwrap(),
+        refresh_token: None,
+        scope: Some(claims.get_scope_str()),
+        id_token: None,
+        token_type: auth::TokenType::Bearer,
+        expires_in: claims.oauth2_claims.get_life_sec(),
+    })
+}
+
+/// Returns the introspection of a client token, inactive if the token is revoked.
+pub async fn introspect(
+    token_claims: &app_client_schema::AppClientTokenClaims,
+) -> auth_jwt::IntrospectResult {
+    if token_claims.oauth2_claims.validate_date_range().is_err()
+        || !token_is_exist(token_claims).await
+    {
+        return auth_jwt::IntrospectResult::inactive();
+    }
+    let mut result = auth_jwt::IntrospectResult::from_claims(
+        &token_claims.oauth2_claims,
+        token_claims.client_id.to_string(),
+    );
+    result.client_id = Some(token_claims.client_id.to_string());
+    result.scope = Some(token_claims.get_scope_str());
+    result
+}
+
+pub async fn save_token(token_claims: &app_client_schema::AppClientTokenClaims) {
+    redis_repository::set(
+        token_claims.get_key_for_cache(),
+        token_claims.application_id.to_string(),
+        Some(token_claims.oauth2_claims.get_remaining_life_sec().max(1)),
+    )
+    .await;
+}
+
+pub async fn token_is_exist(token_claims: &app_client_schema::AppClientTokenClaims) -> bool {
+    redis_repository::exist(token_claims.get_key_for_cache()).await
+}
+
+pub async fn del_token(token_claims: &app_client_schema::AppClientTokenClaims) {
+    redis_repository::del(token_claims.get_key_for_cache()).await;
+}
+
+/// Revokes every token issued to the client.
+pub async fn del_tokens(client_id: Uuid) {
+    redis_repository::del_keys(app_client_schema::get_prefix_key_for_cache(
+        client_id.to_string(),
+    ))
+    .await;
+}
+
+enum ErrorCheckPermissions {
+    Invalid,
+    Missing,
+}
+
+/// Checks the permissions can be given to a client by the staff user.
+///
+/// A client may only hold key permissions, and only the ones the staff user holds.
+async fn check_permissions(
+    user_id: Uuid,
+    application_id: Uuid,
+    permissions: &[AppStaffPermissions],
+) -> Result<(), ErrorCheckPermissions> {
+    let key_permissions = AppStaffPermissions::get_all_key();
+    if !permissions.iter().all(|v| key_permissions.contains(v)) {
+        return Err(ErrorCheckPermissions::Invalid);
+    }
+    let user_permissions = application_usecase::get_permissions(user_id, application_id)
+        .await
+        .unwrap_or_default();
+    if !permissions.iter().all(|v| user_permissions.contains(v)) {
+        return Err(ErrorCheckPermissions::Missing);
+    }
+    Ok(())
+}
+
+async fn get_model(application_id: Uuid, client_id: Uuid) -> Option<app_client_entity::Model> {
+    let filter = Condition::all()
+        .add(app_client_entity::Column::Id.eq(client_id))
+        .add(app_client_entity::Column::ApplicationId.eq(application_id));
+    let rep = AppClientRep::new().await;
+    rep.get_one(Some(filter)).await.unwrap()
+}
@@ -0,0 +1,151 @@
+use super::application as application_usecase;
+use crate::{query::application as application_query, schema::application as application_schema};
+use orm_util_lib::prelude::EntityFilterableTrait;
+use repository_db_lib::{
+    app_staff::{app_staff_entity, AppStaff as AppStaffRep},
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use sea_orm::{ColumnTrait, Condition, Set};
+use uuid::Uuid;
+
+pub enum ErrorCreate {
+    UserNotFound,
+    StaffAllreadyExist,
+    AddStaffFailed,
+}
+
+pub enum ErrorUpdate {
+    StaffNotFound,
+    LastFullPermissionsStaff,
+}
+
+pub enum ErrorDelete {
+    StaffNotFound,
+    LastFullPermissionsStaff,
+}
+
+pub async fn get_all(
+    application_id: Uuid,
+    query_filter: &application_query::ApplicationStaff,
+) -> application_schema::ApplicationStaffList {
+    // Get filter
+    let filter = query_filter
+        .to_condition::<app_staff_entity::Entity>()
+        .add(app_staff_entity::Column::ApplicationId.eq(application_id));
+    // Get Models
+    let rep = AppStaffRep::new().await;
+    let (app_staff_models, limit, offset, total_count) = rep
+        .get_multiple(Some(filter), query_filter.offset, query_filter.limit)
+        .await
+        .unwrap();
+    application_schema::ApplicationStaffList::from_models(
+        &app_staff_models,
+        limit,
+        offset,
+        total_count,
+    )
+}
+
+pub async fn create(
+    application_id: Uuid,
+    new_staff: &application_schema::CreateApplicationStaff,
+) -> Result<application_schema::ApplicationStaff, ErrorCreate> {
+    // Check new staff is an existing staff user
+    let filter = Condition::all()
+        .add(user_entity::Column::Id.eq(new_staff.user_id))
+        .add(user_entity::Column::IsStaff.eq(true));
+    let user_rep = UserRep::new().await;
+    if !user_rep.is_exist(Some(filter)).await.unwrap() {
+        return Err(ErrorCreate::UserNotFound);
+    }
+    if get_model(application_id, new_staff.user_id).await.is_some() {
+        return Err(ErrorCreate::StaffAllreadyExist);
+    }
+
+    match application_usecase::add_staff(
+        None,
+        application_id,
+        new_staff.user_id,
+        application_schema::ApplicationPermissions::to_models(&new_staff.permissions),
+    )
+    .await
+    {
+        Ok(v) => Ok(v),
+        Err(e) => match e {
+            application_usecase::ErrorAddStaff::ErrorCreate => Err(ErrorCreate::AddStaffFailed),
+        },
+    }
+}
+
+pub async fn update(
+    application_id: Uuid,
+    staff_user_id: Uuid,
+    staff: &application_schema::UpdateApplicationStaff,
+) -> Result<application_schema::ApplicationStaff, ErrorUpdate> {
+    // Try to get staff
+    let app_staff_model = match get_model(application_id, staff_user_id).await {
+        Some(v) => v,
+        None => return Err(ErrorUpdate::StaffNotFound),
+    };
+
+    // Application must keep at least one staff with all permissions
+    let permissions = application_schema::ApplicationPermissions::to_models(&staff.permissions);
+    if !app_staff_entity::AppStaffPermissions::is_all(&permissions)
+        && !has_other_full_permissions_staff(application_id, staff_user_id).await
+    {
+        return Err(ErrorUpdate::LastFullPermissionsStaff);
+    }
+
+    // Convert app staff model into active model
+    let mut app_staff_model: app_staff_entity::ActiveModel = app_staff_model.into();
+    app_staff_model.permissions = Set(permissions);
+
+    // Convert Model into Schema
+    let rep = AppStaffRep::new().await;
+    let app_staff_model = rep.update(app_staff_model).await.unwrap();
+    Ok(application_schema::ApplicationStaff::from_model(
+        &app_staff_model,
+    ))
+}
+
+pub async fn delete(application_id: Uuid, staff_user_id: Uuid) -> Result<(), ErrorDelete> {
+    // Try to get staff
+    let app_staff_model = match get_model(application_id, staff_user_id).await {
+        Some(v) => v,
+        None => return Err(ErrorDelete::StaffNotFound),
+    };
+
+    // Application must keep at least one staff with all permissions
+    if app_staff_entity::AppStaffPermissions::is_all(&app_staff_model.permissions)
+        && !has_other_full_permissions_staff(application_id, staff_user_id).await
+    {
+        return Err(ErrorDelete::LastFullPermissionsStaff);
+    }
+
+    let rep = AppStaffRep::new().await;
+    rep.delete_by_id(app_staff_model.id).await.unwrap();
+    Ok(())
+}
+
+async fn get_model(application_id: Uuid, user_id: Uuid) -> Option<app_staff_entity::Model> {
+    let filter = Condition::all()
+        .add(app_staff_entity::Column::ApplicationId.eq(application_id))
+        .add(app_staff_entity::Column::UserId.eq(user_id));
+    let rep = AppStaffRep::new().await;
+    rep.get_one(Some(filter)).await.unwrap()
+}
+
+async fn has_other_full_permissions_staff(application_id: Uuid, excluded_user_id: Uuid) -> bool {
+    let filter = Condition::all()
+        .add(app_staff_entity::Column::ApplicationId.eq(application_id))
+        .add(app_staff_entity::Column::UserId.ne(excluded_user_id));
+    let rep = AppStaffRep::new().await;
+    let (app_staff_models, _, _, _) = rep
+        .get_multiple(Some(filter), None, Some(-1))
+        .await
+        .unwrap();
+    app_staff_models
+        .iter()
+        .any(|model| app_staff_entity::AppStaffPermissions::is_all(&model.permissions))
+}
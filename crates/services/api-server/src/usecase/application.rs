@@ -7,23 +7,50 @@ use orm_util_lib::prelude::EntityFilterableTrait;
 use repository_db_lib::{
     app_staff::{app_staff_entity, AppStaff as AppStaffRep},
     application::{application_entity, Application as ApplicationRep},
-    Repository,
+    Repository, SoftDelete, UnitOfWork,
 };
-use sea_orm::{ColumnTrait, Condition, Set};
+use sea_orm::{prelude::Expr, sea_query::extension::postgres::PgFunc, ColumnTrait, Condition, Set};
+use url::Url;
+use util_lib::string::validate::is_str_1_2048;
 use uuid::Uuid;
 
 pub enum ErrorCreate {
     ApplicationNameAllreadyExist,
+    InvalidRedirectUri,
     AddCreatorIntoNewApplication,
 }
 
+pub enum ErrorGet {
+    ApplicationNotFound,
+}
+
+pub enum ErrorUpdate {
+    ApplicationNotFound,
+    ApplicationNameAllreadyExist,
+    InvalidRedirectUri,
+}
+
+pub enum ErrorDelete {
+    ApplicationNotFound,
+}
+
+pub enum ErrorRestore {
+    ApplicationNotFound,
+}
+
 pub async fn create(
     creator: &user_guard::User,
     new_application: &application_schema::CreateApplication,
 ) -> Result<application_schema::Application, ErrorCreate> {
-    let rep = ApplicationRep::new().await;
+    if !new_application.redirect_uris.iter().all(|v| is_valid_redirect_uri(v)) {
+        return Err(ErrorCreate::InvalidRedirectUri);
+    }
+
+    // Application and its creator staff are saved in one transaction
+    let uow = UnitOfWork::begin().await.unwrap();
+    let rep = ApplicationRep::new().await.with_deleted().with_txn(&uow);
 
-    // Check if application by name allready exist
+    // Check if application by name (deleted included) allready exist
     let filter =
         Condition::all().add(application_entity::Column::Name.eq(new_application.name.to_owned()));
     if let Some(_) = rep.get_one(Some(filter)).await.unwrap() {
@@ -34,11 +61,13 @@ pub async fn create(
     let application_model = application_entity::ActiveModel {
         name: Set(new_application.name.to_owned()),
         description: Set(new_application.description.to_owned()),
+        redirect_uris: Set(new_application.redirect_uris.to_owned()),
         ..Default::default()
     };
     let application_model = rep.create(application_model).await.unwrap();
 
     if add_staff(
+        Some(&uow),
         application_model.id.to_owned(),
         creator.claims.id.to_owned(),
         app_staff_entity::AppStaffPermissions::get_all(),
@@ -46,9 +75,10 @@ pub async fn create(
     .await
     .is_err()
     {
-        let _ = rep.delete_by_id(application_model.id).await;
+        let _ = uow.rollback().await;
         return Err(ErrorCreate::AddCreatorIntoNewApplication);
     }
+    uow.commit().await.unwrap();
 
     Ok(application_schema::Application::from_model(
         &application_model,
@@ -64,8 +94,12 @@ pub async fn get_all(
         .to_condition::<application_entity::Entity>()
         .add(app_staff_entity::Column::UserId.eq(user.claims.id))
         .add(
-            app_staff_entity::Column::Permissions
-                .contains(app_staff_entity::AppStaffPermissions::ReadApplication.to_string()),
+            Expr::val(app_staff_entity::AppStaffPermissions::ReadApplication.to_string()).eq(
+                PgFunc::any(Expr::col((
+                    app_staff_entity::Entity,
+                    app_staff_entity::Column::Permissions,
+                ))),
+            ),
         );
     let rep = ApplicationRep::new().await;
 
@@ -78,16 +112,111 @@ pub async fn get_all(
     application_schema::ApplicationList::from_models(&app_models, limit, offset, total_count)
 }
 
+pub async fn get(application_id: Uuid) -> Result<application_schema::Application, ErrorGet> {
+    match get_model(application_id).await {
+        Some(v) => Ok(application_schema::Application::from_model(&v)),
+        None => Err(ErrorGet::ApplicationNotFound),
+    }
+}
+
+pub async fn update(
+    application_id: Uuid,
+    application: &application_schema::UpdateApplication,
+) -> Result<application_schema::Application, ErrorUpdate> {
+    if !application.redirect_uris.iter().all(|v| is_valid_redirect_uri(v)) {
+        return Err(ErrorUpdate::InvalidRedirectUri);
+    }
+
+    // Try to get application by id
+    let application_model = match get_model(application_id).await {
+        Some(v) => v,
+        None => return Err(ErrorUpdate::ApplicationNotFound),
+    };
+
+    // Check if another application (deleted included) allready use the name
+    let rep = ApplicationRep::new().await.with_deleted();
+    let filter = Condition::all()
+        .add(application_entity::Column::Name.eq(application.name.to_owned()))
+        .add(application_entity::Column::Id.ne(application_id));
+    if rep.is_exist(Some(filter)).await.unwrap() {
+        return Err(ErrorUpdate::ApplicationNameAllreadyExist);
+    }
+
+    // Convert application model into active model
+    let mut application_model: application_entity::ActiveModel = application_model.into();
+    application_model.name = Set(application.name.to_owned());
+    application_model.description = Set(application.description.to_owned());
+    application_model.redirect_uris = Set(application.redirect_uris.to_owned());
+
+    // Convert Model into Schema
+    let application_model = rep.update(application_model).await.unwrap();
+    Ok(application_schema::Application::from_model(
+        &application_model,
+    ))
+}
+
+pub async fn delete(application_id: Uuid) -> Result<(), ErrorDelete> {
+    if get_model(application_id).await.is_none() {
+        return Err(ErrorDelete::ApplicationNotFound);
+    }
+    let rep = ApplicationRep::new().await;
+    rep.delete_by_id(application_id).await.unwrap();
+    Ok(())
+}
+
+pub async fn restore(
+    application_id: Uuid,
+) -> Result<application_schema::Application, ErrorRestore> {
+    // Only deleted application can be restored
+    let rep = ApplicationRep::new().await.only_deleted();
+    let mut application_model = match rep.get_by_id(application_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorRestore::ApplicationNotFound),
+    };
+
+    let filter = Condition::all().add(application_entity::Column::Id.eq(application_id));
+    rep.restore(filter).await.unwrap();
+
+    application_model.is_deleted = false;
+    Ok(application_schema::Application::from_model(
+        &application_model,
+    ))
+}
+
+/// Returns the permissions of the user on the application, `None` if the application doesn't
+/// exist.
+pub async fn get_permissions(
+    user_id: Uuid,
+    application_id: Uuid,
+) -> Option<Vec<app_staff_entity::AppStaffPermissions>> {
+    // Check application exist
+    get_model(application_id).await?;
+
+    // Get user permissions on application
+    let app_staff_rep = AppStaffRep::new().await;
+    Some(
+        app_staff_rep
+            .get_permissions(application_id, user_id)
+            .await
+            .unwrap()
+            .unwrap_or_default(),
+    )
+}
+
 pub enum ErrorAddStaff {
     ErrorCreate,
 }
 
-async fn add_staff(
+pub async fn add_staff(
+    uow: Option<&UnitOfWork>,
     application_id: Uuid,
     user_id: Uuid,
     permissions: Vec<app_staff_entity::AppStaffPermissions>,
 ) -> Result<application_schema::ApplicationStaff, ErrorAddStaff> {
-    let app_staff_rep = AppStaffRep::new().await;
+    let mut app_staff_rep = AppStaffRep::new().await;
+    if let Some(uow) = uow {
+        app_staff_rep = app_staff_rep.with_txn(uow);
+    }
     let app_staff_model = app_staff_entity::ActiveModel {
         application_id: Set(application_id),
         user_id: Set(user_id),
@@ -104,3 +233,19 @@ async fn add_staff(
         &app_staff_model,
     ))
 }
+
+/// Checks the redirect URI is an absolute `http(s)` URI without fragment (RFC 6749 3.1.2).
+fn is_valid_redirect_uri(redirect_uri: &str) -> bool {
+    if !is_str_1_2048(redirect_uri) {
+        return false;
+    }
+    match Url::parse(redirect_uri) {
+        Ok(v) => matches!(v.scheme(), "http" | "https") && v.fragment().is_none(),
+        Err(_) => false,
+    }
+}
+
+async fn get_model(application_id: Uuid) -> Option<application_entity::Model> {
+    let rep = ApplicationRep::new().await;
+    rep.get_by_id(application_id).await.unwrap()
+}
@@ -1,53 +1,345 @@
-use super::user as user_usecase;
-use crate::schema::{auth as auth_schema, user as user_schema};
+use super::{
+    app_client as app_client_usecase, email_verification as email_verification_usecase,
+    login_throttle as login_throttle_usecase, mfa as mfa_usecase, oauth as oauth_usecase,
+    user as user_usecase,
+};
+use crate::{
+    guard::client::Client,
+    schema::{app_client as app_client_schema, auth as auth_schema, user as user_schema},
+    settings::SETTINGS,
+};
+use once_cell::sync::Lazy;
 use repository_db_lib::user::{user_entity, Repository, User as UserRep};
 use repository_redis_lib as redis_repository;
-use sea_orm::{ColumnTrait, Condition};
+use time::{Duration, OffsetDateTime};
 use util_lib::{
     auth::{self, jwt as auth_jwt},
+    crypto,
     jwt::encode as jwt_encode,
 };
+use uuid::Uuid;
 
 pub enum ErrorLogin {
-    UserNotFound,
-    InvalidPassword,
+    InvalidCredentials,
+    TooManyAttempts { retry_after: u64 },
+    EmailNotVerified,
+}
+
+pub enum ErrorLoginMfa {
+    InvalidMfaToken,
+    InvalidCode,
+    TooManyAttempts { retry_after: u64 },
 }
 
 pub enum ErrorRegister {
     EmailAllreadyExist,
 }
 
+pub enum ErrorToken {
+    MissingRefreshToken,
+    InvalidGrant,
+    RefreshTokenReused,
+    InvalidClient,
+    InvalidScope,
+    MissingAuthorizationCode,
+}
+
+/// Minimal delay between two updates of the last use of a session.
+const SESSION_TOUCH_INTERVAL_SEC: i64 = 60;
+/// Lifetime of the token version of a user in the cache, it is read again from the database after
+/// it.
+const TOKEN_VERSION_CACHE_SEC: u64 = 86400;
+
+/// Hash checked when the email doesn't exist, so the response time doesn't reveal it.
+static DUMMY_PASSWORD_HASH: Lazy<String> =
+    Lazy::new(|| crypto::get_default_hasher().hash(&user_entity::Model::gen_password()));
+
+/// Checks the credentials of the user and starts a new session.
+///
+/// A user with MFA enabled gets a challenge instead of the tokens, exchanged with `login_mfa`.
+/// Failed logins are throttled per email and per client IP, a wrong email and a wrong password
+/// give the same error.
 pub async fn login(
     user_login: &auth_schema::Login,
-) -> Result<auth_jwt::Oauth2LoginResult, ErrorLogin> {
+    client: &Client,
+) -> Result<auth_schema::LoginResult, ErrorLogin> {
+    // Refuse the login during the backoff or the lockout of the email or of the client
+    if let Some(retry_after) =
+        login_throttle_usecase::get_retry_after(&user_login.email, client.ip.as_ref()).await
+    {
+        return Err(ErrorLogin::TooManyAttempts { retry_after });
+    }
     // Get filter
-    let filter = Condition::all().add(user_entity::Column::Email.eq(user_login.email.to_owned()));
-    // Try to get User
+    let filter = UserRep::get_email_filter(&user_login.email);
+    // Try to get User and check its password
     let rep = UserRep::new().await;
-    let user: user_entity::Model;
-    match rep.get_one(Some(filter)).await.unwrap() {
-        Some(v) => user = v,
+    let user = match rep.get_one(Some(filter)).await.unwrap() {
+        Some(v) if v.is_valid_password(user_login.password.as_str()) => Some(v),
+        Some(_) => None,
         None => {
-            return Err(ErrorLogin::UserNotFound);
+            let _ = crypto::verify(user_login.password.as_str(), &DUMMY_PASSWORD_HASH);
+            None
         }
+    };
+    let user = match user {
+        Some(v) => v,
+        None => {
+            login_throttle_usecase::fail(&user_login.email, client.ip.as_ref()).await;
+            return Err(ErrorLogin::InvalidCredentials);
+        }
+    };
+    // Replace a hash of an outdated algorithm or parameters, the password is known only now
+    if user.password_needs_rehash() {
+        rep.update_password_hash(
+            user.id,
+            crypto::get_default_hasher().hash(user_login.password.as_str()),
+        )
+        .await
+        .unwrap();
+    }
+    // Told only with the right password, so it doesn't reveal the email
+    let verification_required: bool = SETTINGS.email_verification.required_for_login.into();
+    if verification_required && !user.is_email_verified() {
+        return Err(ErrorLogin::EmailNotVerified);
     }
-    // Check User password
-    if !user.is_valid_password(user_login.password.as_str()) {
-        return Err(ErrorLogin::InvalidPassword);
+    // The failed logins are forgotten only once the second factor is checked too
+    if user.is_mfa_enabled() {
+        return Ok(auth_schema::LoginResult::MfaChallenge(
+            mfa_usecase::create_challenge(user.id).await,
+        ));
     }
+    login_throttle_usecase::succeed(&user_login.email).await;
+    Ok(auth_schema::LoginResult::Token(
+        issue_tokens(&user, client, false).await,
+    ))
+}
+
+/// Exchanges the MFA challenge of the login and a TOTP or recovery code for a new session.
+pub async fn login_mfa(
+    login_mfa: &auth_schema::LoginMfa,
+    client: &Client,
+) -> Result<auth_jwt::Oauth2LoginResult, ErrorLoginMfa> {
+    // Try to get challenge and its User
+    let challenge = match mfa_usecase::get_challenge(&login_mfa.mfa_token).await {
+        Some(v) => v,
+        None => return Err(ErrorLoginMfa::InvalidMfaToken),
+    };
+    let rep = UserRep::new().await;
+    let user = match rep.get_by_id(challenge.user_id).await.unwrap() {
+        Some(v) if v.is_mfa_enabled() => v,
+        _ => {
+            mfa_usecase::del_challenge(&login_mfa.mfa_token).await;
+            return Err(ErrorLoginMfa::InvalidMfaToken);
+        }
+    };
+    // Check code (the challenge is revoked after too many wrong codes, the wrong codes are
+    // throttled as failed logins so new challenges don't allow to guess codes)
+    if let Some(retry_after) =
+        login_throttle_usecase::get_retry_after(&user.email, client.ip.as_ref()).await
+    {
+        return Err(ErrorLoginMfa::TooManyAttempts { retry_after });
+    }
+    if !mfa_usecase::verify_code(&user, &login_mfa.code).await {
+        mfa_usecase::fail_challenge(&login_mfa.mfa_token, challenge).await;
+        login_throttle_usecase::fail(&user.email, client.ip.as_ref()).await;
+        return Err(ErrorLoginMfa::InvalidCode);
+    }
+    mfa_usecase::del_challenge(&login_mfa.mfa_token).await;
+    login_throttle_usecase::succeed(&user.email).await;
+    Ok(issue_tokens(&user, client, true).await)
+}
+
+/// Starts a new session of the user and returns its access and refresh tokens.
+///
+/// `mfa` tells the session was verified with a second factor.
+pub async fn issue_tokens(
+    user: &user_entity::Model,
+    client: &Client,
+    mfa: bool,
+) -> auth_jwt::Oauth2LoginResult {
     // Get access and refresh user claims
     let (access_user_claims, refresh_user_claims) =
-        auth_schema::SelfUserTokenClaims::access_and_refresh_from_model(&user);
-    // Save user claims in cache
-    save_token(&access_user_claims).await;
-    save_token(&refresh_user_claims).await;
-    // Convert claims into jwt and return
-    Ok(auth_jwt::Oauth2LoginResult {
-        access_token: jwt_encode(&access_user_claims).unwrap(),
-        refresh_token: jwt_encode(&refresh_user_claims).unwrap(),
+        auth_schema::SelfUserTokenClaims::access_and_refresh_from_model(user, mfa);
+    save_new_session(access_user_claims, refresh_user_claims, client).await
+}
+
+/// Starts a new session of the user for an application and returns its access and refresh
+/// tokens.
+///
+/// The tokens have the application as audience and the granted scopes, without the staff
+/// permissions of the user: they only give access to the claims of the user.
+pub async fn issue_application_tokens(
+    user: &user_entity::Model,
+    client: &Client,
+    mfa: bool,
+    application_id: Uuid,
+    scopes: &[String],
+) -> auth_jwt::Oauth2LoginResult {
+    let scope = Some(scopes.join(" "));
+    let (access_user_claims, refresh_user_claims) =
+        auth_schema::SelfUserTokenClaims::access_and_refresh_from_model(user, mfa);
+    save_new_session(
+        access_user_claims.for_application(application_id.to_string(), scope.to_owned()),
+        refresh_user_claims.for_application(application_id.to_string(), scope),
+        client,
+    )
+    .await
+}
+
+/// Saves the claims with a new session in cache and converts them into jwt.
+async fn save_new_session(
+    access_user_claims: auth_schema::SelfUserTokenClaims,
+    refresh_user_claims: auth_schema::SelfUserTokenClaims,
+    client: &Client,
+) -> auth_jwt::Oauth2LoginResult {
+    let session = auth_schema::SessionMeta::new(client.ip.to_owned(), client.user_agent.to_owned());
+    save_token(&access_user_claims, &session).await;
+    save_token(&refresh_user_claims, &session).await;
+    get_login_result(&access_user_claims, &refresh_user_claims)
+}
+
+fn get_login_result(
+    access_user_claims: &auth_schema::SelfUserTokenClaims,
+    refresh_user_claims: &auth_schema::SelfUserTokenClaims,
+) -> auth_jwt::Oauth2LoginResult {
+    auth_jwt::Oauth2LoginResult {
+        access_token: jwt_encode(access_user_claims).unwrap(),
+        refresh_token: Some(jwt_encode(refresh_user_claims).unwrap()),
+        scope: access_user_claims.scope.to_owned(),
+        id_token: None,
         token_type: auth::TokenType::Bearer,
         expires_in: access_user_claims.oauth2_claims.get_life_sec(),
-    })
+    }
+}
+
+pub async fn token(
+    token_input: &auth_jwt::TokenInput,
+    client: &Client,
+) -> Result<auth_jwt::Oauth2LoginResult, ErrorToken> {
+    match token_input.grant_type {
+        auth_jwt::Oauth2GrantType::RefreshToken => match &token_input.refresh_token {
+            Some(v) => refresh(v, client).await,
+            None => Err(ErrorToken::MissingRefreshToken),
+        },
+        auth_jwt::Oauth2GrantType::ClientCredentials => {
+            // Credentials of the body take precedence over the basic authorization header
+            let (client_id, client_secret) =
+                match (&token_input.client_id, &token_input.client_secret) {
+                    (Some(id), Some(secret)) => (id.to_owned(), secret.to_owned()),
+                    _ => match &client.credentials {
+                        Some(v) => v.to_owned(),
+                        None => return Err(ErrorToken::InvalidClient),
+                    },
+                };
+            match app_client_usecase::issue_token(
+                &client_id,
+                &client_secret,
+                token_input.scope.as_ref(),
+            )
+            .await
+            {
+                Ok(v) => Ok(v),
+                Err(e) => match e {
+                    app_client_usecase::ErrorIssueToken::InvalidClient => {
+                        Err(ErrorToken::InvalidClient)
+                    }
+                    app_client_usecase::ErrorIssueToken::InvalidScope => {
+                        Err(ErrorToken::InvalidScope)
+                    }
+                },
+            }
+        }
+        auth_jwt::Oauth2GrantType::AuthorizationCode => {
+            match oauth_usecase::exchange_code(token_input, client).await {
+                Ok(v) => Ok(v),
+                Err(e) => match e {
+                    oauth_usecase::ErrorExchangeCode::MissingParameter => {
+                        Err(ErrorToken::MissingAuthorizationCode)
+                    }
+                    oauth_usecase::ErrorExchangeCode::InvalidGrant => Err(ErrorToken::InvalidGrant),
+                },
+            }
+        }
+    }
+}
+
+async fn refresh(
+    refresh_token: &String,
+    client: &Client,
+) -> Result<auth_jwt::Oauth2LoginResult, ErrorToken> {
+    // Try to deserialize refresh claims from jwt
+    let refresh_claims = match auth_schema::SelfUserTokenClaims::from_jwt(refresh_token) {
+        Ok(v) => v,
+        Err(_) => return Err(ErrorToken::InvalidGrant),
+    };
+    if refresh_claims.oauth2_claims.is_access() {
+        return Err(ErrorToken::InvalidGrant);
+    }
+    if refresh_claims.oauth2_claims.validate_date_range().is_err() {
+        return Err(ErrorToken::InvalidGrant);
+    }
+    // Consume the refresh token at once (concurrent refreshes can't both rotate it), a missing
+    // token is revoked or was allready rotated: the second means the session was stolen
+    let cached_token = match redis_repository::get_del::<String>(refresh_claims.get_key_for_cache())
+        .await
+        .and_then(|v| serde_json::from_str::<auth_schema::CachedToken>(&v).ok())
+    {
+        Some(v) => v,
+        None => {
+            if redis_repository::exist(auth_schema::get_used_key_for_cache(
+                refresh_claims.id.to_string(),
+                refresh_claims.oauth2_claims.jti.to_string(),
+            ))
+            .await
+            {
+                del_session_tokens(&refresh_claims).await;
+                return Err(ErrorToken::RefreshTokenReused);
+            }
+            return Err(ErrorToken::InvalidGrant);
+        }
+    };
+    // Revoke the access token of the old pair and remember the refresh token as used
+    del_acc_ref_tokens(&refresh_claims).await;
+    redis_repository::set(
+        auth_schema::get_used_key_for_cache(
+            refresh_claims.id.to_string(),
+            refresh_claims.oauth2_claims.jti.to_string(),
+        ),
+        refresh_claims.oauth2_claims.sid.to_string(),
+        Some(refresh_claims.oauth2_claims.get_remaining_life_sec().max(1)),
+    )
+    .await;
+    // Get actual User (claims could be changed since last login)
+    let rep = UserRep::new().await;
+    let user = match rep.get_by_id(refresh_claims.id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorToken::InvalidGrant),
+    };
+    // Revoke the session if the access of the user changed since the issue
+    if user.token_version != refresh_claims.token_version {
+        del_session_tokens(&refresh_claims).await;
+        return Err(ErrorToken::InvalidGrant);
+    }
+    // Rotate access and refresh user claims in the same session (for the same application)
+    let (mut access_user_claims, mut refresh_user_claims) =
+        auth_schema::SelfUserTokenClaims::access_and_refresh_from_model_for_session(
+            &user,
+            refresh_claims.oauth2_claims.sid,
+            refresh_claims.mfa,
+        );
+    if let Some(aud) = &refresh_claims.aud {
+        access_user_claims =
+            access_user_claims.for_application(aud.to_owned(), refresh_claims.scope.to_owned());
+        refresh_user_claims =
+            refresh_user_claims.for_application(aud.to_owned(), refresh_claims.scope.to_owned());
+    }
+    let mut session = cached_token.session;
+    session.ip = client.ip.to_owned();
+    session.user_agent = client.user_agent.to_owned();
+    session.last_used_at = OffsetDateTime::now_utc();
+    save_token(&access_user_claims, &session).await;
+    save_token(&refresh_user_claims, &session).await;
+    Ok(get_login_result(&access_user_claims, &refresh_user_claims))
 }
 
 pub async fn logout(user_claims: &auth_schema::SelfUserTokenClaims) {
@@ -55,49 +347,86 @@ pub async fn logout(user_claims: &auth_schema::SelfUserTokenClaims) {
 }
 
 pub async fn introspect(token_intro: &auth_jwt::IntrospectInput) -> auth_jwt::IntrospectResult {
-    // Try to deserialize claims from jwt
-    let user_claims: auth_schema::SelfUserTokenClaims;
-    match auth_schema::SelfUserTokenClaims::from_jwt(&token_intro.token) {
-        Ok(v) => user_claims = v,
+    // Try to deserialize claims from jwt (the type hint is ignored, the type is in the claims)
+    let user_claims = match auth_schema::SelfUserTokenClaims::from_jwt(&token_intro.token) {
+        Ok(v) => v,
         Err(_) => {
-            return auth_jwt::IntrospectResult {
-                active: false,
-                ..Default::default()
-            };
+            return match app_client_schema::AppClientTokenClaims::from_jwt(&token_intro.token) {
+                Ok(v) => app_client_usecase::introspect(&v).await,
+                Err(_) => auth_jwt::IntrospectResult::inactive(),
+            }
         }
-    }
-    // Ceck match with token type (if token type was send)
-    if let Some(_) = &token_intro.token_type_hint {
-        if !(token_intro.is_access() == user_claims.oauth2_claims.is_access()) {
-            return auth_jwt::IntrospectResult {
-                active: false,
-                ..Default::default()
-            };
-        }
-    }
+    };
     // Check lifetime range
-    if let Err(_) = user_claims.oauth2_claims.validate_date_range() {
-        return auth_jwt::IntrospectResult {
-            active: false,
-            ..Default::default()
-        };
+    if user_claims.oauth2_claims.validate_date_range().is_err() {
+        return auth_jwt::IntrospectResult::inactive();
     }
-    // Check token not revoked (on exist)
+    // Check token not revoked (on exist and on user token version)
     if !token_is_exist(&user_claims).await {
-        return auth_jwt::IntrospectResult {
-            active: false,
-            ..Default::default()
-        };
+        return auth_jwt::IntrospectResult::inactive();
     }
-    // Return result
-    auth_jwt::IntrospectResult {
-        active: true,
-        ..Default::default()
+    let rep = UserRep::new().await;
+    let user = match rep.get_by_id(user_claims.id).await.unwrap() {
+        Some(v) if v.token_version == user_claims.token_version => v,
+        _ => return auth_jwt::IntrospectResult::inactive(),
+    };
+    // Return claims of the token
+    let mut result =
+        auth_jwt::IntrospectResult::from_claims(&user_claims.oauth2_claims, user.id.to_string());
+    result.username = Some(user.email);
+    result.aud = user_claims.aud.to_owned();
+    result.scope = match user_claims.is_application_token() {
+        true => user_claims.scope,
+        false => user_claims.permissions.map(|permissions| {
+            permissions
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        }),
+    };
+    result
+}
+
+/// Revokes the token and its paired access or refresh token, if the token was issued to the
+/// client.
+///
+/// The tokens of a user are issued to the application of their audience, the sessions of the
+/// service itself are revoked with `/logout`. The tokens of an application client are issued to
+/// the client.
+///
+/// Invalid, expired, already revoked tokens and the tokens of another client are ignored, so the
+/// caller can't learn whether a token exists (RFC 7009, section 2.1). The type hint is not needed:
+/// the tokens are self-contained and their claims tell their type.
+pub async fn revoke(client_id: &str, token_revoke: &auth_jwt::RevokeInput) {
+    if let Ok(user_claims) = auth_schema::SelfUserTokenClaims::from_jwt(&token_revoke.token) {
+        if user_claims.aud.as_deref() == Some(client_id) {
+            del_acc_ref_tokens(&user_claims).await;
+        }
+    } else if let Ok(client_claims) =
+        app_client_schema::AppClientTokenClaims::from_jwt(&token_revoke.token)
+    {
+        if client_claims.client_id.to_string() == client_id {
+            app_client_usecase::del_token(&client_claims).await;
+        }
     }
 }
 
+/// Checks the credentials of a client allowed to introspect tokens (`INTROSPECTION_CLIENTS`).
+pub async fn authenticate_client(client_id: &str, client_secret: &str) -> bool {
+    SETTINGS
+        .introspection
+        .clients
+        .split(',')
+        .filter_map(|v| v.trim().split_once(':'))
+        .any(|(id, secret)| {
+            id == client_id && crypto::constant_time_eq(secret.as_bytes(), client_secret.as_bytes())
+        })
+}
+
 pub async fn registration(
     user_reg: &auth_schema::Register,
+    client: &Client,
 ) -> Result<user_schema::User, ErrorRegister> {
     match user_usecase::create(
         &user_schema::CreateUser {
@@ -110,18 +439,34 @@ pub async fn registration(
     )
     .await
     {
-        Ok(v) => Ok(v),
+        Ok(v) => {
+            email_verification_usecase::send_token(
+                v.id,
+                v.name.to_owned(),
+                v.email.to_owned(),
+                client.locale.to_owned(),
+            )
+            .await;
+            Ok(v)
+        }
         Err(e) => match e {
             user_usecase::ErrorCreate::EmailAllreadyExist => Err(ErrorRegister::EmailAllreadyExist),
         },
     }
 }
 
-pub async fn save_token(token_claims: &auth_schema::SelfUserTokenClaims) {
+pub async fn save_token(
+    token_claims: &auth_schema::SelfUserTokenClaims,
+    session: &auth_schema::SessionMeta,
+) {
+    let cached_token = auth_schema::CachedToken {
+        claims: token_claims.to_owned(),
+        session: session.to_owned(),
+    };
     redis_repository::set(
         token_claims.get_key_for_cache(),
-        serde_json::to_string(&token_claims).unwrap(),
-        Some(token_claims.oauth2_claims.get_life_sec()),
+        serde_json::to_string(&cached_token).unwrap(),
+        Some(token_claims.oauth2_claims.get_remaining_life_sec().max(1)),
     )
     .await;
 }
@@ -130,6 +475,108 @@ pub async fn token_is_exist(token_claims: &auth_schema::SelfUserTokenClaims) ->
     redis_repository::exist(token_claims.get_key_for_cache()).await
 }
 
+/// Returns the cached token matching the claims, `None` if the token is revoked.
+pub async fn get_cached_token(
+    token_claims: &auth_schema::SelfUserTokenClaims,
+) -> Option<auth_schema::CachedToken> {
+    get_cached_token_by_key(token_claims.get_key_for_cache()).await
+}
+
+/// Returns every cached (not revoked) token of the user with its cache key.
+pub async fn get_cached_tokens(user_id: Uuid) -> Vec<(String, auth_schema::CachedToken)> {
+    let mut cached_tokens = Vec::new();
+    for key in
+        redis_repository::get_keys(auth_schema::get_prefix_key_for_cache(user_id.to_string())).await
+    {
+        if let Some(v) = get_cached_token_by_key(key.to_owned()).await {
+            cached_tokens.push((key, v));
+        }
+    }
+    cached_tokens
+}
+
+/// Updates the last use of the session of the token.
+///
+/// The cache is written at most once per `SESSION_TOUCH_INTERVAL_SEC` for a token.
+pub async fn touch_session(
+    token_claims: &auth_schema::SelfUserTokenClaims,
+    cached_token: auth_schema::CachedToken,
+) {
+    let now = OffsetDateTime::now_utc();
+    if now - cached_token.session.last_used_at < Duration::seconds(SESSION_TOUCH_INTERVAL_SEC) {
+        return;
+    }
+    let mut session = cached_token.session;
+    session.last_used_at = now;
+    // Only an existing token is updated, a token revoked meanwhile stays revoked
+    let cached_token = auth_schema::CachedToken {
+        claims: token_claims.to_owned(),
+        session,
+    };
+    redis_repository::set_xx(
+        token_claims.get_key_for_cache(),
+        serde_json::to_string(&cached_token).unwrap(),
+    )
+    .await;
+}
+
+async fn get_cached_token_by_key(key: String) -> Option<auth_schema::CachedToken> {
+    match redis_repository::get::<String>(key).await {
+        Some(v) => serde_json::from_str(&v).ok(),
+        None => None,
+    }
+}
+
+/// Checks the token was issued with the actual token version of an existing user.
+///
+/// The version is bumped on every change of the password, staff flag, staff permissions, MFA or
+/// deleted flag of the user, revoking the tokens issued before. The cache holds the version
+/// written by [`update_user`] on every bump, it is read from the database only if missing.
+pub async fn token_version_is_actual(token_claims: &auth_schema::SelfUserTokenClaims) -> bool {
+    let key = auth_schema::get_token_version_key_for_cache(token_claims.id.to_string());
+    let cached_version = redis_repository::get::<i32>(key.to_owned()).await;
+    if let Some(v) = cached_version {
+        if v >= token_claims.token_version {
+            return v == token_claims.token_version;
+        }
+    }
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(token_claims.id).await.unwrap() {
+        Some(v) => v,
+        None => return false,
+    };
+    match cached_version {
+        // A bump written meanwhile is not overwritten by the version read before it
+        None => {
+            redis_repository::set_nx(key, user_model.token_version, TOKEN_VERSION_CACHE_SEC).await;
+        }
+        // The versions only grow, the cached one missed a bump
+        Some(_) => {
+            redis_repository::set(key, user_model.token_version, Some(TOKEN_VERSION_CACHE_SEC))
+                .await;
+        }
+    }
+    user_model.token_version == token_claims.token_version
+}
+
+/// Saves the user and writes its token version into the cache, the save bumps it if the access
+/// of the user changed.
+///
+/// Every save of the access of a user goes through it, so the tokens are revoked at once.
+pub async fn update_user(
+    rep: &UserRep,
+    user_model: user_entity::ActiveModel,
+) -> user_entity::Model {
+    let user_model = rep.update(user_model).await.unwrap();
+    redis_repository::set(
+        auth_schema::get_token_version_key_for_cache(user_model.id.to_string()),
+        user_model.token_version,
+        Some(TOKEN_VERSION_CACHE_SEC),
+    )
+    .await;
+    user_model
+}
+
 pub async fn del_acc_ref_tokens(token_claims: &auth_schema::SelfUserTokenClaims) {
     redis_repository::del(token_claims.get_key_for_cache()).await;
     redis_repository::del(auth_schema::get_key_for_cache(
@@ -138,3 +585,11 @@ pub async fn del_acc_ref_tokens(token_claims: &auth_schema::SelfUserTokenClaims)
     ))
     .await;
 }
+
+pub async fn del_session_tokens(token_claims: &auth_schema::SelfUserTokenClaims) {
+    for (key, cached_token) in get_cached_tokens(token_claims.id).await {
+        if cached_token.claims.oauth2_claims.sid == token_claims.oauth2_claims.sid {
+            redis_repository::del(key).await;
+        }
+    }
+}
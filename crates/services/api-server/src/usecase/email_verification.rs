@@ -0,0 +1,202 @@
+use super::{notification as notification_usecase, password_reset as password_reset_usecase};
+use crate::{
+    guard::client::Client,
+    schema::{email_verification as email_verification_schema, user as user_schema},
+    settings::SETTINGS,
+};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository, SoftDelete,
+};
+use repository_redis_lib as redis_repository;
+use sea_orm::{Set, SqlErr};
+use time::{Duration, OffsetDateTime};
+use util_lib::crypto;
+use uuid::Uuid;
+
+pub enum ErrorConfirm {
+    InvalidToken,
+    EmailAllreadyExist,
+}
+
+pub enum ErrorUpdateEmail {
+    UserNotFound,
+    WrongPassword,
+    EmailAllreadyExist,
+}
+
+/// Creates a verification token for the email of the user and hands it to the notification
+/// queue.
+///
+/// The previous token of the user is void, only the last email asked can be verified.
+pub async fn send_token(user_id: Uuid, name: String, email: String, locale: Option<String>) {
+    let settings = &SETTINGS.email_verification;
+
+    // Void the previous token of the user and save the new one in cache
+    let user_key = email_verification_schema::get_user_key_for_cache(user_id.to_string());
+    if let Some(v) = redis_repository::get_del::<String>(user_key.to_owned()).await {
+        redis_repository::del(email_verification_schema::get_key_for_cache(v)).await;
+    }
+    let token = crypto::gen_token(email_verification_schema::TOKEN_LEN);
+    let token_digest = crypto::get_token_digest(&token);
+    let verification_cache = email_verification_schema::EmailVerificationCache {
+        user_id,
+        email: email.to_owned(),
+    };
+    redis_repository::set(
+        email_verification_schema::get_key_for_cache(token_digest.to_owned()),
+        serde_json::to_string(&verification_cache).unwrap(),
+        Some(settings.token_life_sec),
+    )
+    .await;
+    redis_repository::set(user_key, token_digest, Some(settings.token_life_sec)).await;
+
+    // Send token to the email
+    let notification = email_verification_schema::EmailVerificationNotification {
+        user_id,
+        email: email.to_owned(),
+        name,
+        token,
+        expires_at: OffsetDateTime::now_utc() + Duration::seconds(settings.token_life_sec as i64),
+    };
+    notification_usecase::send(
+        email_verification_schema::NOTIFICATION_EVENT,
+        &email,
+        locale,
+        &notification,
+    )
+    .await;
+}
+
+/// Sends a new verification token to the email if it belongs to a user who didn't verify it.
+///
+/// The token is sent in the background, the caller returns at once whether the email exists or
+/// not, so neither the response nor its time reveal it.
+pub fn request(
+    verification_request: &email_verification_schema::EmailVerificationRequest,
+    client: &Client,
+) {
+    tokio::spawn(resend_token(
+        verification_request.email.to_owned(),
+        client.ip.to_owned(),
+        client.locale.to_owned(),
+    ));
+}
+
+/// Verifies the email of the token, a change of email is applied only now.
+///
+/// The token is looked up by its digest and removed on read: it is used once.
+pub async fn confirm(
+    verification_confirm: &email_verification_schema::EmailVerificationConfirm,
+) -> Result<user_schema::User, ErrorConfirm> {
+    // Get token from cache (removed on read)
+    let token_digest = crypto::get_token_digest(&verification_confirm.token);
+    let verification_cache = match redis_repository::get_del::<String>(
+        email_verification_schema::get_key_for_cache(token_digest),
+    )
+    .await
+    .and_then(|v| {
+        serde_json::from_str::<email_verification_schema::EmailVerificationCache>(&v).ok()
+    }) {
+        Some(v) => v,
+        None => return Err(ErrorConfirm::InvalidToken),
+    };
+    redis_repository::del(email_verification_schema::get_user_key_for_cache(
+        verification_cache.user_id.to_string(),
+    ))
+    .await;
+
+    // Try to get User
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(verification_cache.user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorConfirm::InvalidToken),
+    };
+
+    // Save verified email (the email may be taken since the request)
+    let is_email_changed = user_model.email != verification_cache.email;
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.email = Set(verification_cache.email);
+    user_model.email_verified_at = Set(Some(OffsetDateTime::now_utc()));
+    let result = rep.update(user_model).await;
+    if let Err(e) = &result {
+        if let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() {
+            return Err(ErrorConfirm::EmailAllreadyExist);
+        }
+    }
+    // A reset token sent to the previous email doesn't reset the password anymore
+    if is_email_changed {
+        password_reset_usecase::void_token(verification_cache.user_id).await;
+    }
+    Ok(user_schema::User::from_model(&result.unwrap()))
+}
+
+/// Sends a verification token to the new email of the user, the email is changed once the
+/// token is confirmed.
+pub async fn update_email(
+    user_id: Uuid,
+    update_email: &user_schema::UpdateUserEmail,
+    client: &Client,
+) -> Result<(), ErrorUpdateEmail> {
+    // Try to get user and check its password
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorUpdateEmail::UserNotFound),
+    };
+    if !user_model.is_valid_password(&update_email.password) {
+        return Err(ErrorUpdateEmail::WrongPassword);
+    }
+    // Check if email (deleted included) allready exist, the own email can change its case
+    let rep = UserRep::new().await.with_deleted();
+    if let Some(v) = rep
+        .get_one(Some(UserRep::get_email_filter(&update_email.email)))
+        .await
+        .unwrap()
+    {
+        if v.id != user_id {
+            return Err(ErrorUpdateEmail::EmailAllreadyExist);
+        }
+    }
+
+    send_token(
+        user_model.id,
+        user_model.name,
+        update_email.email.to_owned(),
+        client.locale.to_owned(),
+    )
+    .await;
+    Ok(())
+}
+
+/// Sends a new verification token to the user of the email, requests over the limits of the
+/// email or of the client IP are ignored.
+async fn resend_token(email: String, ip: Option<String>, locale: Option<String>) {
+    let settings = &SETTINGS.email_verification;
+    let mut subjects = vec![(email.trim().to_lowercase(), settings.email_max_requests)];
+    if let Some(v) = ip {
+        subjects.push((v, settings.ip_max_requests));
+    }
+    for (subject, max_requests) in subjects {
+        let requests = redis_repository::sliding_window_add(
+            email_verification_schema::get_requests_key_for_cache(&subject),
+            settings.window_sec,
+        )
+        .await;
+        if requests > max_requests {
+            return;
+        }
+    }
+
+    // Try to get User with an email to verify
+    let rep = UserRep::new().await;
+    let user_model = match rep
+        .get_one(Some(UserRep::get_email_filter(&email)))
+        .await
+        .unwrap()
+    {
+        Some(v) if !v.is_email_verified() => v,
+        _ => return,
+    };
+    send_token(user_model.id, user_model.name, user_model.email, locale).await;
+}
@@ -0,0 +1,214 @@
+use crate::{guard::client::Client, query::key as key_query, schema::key as key_schema};
+use orm_util_lib::prelude::EntityFilterableTrait;
+use repository_db_lib::{
+    key::{key_entity, Key as KeyRep},
+    user::User as UserRep,
+    Repository, SoftDelete,
+};
+use repository_redis_lib as redis_repository;
+use sea_orm::{ColumnTrait, Condition, Set};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub enum ErrorCreate {
+    UserNotFound,
+    InvalidLifetime,
+    ValueAllreadyExist,
+}
+
+pub enum ErrorGet {
+    KeyNotFound,
+}
+
+pub enum ErrorUpdate {
+    KeyNotFound,
+    UserNotFound,
+    InvalidLifetime,
+}
+
+pub enum ErrorDelete {
+    KeyNotFound,
+}
+
+pub enum ErrorVerify {
+    NotFound,
+    Bunned,
+    Expired,
+    TooManyAttempts { retry_after: u64 },
+}
+
+pub async fn create(
+    creator_id: Uuid,
+    application_id: Uuid,
+    new_key: &key_schema::CreateKey,
+) -> Result<key_schema::KeyDetail, ErrorCreate> {
+    if new_key.lifetime < 0 {
+        return Err(ErrorCreate::InvalidLifetime);
+    }
+    if !user_is_exist(new_key.user_id).await {
+        return Err(ErrorCreate::UserNotFound);
+    }
+
+    // Check if key value (deleted included) allready exist
+    let rep = KeyRep::new().await.with_deleted();
+    let filter = Condition::all().add(key_entity::Column::Value.eq(new_key.value.to_owned()));
+    if rep.is_exist(Some(filter)).await.unwrap() {
+        return Err(ErrorCreate::ValueAllreadyExist);
+    }
+
+    // Save new key
+    let key_model = key_entity::ActiveModel {
+        value: Set(new_key.value.to_owned()),
+        lifetime: Set(new_key.lifetime),
+        is_bunned: Set(new_key.is_bunned),
+        application_id: Set(application_id),
+        user_id: Set(new_key.user_id),
+        created_by_user_id: Set(creator_id),
+        ..Default::default()
+    };
+    let key_model = rep.create(key_model).await.unwrap();
+    Ok(key_schema::KeyDetail::from_model(&key_model))
+}
+
+pub async fn get_all(application_id: Uuid, query_filter: &key_query::Key) -> key_schema::KeyList {
+    // Get filter
+    let filter = query_filter
+        .to_condition::<key_entity::Entity>()
+        .add(key_entity::Column::ApplicationId.eq(application_id));
+    // Get Models
+    let rep = KeyRep::new().await;
+    let (key_models, limit, offset, total_count) = rep
+        .get_multiple(Some(filter), query_filter.offset, query_filter.limit)
+        .await
+        .unwrap();
+    key_schema::KeyList::from_models(&key_models, limit, offset, total_count)
+}
+
+pub async fn get(application_id: Uuid, key_id: Uuid) -> Result<key_schema::KeyDetail, ErrorGet> {
+    match get_model(application_id, key_id).await {
+        Some(v) => Ok(key_schema::KeyDetail::from_model(&v)),
+        None => Err(ErrorGet::KeyNotFound),
+    }
+}
+
+pub async fn update(
+    application_id: Uuid,
+    key_id: Uuid,
+    key: &key_schema::UpdateKey,
+) -> Result<key_schema::KeyDetail, ErrorUpdate> {
+    if key.lifetime < 0 {
+        return Err(ErrorUpdate::InvalidLifetime);
+    }
+
+    // Try to get key by id
+    let key_model = match get_model(application_id, key_id).await {
+        Some(v) => v,
+        None => return Err(ErrorUpdate::KeyNotFound),
+    };
+    if key_model.user_id != key.user_id && !user_is_exist(key.user_id).await {
+        return Err(ErrorUpdate::UserNotFound);
+    }
+
+    // Convert key model into active model
+    let mut key_model: key_entity::ActiveModel = key_model.into();
+    key_model.lifetime = Set(key.lifetime);
+    key_model.is_bunned = Set(key.is_bunned);
+    key_model.user_id = Set(key.user_id);
+
+    // Convert Model into Schema
+    let rep = KeyRep::new().await;
+    let key_model = rep.update(key_model).await.unwrap();
+    Ok(key_schema::KeyDetail::from_model(&key_model))
+}
+
+pub async fn delete(application_id: Uuid, key_id: Uuid) -> Result<(), ErrorDelete> {
+    if get_model(application_id, key_id).await.is_none() {
+        return Err(ErrorDelete::KeyNotFound);
+    }
+    let rep = KeyRep::new().await;
+    rep.delete_by_id(key_id).await.unwrap();
+    Ok(())
+}
+
+/// Verifies the key and activates it on its first use.
+///
+/// The unknown keys sent from the client IP are counted, its verifications are locked after too
+/// many of them so the values can't be guessed.
+pub async fn verify(
+    key: &key_schema::VerifyKey,
+    client: &Client,
+) -> Result<key_schema::KeyVerification, ErrorVerify> {
+    if let Some(retry_after) = get_verify_retry_after(client.ip.as_ref()).await {
+        return Err(ErrorVerify::TooManyAttempts { retry_after });
+    }
+    let rep = KeyRep::new().await;
+    let filter = Condition::all().add(key_entity::Column::Value.eq(key.value.to_owned()));
+
+    // Try to get key by value
+    let key_model = match rep.get_one(Some(filter)).await.unwrap() {
+        Some(v) => v,
+        None => {
+            count_verify_failure(client.ip.as_ref()).await;
+            return Err(ErrorVerify::NotFound);
+        }
+    };
+    if key_model.is_bunned {
+        return Err(ErrorVerify::Bunned);
+    }
+
+    // Activate key on first use, a concurrent verification may have activated it first
+    let now = OffsetDateTime::now_utc();
+    let key_model = match key_model.activated_at {
+        Some(_) => key_model,
+        None => match rep.activate(key_model.id, now).await.unwrap() {
+            true => key_entity::Model {
+                activated_at: Some(now),
+                updated_at: now,
+                ..key_model
+            },
+            false => rep.get_by_id(key_model.id).await.unwrap().unwrap(),
+        },
+    };
+    if !key_model.is_not_expired_at(now) {
+        return Err(ErrorVerify::Expired);
+    }
+
+    Ok(key_schema::KeyVerification::from_model(&key_model, now))
+}
+
+/// Returns the seconds before the next verification from the client IP if its verifications are
+/// locked.
+async fn get_verify_retry_after(ip: Option<&String>) -> Option<u64> {
+    let ip = ip?;
+    let failures = redis_repository::get::<u64>(key_schema::get_verify_failures_key_for_cache(ip))
+        .await
+        .unwrap_or(0);
+    match failures >= key_schema::VERIFY_MAX_FAILURES {
+        true => Some(key_schema::VERIFY_LOCK_SEC),
+        false => None,
+    }
+}
+
+/// Counts an unknown key sent from the client IP.
+async fn count_verify_failure(ip: Option<&String>) {
+    if let Some(ip) = ip {
+        redis_repository::incr(
+            key_schema::get_verify_failures_key_for_cache(ip),
+            key_schema::VERIFY_LOCK_SEC,
+        )
+        .await;
+    }
+}
+
+async fn get_model(application_id: Uuid, key_id: Uuid) -> Option<key_entity::Model> {
+    let filter = Condition::all()
+        .add(key_entity::Column::Id.eq(key_id))
+        .add(key_entity::Column::ApplicationId.eq(application_id));
+    let rep = KeyRep::new().await;
+    rep.get_one(Some(filter)).await.unwrap()
+}
+
+async fn user_is_exist(user_id: Uuid) -> bool {
+    let rep = UserRep::new().await;
+    rep.get_by_id(user_id).await.unwrap().is_some()
+}
@@ -0,0 +1,179 @@
+use crate::{
+    query::login_lockout as login_lockout_query, schema::login_lockout as login_lockout_schema,
+    settings::SETTINGS,
+};
+use login_lockout_schema::LockoutKind;
+use repository_redis_lib as redis_repository;
+use time::{Duration, OffsetDateTime};
+
+pub enum ErrorClear {
+    LockoutNotFound,
+}
+
+/// Returns the seconds to wait before the next login of the email from the client IP, `None` if
+/// the login is allowed.
+///
+/// The login is refused while the email or the IP is locked out, and while the backoff of the
+/// last failed login of the email runs.
+pub async fn get_retry_after(email: &str, ip: Option<&String>) -> Option<u64> {
+    let now = OffsetDateTime::now_utc();
+    let mut retry_after: Option<u64> = None;
+    for (kind, subject) in get_subjects(email, ip) {
+        if let Some(v) = get_lockout(kind, &subject).await {
+            let wait_sec = (v.expires_at - now).whole_seconds().max(1) as u64;
+            retry_after = Some(retry_after.unwrap_or(0).max(wait_sec));
+        }
+    }
+    let backoff_key = login_lockout_schema::get_backoff_key_for_cache(&normalize_email(email));
+    if let Some(v) = redis_repository::get::<i64>(backoff_key).await {
+        let wait_sec = v - now.unix_timestamp();
+        if wait_sec > 0 {
+            retry_after = Some(retry_after.unwrap_or(0).max(wait_sec as u64));
+        }
+    }
+    retry_after
+}
+
+/// Counts a failed login of the email from the client IP.
+///
+/// The email waits an exponential backoff before the next login, the email or the IP is locked
+/// out once its failed logins in the window reach the limit.
+pub async fn fail(email: &str, ip: Option<&String>) {
+    let settings = &SETTINGS.login_throttle;
+    for (kind, subject) in get_subjects(email, ip) {
+        let failures = redis_repository::sliding_window_add(
+            login_lockout_schema::get_failures_key_for_cache(kind, &subject),
+            settings.window_sec,
+        )
+        .await;
+        let max_attempts = match kind {
+            LockoutKind::Email => settings.email_max_attempts,
+            LockoutKind::Ip => settings.ip_max_attempts,
+        };
+        if failures >= max_attempts {
+            lock(kind, &subject, failures).await;
+        } else if kind == LockoutKind::Email {
+            let backoff_sec = get_backoff_sec(failures);
+            redis_repository::set(
+                login_lockout_schema::get_backoff_key_for_cache(&subject),
+                OffsetDateTime::now_utc().unix_timestamp() + backoff_sec as i64,
+                Some(backoff_sec),
+            )
+            .await;
+        }
+    }
+}
+
+/// Forgets the failed logins of the email after a successful login.
+///
+/// The failed logins of the IP are kept, a shared client must not reset them.
+pub async fn succeed(email: &str) {
+    clear_email_failures(&normalize_email(email)).await;
+}
+
+/// Returns every lockout in effect, the latest first.
+pub async fn get_all() -> login_lockout_schema::LoginLockoutList {
+    let mut lockouts = Vec::new();
+    for key in
+        redis_repository::get_keys(login_lockout_schema::get_lockout_prefix_key_for_cache()).await
+    {
+        if let Some(v) = get_lockout_by_key(key).await {
+            lockouts.push(v);
+        }
+    }
+    lockouts.sort_by_key(|v| std::cmp::Reverse(v.locked_at));
+    login_lockout_schema::LoginLockoutList { lockouts }
+}
+
+/// Lifts the lockout and forgets the failed logins counted for it.
+pub async fn clear(req_query: &login_lockout_query::LoginLockout) -> Result<(), ErrorClear> {
+    let subject = match req_query.kind {
+        LockoutKind::Email => normalize_email(&req_query.subject),
+        LockoutKind::Ip => req_query.subject.trim().to_string(),
+    };
+    if get_lockout(req_query.kind, &subject).await.is_none() {
+        return Err(ErrorClear::LockoutNotFound);
+    }
+    redis_repository::del(login_lockout_schema::get_lockout_key_for_cache(
+        req_query.kind,
+        &subject,
+    ))
+    .await;
+    match req_query.kind {
+        LockoutKind::Email => clear_email_failures(&subject).await,
+        LockoutKind::Ip => {
+            redis_repository::del(login_lockout_schema::get_failures_key_for_cache(
+                LockoutKind::Ip,
+                &subject,
+            ))
+            .await
+        }
+    }
+    Ok(())
+}
+
+async fn lock(kind: LockoutKind, subject: &str, failures: u64) {
+    let lockout_sec = SETTINGS.login_throttle.lockout_sec;
+    let now = OffsetDateTime::now_utc();
+    let lockout = login_lockout_schema::LoginLockout {
+        kind,
+        subject: subject.to_owned(),
+        failures,
+        locked_at: now,
+        expires_at: now + Duration::seconds(lockout_sec as i64),
+    };
+    redis_repository::set(
+        login_lockout_schema::get_lockout_key_for_cache(kind, subject),
+        serde_json::to_string(&lockout).unwrap(),
+        Some(lockout_sec),
+    )
+    .await;
+}
+
+async fn get_lockout(
+    kind: LockoutKind,
+    subject: &str,
+) -> Option<login_lockout_schema::LoginLockout> {
+    get_lockout_by_key(login_lockout_schema::get_lockout_key_for_cache(
+        kind, subject,
+    ))
+    .await
+}
+
+async fn get_lockout_by_key(key: String) -> Option<login_lockout_schema::LoginLockout> {
+    match redis_repository::get::<String>(key).await {
+        Some(v) => serde_json::from_str(&v).ok(),
+        None => None,
+    }
+}
+
+async fn clear_email_failures(subject: &str) {
+    redis_repository::del(login_lockout_schema::get_failures_key_for_cache(
+        LockoutKind::Email,
+        subject,
+    ))
+    .await;
+    redis_repository::del(login_lockout_schema::get_backoff_key_for_cache(subject)).await;
+}
+
+fn get_subjects(email: &str, ip: Option<&String>) -> Vec<(LockoutKind, String)> {
+    let mut subjects = vec![(LockoutKind::Email, normalize_email(email))];
+    if let Some(v) = ip {
+        subjects.push((LockoutKind::Ip, v.to_owned()));
+    }
+    subjects
+}
+
+/// Returns the backoff after the failed logins, doubled on every failure up to the maximum.
+fn get_backoff_sec(failures: u64) -> u64 {
+    let settings = &SETTINGS.login_throttle;
+    let factor = 2u64.saturating_pow(failures.saturating_sub(1).min(63) as u32);
+    settings
+        .backoff_base_sec
+        .saturating_mul(factor)
+        .min(settings.backoff_max_sec)
+}
+
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
@@ -0,0 +1,344 @@
+use super::auth as auth_usecase;
+use crate::{
+    schema::{auth as auth_schema, mfa as mfa_schema, user as user_schema},
+    settings::SETTINGS,
+};
+use rand::Rng;
+use repository_db_lib::user::{user_entity, Repository, User as UserRep};
+use repository_redis_lib as redis_repository;
+use sea_orm::{ColumnTrait, Condition, Set};
+use time::OffsetDateTime;
+use util_lib::auth::totp;
+use uuid::Uuid;
+
+/// Number of steps accepted before and after the current one (clock drift of the device).
+const TOTP_SKEW: u64 = 1;
+
+pub enum ErrorGet {
+    UserNotFound,
+}
+
+pub enum ErrorEnrol {
+    UserNotFound,
+    MfaAllreadyEnabled,
+}
+
+pub enum ErrorActivate {
+    UserNotFound,
+    MfaNotEnrolled,
+    MfaAllreadyEnabled,
+    InvalidCode,
+    TooManyAttempts { retry_after: u64 },
+}
+
+pub enum ErrorRegenerateRecoveryCodes {
+    UserNotFound,
+    MfaNotEnabled,
+    InvalidCode,
+    TooManyAttempts { retry_after: u64 },
+}
+
+pub enum ErrorDisable {
+    UserNotFound,
+    MfaNotEnabled,
+    MfaRequired,
+    InvalidCode,
+    TooManyAttempts { retry_after: u64 },
+}
+
+pub enum ErrorUpdateRequired {
+    UserNotFound,
+}
+
+pub async fn get(user_id: Uuid) -> Result<mfa_schema::Mfa, ErrorGet> {
+    let rep = UserRep::new().await;
+    match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => Ok(mfa_schema::Mfa::from_model(&v)),
+        None => Err(ErrorGet::UserNotFound),
+    }
+}
+
+/// Starts the enrolment with a new secret and new recovery codes.
+///
+/// MFA is enabled only once a code of the secret is confirmed with `activate`, a pending
+/// enrolment is replaced by a new one.
+pub async fn enrol(user_id: Uuid) -> Result<mfa_schema::MfaEnrolment, ErrorEnrol> {
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorEnrol::UserNotFound),
+    };
+    if user_model.is_mfa_enabled() {
+        return Err(ErrorEnrol::MfaAllreadyEnabled);
+    }
+
+    let secret = totp::gen_secret();
+    let recovery_codes = user_entity::Model::gen_recovery_codes();
+    let provisioning_uri =
+        totp::get_provisioning_uri(&secret, &SETTINGS.mfa.issuer, &user_model.email);
+
+    // Save secret and hashes of the recovery codes
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.mfa_secret = Set(Some(totp::encode_secret(&secret)));
+    user_model.mfa_recovery_codes = Set(hash_recovery_codes(&recovery_codes));
+    rep.update(user_model).await.unwrap();
+
+    Ok(mfa_schema::MfaEnrolment {
+        secret: totp::encode_secret(&secret),
+        provisioning_uri,
+        recovery_codes,
+    })
+}
+
+/// Enables MFA once the user proves the authenticator app has the secret.
+///
+/// Every session of the user is revoked, the next login needs a code.
+pub async fn activate(
+    user_id: Uuid,
+    mfa_code: &mfa_schema::MfaCode,
+) -> Result<mfa_schema::Mfa, ErrorActivate> {
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorActivate::UserNotFound),
+    };
+    if user_model.is_mfa_enabled() {
+        return Err(ErrorActivate::MfaAllreadyEnabled);
+    }
+    let secret = match get_secret(&user_model) {
+        Some(v) => v,
+        None => return Err(ErrorActivate::MfaNotEnrolled),
+    };
+    if let Some(retry_after) = count_attempt(user_id).await {
+        return Err(ErrorActivate::TooManyAttempts { retry_after });
+    }
+    if !verify_totp(user_model.id, &secret, &mfa_code.code).await {
+        return Err(ErrorActivate::InvalidCode);
+    }
+    reset_attempts(user_id).await;
+
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.mfa_enabled_at = Set(Some(OffsetDateTime::now_utc()));
+    let user_model = auth_usecase::update_user(&rep, user_model).await;
+    Ok(mfa_schema::Mfa::from_model(&user_model))
+}
+
+/// Replaces the recovery codes of the user, the old ones can't be used anymore.
+pub async fn regenerate_recovery_codes(
+    user_id: Uuid,
+    mfa_code: &mfa_schema::MfaCode,
+) -> Result<mfa_schema::MfaRecoveryCodes, ErrorRegenerateRecoveryCodes> {
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorRegenerateRecoveryCodes::UserNotFound),
+    };
+    if !user_model.is_mfa_enabled() {
+        return Err(ErrorRegenerateRecoveryCodes::MfaNotEnabled);
+    }
+    let secret = get_secret(&user_model).unwrap();
+    if let Some(retry_after) = count_attempt(user_id).await {
+        return Err(ErrorRegenerateRecoveryCodes::TooManyAttempts { retry_after });
+    }
+    if !verify_totp(user_model.id, &secret, &mfa_code.code).await {
+        return Err(ErrorRegenerateRecoveryCodes::InvalidCode);
+    }
+    reset_attempts(user_id).await;
+
+    let recovery_codes = user_entity::Model::gen_recovery_codes();
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.mfa_recovery_codes = Set(hash_recovery_codes(&recovery_codes));
+    rep.update(user_model).await.unwrap();
+    Ok(mfa_schema::MfaRecoveryCodes { recovery_codes })
+}
+
+/// Disables MFA with a TOTP or recovery code, unless MFA is enforced by staff.
+pub async fn disable(user_id: Uuid, mfa_code: &mfa_schema::MfaCode) -> Result<(), ErrorDisable> {
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorDisable::UserNotFound),
+    };
+    if !user_model.is_mfa_enabled() {
+        return Err(ErrorDisable::MfaNotEnabled);
+    }
+    if user_model.mfa_required {
+        return Err(ErrorDisable::MfaRequired);
+    }
+    if let Some(retry_after) = count_attempt(user_id).await {
+        return Err(ErrorDisable::TooManyAttempts { retry_after });
+    }
+    if !verify_code(&user_model, &mfa_code.code).await {
+        return Err(ErrorDisable::InvalidCode);
+    }
+    reset_attempts(user_id).await;
+
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.mfa_secret = Set(None);
+    user_model.mfa_enabled_at = Set(None);
+    user_model.mfa_recovery_codes = Set(Vec::new());
+    auth_usecase::update_user(&rep, user_model).await;
+    Ok(())
+}
+
+/// Enforces MFA for a staff user (or stops to), the sessions of the user are revoked.
+pub async fn update_required(
+    user_id: Uuid,
+    mfa_required: &mfa_schema::UpdateMfaRequired,
+) -> Result<user_schema::User, ErrorUpdateRequired> {
+    let rep = UserRep::new().await;
+    let filter = Condition::all()
+        .add(user_entity::Column::Id.eq(user_id))
+        .add(user_entity::Column::IsStaff.eq(true));
+    let user_model = match rep.get_one(Some(filter)).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorUpdateRequired::UserNotFound),
+    };
+
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.mfa_required = Set(mfa_required.required);
+    let user_model = auth_usecase::update_user(&rep, user_model).await;
+    Ok(user_schema::User::from_model(&user_model))
+}
+
+/// Checks a TOTP code or a recovery code of the user with MFA enabled.
+///
+/// A TOTP code is accepted once, a recovery code is removed once used.
+pub async fn verify_code(user_model: &user_entity::Model, code: &str) -> bool {
+    let secret = match get_secret(user_model) {
+        Some(v) if user_model.is_mfa_enabled() => v,
+        _ => return false,
+    };
+    if code.len() == totp::DIGITS as usize && code.chars().all(|c| c.is_ascii_digit()) {
+        return verify_totp(user_model.id, &secret, code).await;
+    }
+    let position = match user_model.find_recovery_code(code) {
+        Some(v) => v,
+        None => return false,
+    };
+    // Only the request removing the code accepts it, a concurrent use of the code is refused
+    let rep = UserRep::new().await;
+    rep.remove_recovery_code(user_model.id, &user_model.mfa_recovery_codes[position])
+        .await
+        .unwrap()
+}
+
+/// Starts the MFA challenge of the login of the user.
+pub async fn create_challenge(user_id: Uuid) -> auth_schema::MfaChallenge {
+    let mfa_token: String = rand::rng()
+        .sample_iter(rand::distr::Alphanumeric)
+        .take(43)
+        .map(char::from)
+        .collect();
+    let challenge = mfa_schema::MfaChallengeCache {
+        user_id,
+        expires_at: OffsetDateTime::now_utc().unix_timestamp() as u64
+            + mfa_schema::CHALLENGE_LIFE_SEC,
+    };
+    save_challenge(&mfa_token, &challenge).await;
+    auth_schema::MfaChallenge {
+        mfa_required: true,
+        mfa_token,
+        expires_in: mfa_schema::CHALLENGE_LIFE_SEC,
+    }
+}
+
+/// Returns the challenge of the token, `None` if it expired or was revoked.
+pub async fn get_challenge(mfa_token: &str) -> Option<mfa_schema::MfaChallengeCache> {
+    match redis_repository::get::<String>(mfa_schema::get_challenge_key_for_cache(
+        mfa_token.to_owned(),
+    ))
+    .await
+    {
+        Some(v) => serde_json::from_str(&v).ok(),
+        None => None,
+    }
+}
+
+/// Counts a wrong code of the challenge, the challenge is revoked after too many attempts.
+///
+/// The counter is a separate key incremented atomically, so concurrent wrong codes are all
+/// counted.
+pub async fn fail_challenge(mfa_token: &str, challenge: mfa_schema::MfaChallengeCache) {
+    let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+    let attempts = redis_repository::incr(
+        mfa_schema::get_challenge_attempts_key_for_cache(mfa_token.to_owned()),
+        challenge.expires_at.saturating_sub(now).max(1),
+    )
+    .await;
+    if attempts >= mfa_schema::CHALLENGE_MAX_ATTEMPTS {
+        del_challenge(mfa_token).await;
+    }
+}
+
+pub async fn del_challenge(mfa_token: &str) {
+    redis_repository::del(mfa_schema::get_challenge_key_for_cache(
+        mfa_token.to_owned(),
+    ))
+    .await;
+    redis_repository::del(mfa_schema::get_challenge_attempts_key_for_cache(
+        mfa_token.to_owned(),
+    ))
+    .await;
+}
+
+async fn save_challenge(mfa_token: &str, challenge: &mfa_schema::MfaChallengeCache) {
+    let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+    redis_repository::set(
+        mfa_schema::get_challenge_key_for_cache(mfa_token.to_owned()),
+        serde_json::to_string(challenge).unwrap(),
+        Some(challenge.expires_at.saturating_sub(now).max(1)),
+    )
+    .await;
+}
+
+/// Counts a code sent to change the MFA of the user, the changes are locked after too many codes.
+///
+/// The code is counted before it is checked, so concurrent wrong codes can't exceed the limit.
+/// Returns the seconds before the next attempt if the changes are locked.
+async fn count_attempt(user_id: Uuid) -> Option<u64> {
+    let attempts = redis_repository::incr(
+        mfa_schema::get_attempts_key_for_cache(user_id.to_string()),
+        mfa_schema::ATTEMPTS_LOCK_SEC,
+    )
+    .await;
+    match attempts > mfa_schema::MAX_ATTEMPTS {
+        true => Some(mfa_schema::ATTEMPTS_LOCK_SEC),
+        false => None,
+    }
+}
+
+/// Forgets the codes counted for the user, called once a code is accepted.
+async fn reset_attempts(user_id: Uuid) {
+    redis_repository::del(mfa_schema::get_attempts_key_for_cache(user_id.to_string())).await;
+}
+
+fn get_secret(user_model: &user_entity::Model) -> Option<Vec<u8>> {
+    user_model
+        .mfa_secret
+        .as_ref()
+        .and_then(|v| totp::decode_secret(v))
+}
+
+fn hash_recovery_codes(recovery_codes: &[String]) -> Vec<String> {
+    recovery_codes
+        .iter()
+        .map(|v| user_entity::Model::hash_recovery_code(v))
+        .collect()
+}
+
+/// Checks the TOTP code, the step of the code is remembered so the code can't be replayed.
+async fn verify_totp(user_id: Uuid, secret: &[u8], code: &str) -> bool {
+    let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+    match totp::verify(secret, code, now, TOTP_SKEW) {
+        Some(step) => {
+            redis_repository::set_nx(
+                mfa_schema::get_step_key_for_cache(user_id.to_string(), step),
+                now,
+                (2 * TOTP_SKEW + 1) * totp::STEP_SEC,
+            )
+            .await
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,28 @@
+use notification_lib::Notification;
+use repository_db_lib::{notification::Notification as NotificationRep, Repository};
+use serde::Serialize;
+
+/// Renders a notification for the recipient and persists it, the notification dispatcher sends
+/// it with the configured transport and retries it on failure.
+///
+/// # Arguments
+/// * `template` - The name of the templates of the notification, e.g. `user-password-reset`.
+/// * `recipient` - The email address of the recipient.
+/// * `locale` - The preferred locale of the recipient, the default locale if `None`.
+/// * `payload` - The values available to the templates.
+pub async fn send<T: Serialize>(
+    template: &str,
+    recipient: &str,
+    locale: Option<String>,
+    payload: &T,
+) {
+    let notification = Notification {
+        template: template.to_string(),
+        recipient: recipient.to_string(),
+        locale,
+        context: serde_json::to_value(payload).unwrap(),
+    };
+    notification_lib::enqueue(&NotificationRep::new().await, &notification)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,389 @@
+use super::{application as application_usecase, auth as auth_usecase};
+use crate::{
+    guard::client::Client,
+    query::oauth as oauth_query,
+    schema::{application as application_schema, auth as auth_schema, oauth as oauth_schema},
+    settings::SETTINGS as API_SETTINGS,
+};
+use rand::Rng;
+use repository_db_lib::{
+    app_consent::{app_consent_entity, AppConsent as AppConsentRep},
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use repository_redis_lib as redis_repository;
+use sea_orm::{ColumnTrait, Condition, Set};
+use std::fmt;
+use time::OffsetDateTime;
+use url::Url;
+use util_lib::{
+    auth::{jwt as auth_jwt, oidc, pkce},
+    jwt::{encode as jwt_encode, key::KEYS},
+    settings::SETTINGS,
+};
+use uuid::Uuid;
+
+pub enum ErrorAuthorize {
+    InvalidClient,
+    InvalidRedirectUri,
+    /// The scope that isn't supported.
+    InvalidScope(String),
+}
+
+pub enum ErrorExchangeCode {
+    MissingParameter,
+    InvalidGrant,
+}
+
+/// Error of the settings of the OpenID provider.
+#[derive(Debug)]
+pub enum ErrorOpenIdConfiguration {
+    /// `JWT_ISSUER` is not an absolute URL, the advertised endpoints would be relative.
+    RelativeIssuer(String),
+    /// `OIDC_AUTHORIZATION_PAGE` is not an absolute URL.
+    RelativeAuthorizationPage(String),
+    /// The ID tokens would be signed with the secret of the service (`HS256`), the applications
+    /// can't verify them.
+    SymmetricAlgorithm,
+}
+
+impl fmt::Display for ErrorOpenIdConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RelativeIssuer(v) => write!(
+                f,
+                "JWT_ISSUER ({}) must be the absolute public URL of the server",
+                v
+            ),
+            Self::RelativeAuthorizationPage(v) => {
+                write!(f, "OIDC_AUTHORIZATION_PAGE ({}) must be an absolute URL", v)
+            }
+            Self::SymmetricAlgorithm => write!(
+                f,
+                "JWT_ALGORITHM must be asymmetric (RS256 or EdDSA) to sign the ID tokens"
+            ),
+        }
+    }
+}
+
+/// Validates the authorization request and returns what to show to the user.
+pub async fn get_authorize_info(
+    user_claims: &auth_schema::SelfUserTokenClaims,
+    req_query: &oauth_query::Authorize,
+) -> Result<oauth_schema::AuthorizeInfo, ErrorAuthorize> {
+    let application = get_application(req_query).await?;
+    let scopes = oauth_schema::parse_scope(req_query.scope.as_ref())
+        .map_err(ErrorAuthorize::InvalidScope)?;
+    let consent_required = match get_consent(application.id, user_claims.id).await {
+        Some(v) => !scopes.iter().all(|scope| v.scopes.contains(scope)),
+        None => true,
+    };
+    Ok(oauth_schema::AuthorizeInfo {
+        application_id: application.id,
+        application_name: application.name,
+        scopes,
+        consent_required,
+    })
+}
+
+/// Saves the decision of the user and returns the redirect to the application.
+///
+/// On approval the consent of the user is extended with the requested scopes and a one-time
+/// code is issued, bound to the redirect URI and to the code challenge.
+pub async fn authorize(
+    user_claims: &auth_schema::SelfUserTokenClaims,
+    req_query: &oauth_query::Authorize,
+    decision: &oauth_schema::AuthorizeDecision,
+) -> Result<oauth_schema::AuthorizeRedirect, ErrorAuthorize> {
+    let application = get_application(req_query).await?;
+    let mut redirect_to = Url::parse(&req_query.redirect_uri).unwrap();
+    // The redirect URI is registered, the errors of the request are returned to the application
+    let scopes = match oauth_schema::parse_scope(req_query.scope.as_ref()) {
+        Ok(v) => v,
+        Err(_) => {
+            redirect_to
+                .query_pairs_mut()
+                .append_pair("error", "invalid_scope");
+            return Ok(get_redirect(redirect_to, req_query.state.as_ref()));
+        }
+    };
+    if !decision.approve {
+        redirect_to
+            .query_pairs_mut()
+            .append_pair("error", "access_denied");
+        return Ok(get_redirect(redirect_to, req_query.state.as_ref()));
+    }
+
+    // Save consent of the user
+    save_consent(application.id, user_claims.id, &scopes).await;
+
+    // The user authenticated on the start of the session of the token
+    let auth_time = match auth_usecase::get_cached_token(user_claims).await {
+        Some(v) => v.session.created_at,
+        None => OffsetDateTime::now_utc(),
+    };
+
+    // Save code in cache
+    let code: String = rand::rng()
+        .sample_iter(rand::distr::Alphanumeric)
+        .take(43)
+        .map(char::from)
+        .collect();
+    let authorization_code = oauth_schema::AuthorizationCode {
+        user_id: user_claims.id,
+        application_id: application.id,
+        redirect_uri: req_query.redirect_uri.to_owned(),
+        scopes,
+        code_challenge: req_query.code_challenge.to_owned(),
+        code_challenge_method: req_query.code_challenge_method,
+        nonce: req_query.nonce.to_owned(),
+        auth_time: auth_time.unix_timestamp() as u64,
+        mfa: user_claims.mfa,
+    };
+    redis_repository::set(
+        oauth_schema::get_key_for_cache(code.to_owned()),
+        serde_json::to_string(&authorization_code).unwrap(),
+        Some(oauth_schema::AUTHORIZATION_CODE_LIFE_SEC),
+    )
+    .await;
+
+    redirect_to.query_pairs_mut().append_pair("code", &code);
+    Ok(get_redirect(redirect_to, req_query.state.as_ref()))
+}
+
+/// Exchanges an authorization code for a new session of the user (`authorization_code` grant).
+///
+/// The tokens are limited to the application and the granted scopes, they give no access to
+/// the staff APIs. An ID token is issued too if the `openid` scope was granted.
+pub async fn exchange_code(
+    token_input: &auth_jwt::TokenInput,
+    client: &Client,
+) -> Result<auth_jwt::Oauth2LoginResult, ErrorExchangeCode> {
+    let (code, redirect_uri, client_id, code_verifier) = match (
+        &token_input.code,
+        &token_input.redirect_uri,
+        &token_input.client_id,
+        &token_input.code_verifier,
+    ) {
+        (Some(code), Some(redirect_uri), Some(client_id), Some(code_verifier)) => {
+            (code, redirect_uri, client_id, code_verifier)
+        }
+        _ => return Err(ErrorExchangeCode::MissingParameter),
+    };
+
+    // Get code from cache (removed on read, a code is used once)
+    let authorization_code =
+        match redis_repository::get_del::<String>(oauth_schema::get_key_for_cache(code.to_owned()))
+            .await
+            .and_then(|v| serde_json::from_str::<oauth_schema::AuthorizationCode>(&v).ok())
+        {
+            Some(v) => v,
+            None => return Err(ErrorExchangeCode::InvalidGrant),
+        };
+    // Check code was issued to the client, for the redirect URI and the code verifier
+    if authorization_code.application_id.to_string() != *client_id
+        || authorization_code.redirect_uri != *redirect_uri
+        || !pkce::verify_code_challenge(
+            code_verifier,
+            &authorization_code.code_challenge,
+            authorization_code.code_challenge_method,
+        )
+    {
+        return Err(ErrorExchangeCode::InvalidGrant);
+    }
+    if application_usecase::get(authorization_code.application_id)
+        .await
+        .is_err()
+    {
+        return Err(ErrorExchangeCode::InvalidGrant);
+    }
+
+    // Get actual User and start a new session
+    let rep = UserRep::new().await;
+    let user = match rep.get_by_id(authorization_code.user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorExchangeCode::InvalidGrant),
+    };
+    let mut result = auth_usecase::issue_application_tokens(
+        &user,
+        client,
+        authorization_code.mfa,
+        authorization_code.application_id,
+        &authorization_code.scopes,
+    )
+    .await;
+    if is_openid_enabled()
+        && authorization_code
+            .scopes
+            .iter()
+            .any(|v| v == oidc::SCOPE_OPENID)
+    {
+        let id_token_claims = oidc::IdTokenClaims::new(
+            get_user_info(&user, Some(&authorization_code.scopes)),
+            authorization_code.application_id.to_string(),
+            authorization_code.auth_time,
+            authorization_code.nonce,
+        );
+        result.id_token = Some(jwt_encode(&id_token_claims).unwrap());
+    }
+    Ok(result)
+}
+
+/// Returns the claims of the user, limited to the granted scopes if any.
+pub fn get_user_info(user: &user_entity::Model, scopes: Option<&Vec<String>>) -> oidc::UserInfo {
+    let is_granted = |scope: &str| scopes.is_none_or(|v| v.iter().any(|s| s == scope));
+    oidc::UserInfo {
+        sub: user.id.to_string(),
+        name: is_granted(oidc::SCOPE_PROFILE).then(|| user.name.to_owned()),
+        email: is_granted(oidc::SCOPE_EMAIL).then(|| user.email.to_owned()),
+    }
+}
+
+/// Returns the claims of the user of the access token, limited to the scopes granted to the
+/// application of the token (all claims for a session of the service).
+pub async fn get_self_user_info(
+    user_claims: &auth_schema::SelfUserTokenClaims,
+) -> Option<oidc::UserInfo> {
+    let scopes = user_claims.get_scopes();
+    let rep = UserRep::new().await;
+    rep.get_by_id(user_claims.id)
+        .await
+        .unwrap()
+        .map(|v| get_user_info(&v, scopes.as_ref()))
+}
+
+/// Checks OpenID Connect is enabled (`OIDC_AUTHORIZATION_PAGE` is set).
+pub fn is_openid_enabled() -> bool {
+    !API_SETTINGS.oidc.authorization_page.is_empty()
+}
+
+/// Checks the settings of the OpenID provider if it is enabled, called once at startup.
+pub fn check_openid_configuration() -> Result<(), ErrorOpenIdConfiguration> {
+    if !is_openid_enabled() {
+        return Ok(());
+    }
+    if !is_absolute_url(&SETTINGS.jwt.issuer) {
+        return Err(ErrorOpenIdConfiguration::RelativeIssuer(
+            SETTINGS.jwt.issuer.to_owned(),
+        ));
+    }
+    let authorization_page = &API_SETTINGS.oidc.authorization_page;
+    if !is_absolute_url(authorization_page) {
+        return Err(ErrorOpenIdConfiguration::RelativeAuthorizationPage(
+            authorization_page.to_owned(),
+        ));
+    }
+    if KEYS.is_symmetric() {
+        return Err(ErrorOpenIdConfiguration::SymmetricAlgorithm);
+    }
+    Ok(())
+}
+
+/// Returns the metadata of the OpenID provider, `None` if OpenID Connect is disabled.
+///
+/// The authorization endpoint is the login and consent page of the front end, the other
+/// endpoints are relative to the issuer.
+pub fn get_openid_configuration() -> Option<oidc::OpenIdConfiguration> {
+    if !is_openid_enabled() {
+        return None;
+    }
+    let issuer = SETTINGS.jwt.issuer.trim_end_matches('/');
+    let to_strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+    Some(oidc::OpenIdConfiguration {
+        issuer: SETTINGS.jwt.issuer.to_owned(),
+        authorization_endpoint: API_SETTINGS.oidc.authorization_page.to_owned(),
+        token_endpoint: format!("{}/oauth/token", issuer),
+        userinfo_endpoint: format!("{}/oauth/userinfo", issuer),
+        jwks_uri: format!("{}/.well-known/jwks.json", issuer),
+        revocation_endpoint: format!("{}/api/auth/revoke", issuer),
+        introspection_endpoint: format!("{}/api/auth/introspect", issuer),
+        response_types_supported: to_strings(&["code"]),
+        grant_types_supported: to_strings(&[
+            "authorization_code",
+            "refresh_token",
+            "client_credentials",
+        ]),
+        subject_types_supported: to_strings(&["public"]),
+        id_token_signing_alg_values_supported: vec![KEYS.get_algorithm()],
+        scopes_supported: to_strings(&oidc::SCOPES_SUPPORTED),
+        // The applications are public clients proving the code with PKCE, the secrets of the
+        // application clients are only for their `client_credentials` grant
+        token_endpoint_auth_methods_supported: to_strings(&["none"]),
+        code_challenge_methods_supported: to_strings(&["S256"]),
+        claims_supported: to_strings(&[
+            "sub",
+            "iss",
+            "aud",
+            "exp",
+            "iat",
+            "auth_time",
+            "nonce",
+            "name",
+            "email",
+        ]),
+    })
+}
+
+fn is_absolute_url(value: &str) -> bool {
+    match Url::parse(value) {
+        Ok(v) => matches!(v.scheme(), "http" | "https") && v.has_host(),
+        Err(_) => false,
+    }
+}
+
+/// Returns the application of the request if the redirect URI is registered for it.
+async fn get_application(
+    req_query: &oauth_query::Authorize,
+) -> Result<application_schema::Application, ErrorAuthorize> {
+    let application = match application_usecase::get(req_query.client_id).await {
+        Ok(v) => v,
+        Err(_) => return Err(ErrorAuthorize::InvalidClient),
+    };
+    if !application.redirect_uris.contains(&req_query.redirect_uri) {
+        return Err(ErrorAuthorize::InvalidRedirectUri);
+    }
+    Ok(application)
+}
+
+fn get_redirect(mut redirect_to: Url, state: Option<&String>) -> oauth_schema::AuthorizeRedirect {
+    if let Some(state) = state {
+        redirect_to.query_pairs_mut().append_pair("state", state);
+    }
+    oauth_schema::AuthorizeRedirect {
+        redirect_to: redirect_to.to_string(),
+    }
+}
+
+async fn get_consent(application_id: Uuid, user_id: Uuid) -> Option<app_consent_entity::Model> {
+    let filter = Condition::all()
+        .add(app_consent_entity::Column::ApplicationId.eq(application_id))
+        .add(app_consent_entity::Column::UserId.eq(user_id));
+    let rep = AppConsentRep::new().await;
+    rep.get_one(Some(filter)).await.unwrap()
+}
+
+async fn save_consent(application_id: Uuid, user_id: Uuid, scopes: &Vec<String>) {
+    let rep = AppConsentRep::new().await;
+    match get_consent(application_id, user_id).await {
+        Some(v) => {
+            let mut consent_scopes = v.scopes.to_owned();
+            for scope in scopes {
+                if !consent_scopes.contains(scope) {
+                    consent_scopes.push(scope.to_owned());
+                }
+            }
+            let mut consent_model: app_consent_entity::ActiveModel = v.into();
+            consent_model.scopes = Set(consent_scopes);
+            rep.update(consent_model).await.unwrap();
+        }
+        None => {
+            let consent_model = app_consent_entity::ActiveModel {
+                application_id: Set(application_id),
+                user_id: Set(user_id),
+                scopes: Set(scopes.to_owned()),
+                ..Default::default()
+            };
+            rep.create(consent_model).await.unwrap();
+        }
+    }
+}
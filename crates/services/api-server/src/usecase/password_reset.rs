@@ -0,0 +1,153 @@
+use super::{
+    auth as auth_usecase, login_throttle as login_throttle_usecase,
+    notification as notification_usecase,
+};
+use crate::{
+    guard::client::Client,
+    schema::{auth as auth_schema, password_reset as password_reset_schema},
+    settings::SETTINGS,
+};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use repository_redis_lib as redis_repository;
+use sea_orm::Set;
+use time::{Duration, OffsetDateTime};
+use util_lib::crypto;
+use uuid::Uuid;
+
+pub enum ErrorConfirm {
+    InvalidToken,
+}
+
+/// Requests a reset of the password of the email.
+///
+/// The reset token is created and sent in the background, the caller returns at once whether
+/// the email exists or not, so neither the response nor its time reveal it.
+pub fn request(reset_request: &password_reset_schema::PasswordResetRequest, client: &Client) {
+    tokio::spawn(send_token(
+        reset_request.email.to_owned(),
+        client.ip.to_owned(),
+        client.locale.to_owned(),
+    ));
+}
+
+/// Sets the new password with a reset token and revokes every session of the user.
+///
+/// The token is looked up by its digest, so it is never compared with a stored value, and it is
+/// removed on read: it is used once.
+pub async fn confirm(
+    reset_confirm: &password_reset_schema::PasswordResetConfirm,
+) -> Result<(), ErrorConfirm> {
+    // Get token from cache (removed on read)
+    let token_digest = crypto::get_token_digest(&reset_confirm.token);
+    let reset_cache = match redis_repository::get_del::<String>(
+        password_reset_schema::get_key_for_cache(token_digest),
+    )
+    .await
+    .and_then(|v| serde_json::from_str::<password_reset_schema::PasswordResetCache>(&v).ok())
+    {
+        Some(v) => v,
+        None => return Err(ErrorConfirm::InvalidToken),
+    };
+    redis_repository::del(password_reset_schema::get_user_key_for_cache(
+        reset_cache.user_id.to_string(),
+    ))
+    .await;
+
+    // Get User, the token is void if the password changed since the request
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(reset_cache.user_id).await.unwrap() {
+        Some(v) if v.token_version == reset_cache.token_version => v,
+        _ => return Err(ErrorConfirm::InvalidToken),
+    };
+
+    // Save new password (hashed on save, the token version is bumped), the token was sent to
+    // the email so the email is verified too
+    let email = user_model.email.to_owned();
+    let is_email_verified = user_model.is_email_verified();
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.password = Set(reset_confirm.new_password.to_owned());
+    if !is_email_verified {
+        user_model.email_verified_at = Set(Some(OffsetDateTime::now_utc()));
+    }
+    auth_usecase::update_user(&rep, user_model).await;
+    redis_repository::del_keys(auth_schema::get_prefix_key_for_cache(
+        reset_cache.user_id.to_string(),
+    ))
+    .await;
+    // The owner of the email proved it, the failed logins are forgotten
+    login_throttle_usecase::succeed(&email).await;
+    Ok(())
+}
+
+/// Voids the pending reset token of the user, if any.
+pub async fn void_token(user_id: Uuid) {
+    let user_key = password_reset_schema::get_user_key_for_cache(user_id.to_string());
+    if let Some(v) = redis_repository::get_del::<String>(user_key).await {
+        redis_repository::del(password_reset_schema::get_key_for_cache(v)).await;
+    }
+}
+
+/// Creates a reset token for the user of the email and hands it to the notification queue.
+///
+/// Requests over the limits of the email or of the client IP are ignored.
+async fn send_token(email: String, ip: Option<String>, locale: Option<String>) {
+    let settings = &SETTINGS.password_reset;
+    let mut subjects = vec![(email.trim().to_lowercase(), settings.email_max_requests)];
+    if let Some(v) = ip {
+        subjects.push((v, settings.ip_max_requests));
+    }
+    for (subject, max_requests) in subjects {
+        let requests = redis_repository::sliding_window_add(
+            password_reset_schema::get_requests_key_for_cache(&subject),
+            settings.window_sec,
+        )
+        .await;
+        if requests > max_requests {
+            return;
+        }
+    }
+
+    // Try to get User
+    let filter = UserRep::get_email_filter(&email);
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_one(Some(filter)).await.unwrap() {
+        Some(v) => v,
+        None => return,
+    };
+
+    // Void the previous token of the user and save the new one in cache
+    void_token(user_model.id).await;
+    let user_key = password_reset_schema::get_user_key_for_cache(user_model.id.to_string());
+    let token = crypto::gen_token(password_reset_schema::TOKEN_LEN);
+    let token_digest = crypto::get_token_digest(&token);
+    let reset_cache = password_reset_schema::PasswordResetCache {
+        user_id: user_model.id,
+        token_version: user_model.token_version,
+    };
+    redis_repository::set(
+        password_reset_schema::get_key_for_cache(token_digest.to_owned()),
+        serde_json::to_string(&reset_cache).unwrap(),
+        Some(settings.token_life_sec),
+    )
+    .await;
+    redis_repository::set(user_key, token_digest, Some(settings.token_life_sec)).await;
+
+    // Send token to the user
+    let notification = password_reset_schema::PasswordResetNotification {
+        user_id: user_model.id,
+        email: user_model.email,
+        name: user_model.name,
+        token,
+        expires_at: OffsetDateTime::now_utc() + Duration::seconds(settings.token_life_sec as i64),
+    };
+    notification_usecase::send(
+        password_reset_schema::NOTIFICATION_EVENT,
+        &notification.email,
+        locale,
+        &notification,
+    )
+    .await;
+}
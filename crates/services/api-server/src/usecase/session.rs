@@ -0,0 +1,75 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use super::auth as auth_usecase;
+use crate::schema::{auth as auth_schema, session as session_schema};
+use repository_db_lib::user::{Repository, SoftDelete, User as UserRep};
+use repository_redis_lib as redis_repository;
+use uuid::Uuid;
+
+pub enum ErrorRevoke {
+    SessionNotFound,
+}
+
+pub enum ErrorRevokeAll {
+    UserNotFound,
+}
+
+pub async fn get_all(
+    user_claims: &auth_schema::SelfUserTokenClaims,
+) -> session_schema::SessionList {
+    // Group tokens by session, keeping the most recent metadata
+    let mut sessions = HashMap::<Uuid, auth_schema::SessionMeta>::new();
+    for (_, cached_token) in auth_usecase::get_cached_tokens(user_claims.id).await {
+        let sid = cached_token.claims.oauth2_claims.sid;
+        match sessions.get(&sid) {
+            Some(v) if v.last_used_at >= cached_token.session.last_used_at => {}
+            _ => {
+                sessions.insert(sid, cached_token.session);
+            }
+        }
+    }
+
+    let mut sessions: Vec<session_schema::Session> = sessions
+        .iter()
+        .map(|(sid, meta)| {
+            session_schema::Session::from_meta(*sid, meta, *sid == user_claims.oauth2_claims.sid)
+        })
+        .collect();
+    sessions.sort_by_key(|v| Reverse(v.last_used_at));
+    session_schema::SessionList { sessions }
+}
+
+pub async fn revoke(
+    user_claims: &auth_schema::SelfUserTokenClaims,
+    session_id: Uuid,
+) -> Result<(), ErrorRevoke> {
+    let mut is_found = false;
+    for (key, cached_token) in auth_usecase::get_cached_tokens(user_claims.id).await {
+        if cached_token.claims.oauth2_claims.sid == session_id {
+            redis_repository::del(key).await;
+            is_found = true;
+        }
+    }
+    match is_found {
+        true => Ok(()),
+        false => Err(ErrorRevoke::SessionNotFound),
+    }
+}
+
+pub async fn revoke_others(user_claims: &auth_schema::SelfUserTokenClaims) {
+    for (key, cached_token) in auth_usecase::get_cached_tokens(user_claims.id).await {
+        if cached_token.claims.oauth2_claims.sid != user_claims.oauth2_claims.sid {
+            redis_repository::del(key).await;
+        }
+    }
+}
+
+pub async fn revoke_all(user_id: Uuid) -> Result<(), ErrorRevokeAll> {
+    // Sessions of a deleted user can be revoked too
+    let rep = UserRep::new().await.with_deleted();
+    if rep.get_by_id(user_id).await.unwrap().is_none() {
+        return Err(ErrorRevokeAll::UserNotFound);
+    }
+    redis_repository::del_keys(auth_schema::get_prefix_key_for_cache(user_id.to_string())).await;
+    Ok(())
+}
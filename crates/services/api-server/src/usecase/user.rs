@@ -1,10 +1,18 @@
-use crate::{query::user as user_query, schema::user as user_schema};
+use std::str::FromStr;
+
+use super::{auth as auth_usecase, login_throttle as login_throttle_usecase};
+use crate::{
+    query::user as user_query,
+    schema::{auth as auth_schema, user as user_schema},
+};
 use orm_util_lib::prelude::EntityFilterableTrait;
 use repository_db_lib::{
     user::{user_entity, User as UserRep},
-    Repository,
+    Repository, SoftDelete,
 };
-use sea_orm::{ColumnTrait, Condition, Set};
+use repository_redis_lib as redis_repository;
+use sea_orm::{ColumnTrait, Condition, Iterable, Set, SqlErr};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub enum ErrorUpdate {
@@ -15,6 +23,15 @@ pub enum ErrorCreate {
     EmailAllreadyExist,
 }
 
+pub enum ErrorUpdateStaffPermissions {
+    UserNotFound,
+    UserNotStaff,
+}
+
+pub enum ErrorSetPassword {
+    UserNotFound,
+}
+
 pub async fn get_all(query_filter: &user_query::User) -> user_schema::UserList {
     // Get filter
     let filter = query_filter.to_condition::<user_entity::Entity>();
@@ -35,9 +52,18 @@ pub async fn create(
     new_user: &user_schema::CreateUser,
     password: Option<&str>,
 ) -> Result<user_schema::User, ErrorCreate> {
-    // Check if email allready exist
-    let filter = Condition::all().add(user_entity::Column::Email.eq(new_user.email.to_owned()));
-    let rep = UserRep::new().await;
+    insert(new_user, password, Default::default()).await
+}
+
+/// Saves a new user, `user_model` holds the fields not given by `new_user`.
+async fn insert(
+    new_user: &user_schema::CreateUser,
+    password: Option<&str>,
+    user_model: user_entity::ActiveModel,
+) -> Result<user_schema::User, ErrorCreate> {
+    // Check if email (deleted included, in any case) allready exist
+    let filter = UserRep::get_email_filter(&new_user.email);
+    let rep = UserRep::new().await.with_deleted();
     if let Some(_) = rep.get_one(Some(filter)).await.unwrap() {
         return Err(ErrorCreate::EmailAllreadyExist);
     }
@@ -54,10 +80,108 @@ pub async fn create(
         password: Set(password),
         birthday: Set(new_user.birthday),
         is_staff: Set(new_user.is_staff.unwrap_or(false)),
-        ..Default::default()
+        ..user_model
     };
+    // The unique index of the emails catches the concurrent creations
+    let result = rep.create(user_model).await;
+    if let Err(e) = &result {
+        if let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() {
+            return Err(ErrorCreate::EmailAllreadyExist);
+        }
+    }
     // Convert Model into Schema
-    let user_model = rep.create(user_model).await.unwrap();
+    Ok(user_schema::User::from_model(&result.unwrap()))
+}
+
+/// Creates a staff user with every staff permission, the first administrator of a deployment.
+///
+/// The email is trusted as verified: it is given by whoever administers the deployment. The
+/// permissions are saved with the user, a failure leaves no staff user without them.
+pub async fn create_superuser(
+    new_user: &user_schema::CreateUser,
+    password: &str,
+) -> Result<user_schema::User, ErrorCreate> {
+    insert(
+        &user_schema::CreateUser {
+            name: new_user.name.to_owned(),
+            email: new_user.email.to_owned(),
+            is_staff: Some(true),
+            birthday: new_user.birthday,
+        },
+        Some(password),
+        get_superuser_model(OffsetDateTime::now_utc()),
+    )
+    .await
+}
+
+/// Returns the fields a superuser is inserted with: every staff permission and the email
+/// verified at `verified_at`.
+pub fn get_superuser_model(verified_at: OffsetDateTime) -> user_entity::ActiveModel {
+    user_entity::ActiveModel {
+        staff_permissions: Set(user_entity::UserStaffPermission::iter().collect()),
+        email_verified_at: Set(Some(verified_at)),
+        ..Default::default()
+    }
+}
+
+/// Adds the permissions to the staff user, the permissions it has are kept.
+pub async fn grant_staff_permissions(
+    user_id: Uuid,
+    permissions: &[user_schema::StaffPermission],
+) -> Result<user_schema::User, ErrorUpdateStaffPermissions> {
+    update_staff_permissions(user_id, |staff_permissions| {
+        for permission in permissions {
+            let permission =
+                user_entity::UserStaffPermission::from_str(&permission.to_string()).unwrap();
+            if !staff_permissions.contains(&permission) {
+                staff_permissions.push(permission);
+            }
+        }
+    })
+    .await
+}
+
+/// Removes the permissions from the staff user.
+pub async fn revoke_staff_permissions(
+    user_id: Uuid,
+    permissions: &[user_schema::StaffPermission],
+) -> Result<user_schema::User, ErrorUpdateStaffPermissions> {
+    let permissions: Vec<user_entity::UserStaffPermission> = permissions
+        .iter()
+        .map(|v| user_entity::UserStaffPermission::from_str(&v.to_string()).unwrap())
+        .collect();
+    update_staff_permissions(user_id, |staff_permissions| {
+        staff_permissions.retain(|v| !permissions.contains(v));
+    })
+    .await
+}
+
+/// Changes the staff permissions of the staff user, the tokens of the user are revoked (the
+/// token version is bumped on save) so the permissions apply at once.
+async fn update_staff_permissions<F>(
+    user_id: Uuid,
+    update: F,
+) -> Result<user_schema::User, ErrorUpdateStaffPermissions>
+where
+    F: FnOnce(&mut Vec<user_entity::UserStaffPermission>),
+{
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorUpdateStaffPermissions::UserNotFound),
+    };
+    if !user_model.is_staff {
+        return Err(ErrorUpdateStaffPermissions::UserNotStaff);
+    }
+
+    let mut staff_permissions = user_model.staff_permissions.to_owned();
+    update(&mut staff_permissions);
+    if staff_permissions == user_model.staff_permissions {
+        return Ok(user_schema::User::from_model(&user_model));
+    }
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.staff_permissions = Set(staff_permissions);
+    let user_model = auth_usecase::update_user(&rep, user_model).await;
     Ok(user_schema::User::from_model(&user_model))
 }
 
@@ -112,6 +236,27 @@ pub async fn update_password(
     let mut user_model: user_entity::ActiveModel = user_model.into();
     user_model.password = Set(passwords.new_password.to_owned());
     // Convert Model into Schema
-    let user_model = rep.update(user_model).await.unwrap();
+    let user_model = auth_usecase::update_user(&rep, user_model).await;
+    Ok(user_schema::User::from_model(&user_model))
+}
+
+/// Sets the password of the user without the old one and revokes every session of the user.
+pub async fn set_password(
+    user_id: Uuid,
+    password: &str,
+) -> Result<user_schema::User, ErrorSetPassword> {
+    let rep = UserRep::new().await;
+    let user_model = match rep.get_by_id(user_id).await.unwrap() {
+        Some(v) => v,
+        None => return Err(ErrorSetPassword::UserNotFound),
+    };
+
+    // Save new password (hashed on save, the token version is bumped)
+    let mut user_model: user_entity::ActiveModel = user_model.into();
+    user_model.password = Set(password.to_owned());
+    let user_model = auth_usecase::update_user(&rep, user_model).await;
+    redis_repository::del_keys(auth_schema::get_prefix_key_for_cache(user_id.to_string())).await;
+    // The user can log in with the new password at once
+    login_throttle_usecase::succeed(&user_model.email).await;
     Ok(user_schema::User::from_model(&user_model))
 }
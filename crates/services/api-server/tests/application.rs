@@ -0,0 +1,143 @@
+use api_server::{
+    schema::application as application_schema, usecase::application as application_usecase,
+};
+use repository_db_lib::{
+    application::{app_staff_entity, application_entity, Application as ApplicationRep},
+    Repository,
+};
+use sea_orm::Set;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+fn get_application_model() -> application_entity::Model {
+    let now = OffsetDateTime::now_utc();
+    application_entity::Model {
+        id: Uuid::new_v4(),
+        name: "Application".to_string(),
+        description: "Description".to_string(),
+        redirect_uris: vec!["https://example.com/callback".to_string()],
+        is_deleted: false,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[test]
+fn application_hides_deleted_flag() {
+    let model = get_application_model();
+    let application =
+        serde_json::to_value(application_schema::Application::from_model(&model)).unwrap();
+    assert_eq!(application["id"], model.id.to_string());
+    assert_eq!(application["name"], "Application");
+    assert_eq!(application["description"], "Description");
+    assert_eq!(
+        application["redirect_uris"][0],
+        "https://example.com/callback"
+    );
+    assert!(application.get("is_deleted").is_none());
+
+    let application_list = serde_json::to_value(application_schema::ApplicationList::from_models(
+        &vec![model.to_owned()],
+        10,
+        20,
+        21,
+    ))
+    .unwrap();
+    assert_eq!(
+        application_list["applications"][0]["id"],
+        model.id.to_string()
+    );
+    assert_eq!(application_list["limit"], 10);
+    assert_eq!(application_list["offset"], 20);
+    assert_eq!(application_list["total"], 21);
+}
+
+#[test]
+fn update_application_checks_fields() {
+    let application: application_schema::UpdateApplication =
+        serde_json::from_str(r#"{"name": "Application"}"#).unwrap();
+    assert_eq!(application.description, "");
+    assert!(application.redirect_uris.is_empty());
+
+    assert!(
+        serde_json::from_str::<application_schema::UpdateApplication>(r#"{"name": ""}"#).is_err()
+    );
+    assert!(
+        serde_json::from_str::<application_schema::UpdateApplication>(&format!(
+            r#"{{"name": "Application", "description": "{}"}}"#,
+            "a".repeat(2049)
+        ))
+        .is_err()
+    );
+}
+
+#[test]
+fn staff_permissions_are_converted_without_duplicates() {
+    let permissions = vec![
+        application_schema::ApplicationPermissions::ReadStaff,
+        application_schema::ApplicationPermissions::CreateClient,
+        application_schema::ApplicationPermissions::ReadStaff,
+    ];
+    let models = application_schema::ApplicationPermissions::to_models(&permissions);
+    assert_eq!(
+        models,
+        vec![
+            app_staff_entity::AppStaffPermissions::ReadStaff,
+            app_staff_entity::AppStaffPermissions::CreateClient,
+        ]
+    );
+    assert_eq!(
+        application_schema::ApplicationPermissions::from_models(
+            &app_staff_entity::AppStaffPermissions::get_all()
+        )
+        .len(),
+        app_staff_entity::AppStaffPermissions::get_all().len()
+    );
+
+    let now = OffsetDateTime::now_utc();
+    let model = app_staff_entity::Model {
+        id: Uuid::new_v4(),
+        application_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        permissions: models,
+        created_at: now,
+        updated_at: now,
+    };
+    let staff =
+        serde_json::to_value(application_schema::ApplicationStaff::from_model(&model)).unwrap();
+    assert_eq!(staff["user_id"], model.user_id.to_string());
+    assert_eq!(
+        staff["permissions"],
+        serde_json::json!(["ReadStaff", "CreateClient"])
+    );
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL (docker-compose up -d)"]
+async fn deleted_application_is_hidden_until_restored() {
+    migration::init().await;
+    let application = ApplicationRep::new()
+        .await
+        .create(application_entity::ActiveModel {
+            name: Set(Uuid::new_v4().to_string()),
+            description: Set(String::new()),
+            redirect_uris: Set(Vec::new()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(application_usecase::restore(application.id).await.is_err());
+
+    assert!(application_usecase::delete(application.id).await.is_ok());
+    assert!(application_usecase::get(application.id).await.is_err());
+    assert!(application_usecase::delete(application.id).await.is_err());
+    assert!(
+        application_usecase::get_permissions(Uuid::new_v4(), application.id)
+            .await
+            .is_none()
+    );
+
+    assert!(application_usecase::restore(application.id).await.is_ok());
+    assert!(application_usecase::get(application.id).await.is_ok());
+}
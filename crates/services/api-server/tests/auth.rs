@@ -0,0 +1,213 @@
+use api_server::{
+    guard::client::Client,
+    schema::{auth as auth_schema, user as user_schema},
+    usecase::{auth as auth_usecase, session as session_usecase, user as user_usecase},
+};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use repository_redis_lib as redis_repository;
+use time::macros::date;
+use util_lib::auth::jwt::{
+    IntrospectInput, Oauth2GrantType, Oauth2TokenType, RevokeInput, TokenInput,
+};
+use uuid::Uuid;
+
+fn get_client() -> Client {
+    Client {
+        ip: Some("127.0.0.1".to_string()),
+        user_agent: Some("test".to_string()),
+        credentials: None,
+        locale: None,
+    }
+}
+
+/// Creates a new user in the database of `DATABASE_URL`.
+async fn get_user() -> user_entity::Model {
+    migration::init().await;
+    let user = match user_usecase::create(
+        &user_schema::CreateUser {
+            name: "User".to_string(),
+            email: format!("{}@example.com", Uuid::new_v4()),
+            is_staff: Some(false),
+            birthday: date!(2000 - 01 - 01),
+        },
+        Some("password"),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("user must be created"),
+    };
+    UserRep::new()
+        .await
+        .get_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+async fn refresh(refresh_token: Option<String>) -> Result<(), auth_usecase::ErrorToken> {
+    let token_input = TokenInput {
+        grant_type: Oauth2GrantType::RefreshToken,
+        refresh_token,
+        client_id: None,
+        client_secret: None,
+        scope: None,
+        code: None,
+        redirect_uri: None,
+        code_verifier: None,
+    };
+    auth_usecase::token(&token_input, &get_client())
+        .await
+        .map(|_| ())
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn tokens_are_revoked() {
+    revoked_refresh_token_can_not_refresh().await;
+    token_version_is_written_on_bump().await;
+    every_session_is_revoked().await;
+    introspection_returns_claims_of_active_tokens().await;
+}
+
+async fn revoked_refresh_token_can_not_refresh() {
+    let user = get_user().await;
+    let application_id = Uuid::new_v4();
+    let revoked =
+        auth_usecase::issue_application_tokens(&user, &get_client(), false, application_id, &[])
+            .await;
+    let kept = auth_usecase::issue_tokens(&user, &get_client(), false).await;
+    let revoke_input = |token: &Option<String>| RevokeInput {
+        token: token.to_owned().unwrap(),
+        token_type_hint: Some(Oauth2TokenType::RefreshToken),
+    };
+
+    // The tokens of another client and the sessions of the service are ignored
+    auth_usecase::revoke(
+        &Uuid::new_v4().to_string(),
+        &revoke_input(&revoked.refresh_token),
+    )
+    .await;
+    auth_usecase::revoke(
+        &application_id.to_string(),
+        &revoke_input(&kept.refresh_token),
+    )
+    .await;
+    let introspect_result = auth_usecase::introspect(&IntrospectInput {
+        token: revoked.access_token.to_owned(),
+        token_type_hint: None,
+    })
+    .await;
+    assert!(introspect_result.active);
+
+    auth_usecase::revoke(
+        &application_id.to_string(),
+        &revoke_input(&revoked.refresh_token),
+    )
+    .await;
+    assert!(matches!(
+        refresh(revoked.refresh_token).await,
+        Err(auth_usecase::ErrorToken::InvalidGrant)
+    ));
+    // The other session of the user is not revoked
+    assert!(refresh(kept.refresh_token).await.is_ok());
+}
+
+async fn token_version_is_written_on_bump() {
+    let user = get_user().await;
+    let tokens = auth_usecase::issue_tokens(&user, &get_client(), false).await;
+    let claims = auth_schema::SelfUserTokenClaims::from_jwt(&tokens.access_token).unwrap();
+
+    // The first check caches the version, the new password bumps it
+    assert!(auth_usecase::token_version_is_actual(&claims).await);
+    assert!(auth_usecase::token_version_is_actual(&claims).await);
+    assert!(user_usecase::set_password(user.id, "new password")
+        .await
+        .is_ok());
+    assert!(!auth_usecase::token_version_is_actual(&claims).await);
+    let user = UserRep::new()
+        .await
+        .get_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // A cache which missed the bump is read again for the newer tokens
+    let tokens = auth_usecase::issue_tokens(&user, &get_client(), false).await;
+    let new_claims = auth_schema::SelfUserTokenClaims::from_jwt(&tokens.access_token).unwrap();
+    redis_repository::set(
+        auth_schema::get_token_version_key_for_cache(user.id.to_string()),
+        claims.token_version,
+        None,
+    )
+    .await;
+    assert!(auth_usecase::token_version_is_actual(&new_claims).await);
+    assert!(!auth_usecase::token_version_is_actual(&claims).await);
+}
+
+async fn every_session_is_revoked() {
+    let user = get_user().await;
+    // More sessions than a page of the scan of the keys
+    for _ in 0..120 {
+        auth_usecase::issue_tokens(&user, &get_client(), false).await;
+    }
+    let tokens = auth_usecase::issue_tokens(&user, &get_client(), false).await;
+    let claims = auth_schema::SelfUserTokenClaims::from_jwt(&tokens.access_token).unwrap();
+    assert!(auth_usecase::token_version_is_actual(&claims).await);
+
+    assert!(session_usecase::revoke_all(user.id).await.is_ok());
+    assert!(auth_usecase::get_cached_tokens(user.id).await.is_empty());
+    // The other keys of the user are kept
+    assert!(
+        redis_repository::exist(auth_schema::get_token_version_key_for_cache(
+            user.id.to_string()
+        ))
+        .await
+    );
+}
+
+async fn introspection_returns_claims_of_active_tokens() {
+    let user = get_user().await;
+    let application_id = Uuid::new_v4();
+    let tokens = auth_usecase::issue_application_tokens(
+        &user,
+        &get_client(),
+        false,
+        application_id,
+        &["openid".to_string(), "email".to_string()],
+    )
+    .await;
+    let introspect = |token: String| async move {
+        auth_usecase::introspect(&IntrospectInput {
+            token,
+            token_type_hint: None,
+        })
+        .await
+    };
+
+    let result = introspect(tokens.access_token.to_owned()).await;
+    assert!(result.active);
+    assert_eq!(result.sub, Some(user.id.to_string()));
+    assert_eq!(result.username, Some(user.email.to_owned()));
+    assert_eq!(result.aud, Some(application_id.to_string()));
+    assert_eq!(result.scope.as_deref(), Some("openid email"));
+
+    let result = introspect(tokens.refresh_token.to_owned().unwrap()).await;
+    assert!(result.active);
+
+    auth_usecase::revoke(
+        &application_id.to_string(),
+        &RevokeInput {
+            token: tokens.access_token.to_owned(),
+            token_type_hint: None,
+        },
+    )
+    .await;
+    assert!(!introspect(tokens.access_token).await.active);
+    assert!(!introspect(tokens.refresh_token.unwrap()).await.active);
+    assert!(!introspect("token".to_string()).await.active);
+}
@@ -0,0 +1,80 @@
+use notification_lib::{store::NotificationStore, Message};
+use repository_amqp_lib::outbox::OutboxStore;
+use repository_db_lib::{
+    notification::{notification_entity, Notification as NotificationRep},
+    outbox::{outbox_entity, Outbox as OutboxRep},
+    Repository,
+};
+use sea_orm::Set;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL (docker-compose up -d)"]
+async fn messages_are_claimed_and_marked() {
+    migration::init().await;
+    outbox_message_is_claimed_and_given_up().await;
+    notification_is_claimed_sent_and_removed().await;
+}
+
+async fn outbox_message_is_claimed_and_given_up() {
+    let rep = OutboxRep::new().await;
+    let outbox_model = rep
+        .create(outbox_entity::ActiveModel {
+            queue: Set("test".to_string()),
+            event: Set("test".to_string()),
+            payload: Set("{}".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let messages = rep.claim_pending(u32::MAX as u64, 60).await.unwrap();
+    assert!(messages.iter().any(|v| v.id == outbox_model.id));
+    // Leased
+    let messages = rep.claim_pending(u32::MAX as u64, 60).await.unwrap();
+    assert!(!messages.iter().any(|v| v.id == outbox_model.id));
+
+    rep.mark_failed(outbox_model.id, "error", None)
+        .await
+        .unwrap();
+    let outbox_model = rep.get_by_id(outbox_model.id).await.unwrap().unwrap();
+    assert_eq!(outbox_model.status, outbox_entity::OutboxStatus::Failed);
+    assert_eq!(outbox_model.attempts, 1);
+    assert_eq!(outbox_model.last_error.as_deref(), Some("error"));
+}
+
+async fn notification_is_claimed_sent_and_removed() {
+    let rep = NotificationRep::new().await;
+    let id = Uuid::new_v4();
+    rep.add(&Message {
+        id,
+        template: "test".to_string(),
+        locale: "en".to_string(),
+        recipient: "user@example.com".to_string(),
+        subject: "Test".to_string(),
+        body_text: "Token".to_string(),
+        body_html: Some("<p>Token</p>".to_string()),
+        attempts: 0,
+    })
+    .await
+    .unwrap();
+
+    let messages = rep.claim_due(u32::MAX as u64, 60).await.unwrap();
+    assert!(messages.iter().any(|v| v.id == id));
+
+    rep.mark_sent(id).await.unwrap();
+    let notification_model = rep.get_by_id(id).await.unwrap().unwrap();
+    assert_eq!(
+        notification_model.status,
+        notification_entity::NotificationStatus::Sent
+    );
+    assert!(notification_model.sent_at.is_some());
+    assert_eq!(notification_model.body_text, "");
+    assert_eq!(notification_model.body_html, None);
+
+    let before = OffsetDateTime::now_utc() + Duration::seconds(1);
+    assert!(rep.delete_sent(before).await.unwrap() >= 1);
+    assert!(rep.get_by_id(id).await.unwrap().is_none());
+}
@@ -0,0 +1,178 @@
+use api_server::{
+    guard::client::Client,
+    schema::{key as key_schema, user as user_schema},
+    usecase::{key as key_usecase, user as user_usecase},
+};
+use repository_db_lib::{
+    application::{application_entity, Application as ApplicationRep},
+    key::{key_entity, Key as KeyRep},
+    Repository,
+};
+use sea_orm::Set;
+use time::{macros::date, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+fn get_key_model(activated_at: Option<OffsetDateTime>, lifetime: i32) -> key_entity::Model {
+    let now = OffsetDateTime::now_utc();
+    key_entity::Model {
+        id: Uuid::new_v4(),
+        value: key_entity::Model::gen_value(),
+        activated_at,
+        lifetime,
+        is_bunned: false,
+        application_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        created_by_user_id: Uuid::new_v4(),
+        is_deleted: false,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[test]
+fn key_detail_is_flat_and_only_detail_has_value() {
+    let model = get_key_model(None, 60);
+    let key = serde_json::to_value(key_schema::Key::from_model(&model)).unwrap();
+    assert_eq!(key["id"], model.id.to_string());
+    assert_eq!(key["lifetime"], 60);
+    assert!(key["activated_at"].is_null());
+    assert!(key.get("value").is_none());
+    assert!(key.get("is_deleted").is_none());
+
+    let key_detail = serde_json::to_value(key_schema::KeyDetail::from_model(&model)).unwrap();
+    assert_eq!(key_detail["value"], model.value);
+    assert_eq!(key_detail["id"], model.id.to_string());
+
+    let key_list = serde_json::to_value(key_schema::KeyList::from_models(
+        &vec![model.to_owned()],
+        10,
+        0,
+        1,
+    ))
+    .unwrap();
+    assert_eq!(key_list["keys"][0]["id"], model.id.to_string());
+    assert_eq!(key_list["total"], 1);
+}
+
+#[test]
+fn verdict_lives_until_the_key_expires_at_most_a_day() {
+    let now = OffsetDateTime::now_utc();
+    let model = get_key_model(Some(now - Duration::seconds(20)), 60);
+    let claims = key_schema::KeyVerdictClaims::from_model(&model, now);
+    assert_eq!(claims.key_id, model.id);
+    assert_eq!(claims.remaining_lifetime, 40);
+    assert_eq!(claims.exp, claims.iat + 40);
+
+    let model = get_key_model(None, 10 * key_schema::KEY_VERDICT_LIFE_SEC as i32);
+    let claims = key_schema::KeyVerdictClaims::from_model(&model, now);
+    assert_eq!(
+        claims.exp,
+        claims.iat + key_schema::KEY_VERDICT_LIFE_SEC as u64
+    );
+}
+
+fn get_client(ip: &str) -> Client {
+    Client {
+        ip: Some(ip.to_string()),
+        user_agent: Some("test".to_string()),
+        credentials: None,
+        locale: None,
+    }
+}
+
+/// Creates a new key of a new application and user in the database of `DATABASE_URL`.
+async fn get_key() -> key_schema::KeyDetail {
+    migration::init().await;
+    let user = match user_usecase::create(
+        &user_schema::CreateUser {
+            name: "User".to_string(),
+            email: format!("{}@example.com", Uuid::new_v4()),
+            is_staff: Some(false),
+            birthday: date!(2000 - 01 - 01),
+        },
+        Some("password"),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("user must be created"),
+    };
+    let application = ApplicationRep::new()
+        .await
+        .create(application_entity::ActiveModel {
+            name: Set(get_value()),
+            description: Set(String::new()),
+            redirect_uris: Set(Vec::new()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    match key_usecase::create(
+        user.id,
+        application.id,
+        &key_schema::CreateKey {
+            value: get_value(),
+            lifetime: 3600,
+            is_bunned: false,
+            user_id: user.id,
+        },
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("key must be created"),
+    }
+}
+
+fn get_value() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn keys_are_verified() {
+    key_is_activated_once().await;
+    unknown_keys_lock_verifications().await;
+}
+
+async fn key_is_activated_once() {
+    let key = get_key().await;
+    let rep = KeyRep::new().await;
+    let activated_at = OffsetDateTime::now_utc();
+    assert!(rep.activate(key.key.id, activated_at).await.unwrap());
+    assert!(!rep
+        .activate(key.key.id, OffsetDateTime::now_utc())
+        .await
+        .unwrap());
+
+    let verification = match key_usecase::verify(
+        &key_schema::VerifyKey { value: key.value },
+        &get_client("127.0.0.1"),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("key must be verified"),
+    };
+    assert_eq!(
+        verification.expires_at.map(|v| v.unix_timestamp()),
+        Some(activated_at.unix_timestamp() + 3600)
+    );
+}
+
+async fn unknown_keys_lock_verifications() {
+    let key = get_key().await;
+    let client = get_client(&Uuid::new_v4().to_string());
+    for _ in 0..key_schema::VERIFY_MAX_FAILURES {
+        let result =
+            key_usecase::verify(&key_schema::VerifyKey { value: get_value() }, &client).await;
+        assert!(matches!(result, Err(key_usecase::ErrorVerify::NotFound)));
+    }
+    // Even a known key is refused while the client is locked
+    let result = key_usecase::verify(&key_schema::VerifyKey { value: key.value }, &client).await;
+    assert!(matches!(
+        result,
+        Err(key_usecase::ErrorVerify::TooManyAttempts { .. })
+    ));
+}
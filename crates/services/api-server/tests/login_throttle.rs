@@ -0,0 +1,104 @@
+use api_server::{
+    query::login_lockout as login_lockout_query,
+    schema::login_lockout::{self as login_lockout_schema, LockoutKind},
+    settings::SETTINGS,
+    usecase::login_throttle as login_throttle_usecase,
+};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+#[test]
+fn lockout_keys_are_matched_by_prefix() {
+    assert_eq!(
+        login_lockout_schema::get_failures_key_for_cache(LockoutKind::Email, "a@example.com"),
+        "LOGIN_FAILURES:email:a@example.com"
+    );
+    assert_eq!(
+        login_lockout_schema::get_backoff_key_for_cache("a@example.com"),
+        "LOGIN_BACKOFF:a@example.com"
+    );
+    let lockout_key = login_lockout_schema::get_lockout_key_for_cache(LockoutKind::Ip, "127.0.0.1");
+    assert_eq!(lockout_key, "LOGIN_LOCKOUT:ip:127.0.0.1");
+    let prefix = login_lockout_schema::get_lockout_prefix_key_for_cache();
+    assert!(lockout_key.starts_with(prefix.trim_end_matches('*')));
+}
+
+#[test]
+fn lockout_is_cached_as_json() {
+    let now = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+    let lockout = login_lockout_schema::LoginLockout {
+        kind: LockoutKind::Email,
+        subject: "a@example.com".to_string(),
+        failures: 10,
+        locked_at: now,
+        expires_at: now + Duration::minutes(15),
+    };
+    let value = serde_json::to_string(&lockout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&value).unwrap();
+    assert_eq!(json["kind"], "email");
+    assert_eq!(json["failures"], 10);
+
+    let cached: login_lockout_schema::LoginLockout = serde_json::from_str(&value).unwrap();
+    assert_eq!(cached.kind, LockoutKind::Email);
+    assert_eq!(cached.locked_at, now);
+    assert_eq!(cached.expires_at, now + Duration::minutes(15));
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn failed_logins_are_throttled() {
+    failed_logins_back_off().await;
+    email_is_locked_out_and_cleared().await;
+}
+
+async fn failed_logins_back_off() {
+    let email = format!("{}@example.com", Uuid::new_v4());
+    let ip = Uuid::new_v4().to_string();
+    assert_eq!(
+        login_throttle_usecase::get_retry_after(&email, Some(&ip)).await,
+        None
+    );
+
+    for _ in 0..3 {
+        login_throttle_usecase::fail(&email, Some(&ip)).await;
+    }
+    // The email waits before the next login, whatever the case or the IP
+    let retry_after = login_throttle_usecase::get_retry_after(&email.to_uppercase(), None).await;
+    assert!(retry_after.is_some_and(|v| v <= SETTINGS.login_throttle.backoff_max_sec));
+
+    login_throttle_usecase::succeed(&email).await;
+    assert_eq!(
+        login_throttle_usecase::get_retry_after(&email, Some(&ip)).await,
+        None
+    );
+}
+
+async fn email_is_locked_out_and_cleared() {
+    let email = format!("{}@example.com", Uuid::new_v4());
+    for _ in 0..SETTINGS.login_throttle.email_max_attempts {
+        login_throttle_usecase::fail(&email, None).await;
+    }
+    let retry_after = login_throttle_usecase::get_retry_after(&email, None).await;
+    // The lockout outlasts the backoff
+    assert!(retry_after.is_some_and(|v| v >= SETTINGS.login_throttle.lockout_sec - 1));
+    assert!(login_throttle_usecase::get_all()
+        .await
+        .lockouts
+        .iter()
+        .any(|v| v.kind == LockoutKind::Email && v.subject == email));
+
+    let req_query = login_lockout_query::LoginLockout {
+        kind: LockoutKind::Email,
+        subject: email.to_uppercase(),
+    };
+    assert!(login_throttle_usecase::clear(&req_query).await.is_ok());
+    assert_eq!(
+        login_throttle_usecase::get_retry_after(&email, None).await,
+        None
+    );
+    assert!(matches!(
+        login_throttle_usecase::clear(&req_query).await,
+        Err(login_throttle_usecase::ErrorClear::LockoutNotFound)
+    ));
+}
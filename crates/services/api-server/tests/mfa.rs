@@ -0,0 +1,129 @@
+use api_server::{
+    schema::{mfa as mfa_schema, user as user_schema},
+    usecase::{mfa as mfa_usecase, user as user_usecase},
+};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use sea_orm::Set;
+use time::{macros::date, OffsetDateTime};
+use uuid::Uuid;
+
+/// Creates a new user with MFA enabled in the database of `DATABASE_URL`, returns it with its
+/// recovery codes.
+async fn get_user_with_mfa() -> (user_entity::Model, Vec<String>) {
+    migration::init().await;
+    let user = match user_usecase::create(
+        &user_schema::CreateUser {
+            name: "User".to_string(),
+            email: format!("{}@example.com", Uuid::new_v4()),
+            is_staff: Some(false),
+            birthday: date!(2000 - 01 - 01),
+        },
+        Some("password"),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("user must be created"),
+    };
+    let recovery_codes = match mfa_usecase::enrol(user.id).await {
+        Ok(v) => v.recovery_codes,
+        Err(_) => panic!("user must be enrolled"),
+    };
+    (enable_mfa(user.id).await, recovery_codes)
+}
+
+/// Enables MFA for the enrolled user without a code.
+async fn enable_mfa(user_id: Uuid) -> user_entity::Model {
+    let rep = UserRep::new().await;
+    let mut user_model: user_entity::ActiveModel =
+        rep.get_by_id(user_id).await.unwrap().unwrap().into();
+    user_model.mfa_enabled_at = Set(Some(OffsetDateTime::now_utc()));
+    rep.update(user_model).await.unwrap()
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn mfa_attempts_are_counted_atomically() {
+    recovery_code_is_accepted_once().await;
+    challenge_is_revoked_after_max_attempts().await;
+    changes_are_locked_after_max_attempts().await;
+}
+
+async fn recovery_code_is_accepted_once() {
+    let (user, recovery_codes) = get_user_with_mfa().await;
+
+    // Both requests read the user before any of them removes the code
+    let (first, second) = tokio::join!(
+        mfa_usecase::verify_code(&user, &recovery_codes[0]),
+        mfa_usecase::verify_code(&user, &recovery_codes[0]),
+    );
+    assert!(first ^ second);
+    let user = UserRep::new()
+        .await
+        .get_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(user.mfa_recovery_codes.len(), recovery_codes.len() - 1);
+    assert!(!mfa_usecase::verify_code(&user, &recovery_codes[0]).await);
+    assert!(mfa_usecase::verify_code(&user, &recovery_codes[1]).await);
+}
+
+async fn challenge_is_revoked_after_max_attempts() {
+    let (user, _) = get_user_with_mfa().await;
+    let mfa_token = mfa_usecase::create_challenge(user.id).await.mfa_token;
+
+    // Every wrong code counts, even sent concurrently with the same read of the challenge
+    let mut failures = Vec::new();
+    for _ in 0..5 {
+        let challenge = mfa_usecase::get_challenge(&mfa_token).await.unwrap();
+        failures.push(mfa_usecase::fail_challenge(&mfa_token, challenge));
+    }
+    for failure in failures {
+        failure.await;
+    }
+    assert!(mfa_usecase::get_challenge(&mfa_token).await.is_none());
+}
+
+async fn changes_are_locked_after_max_attempts() {
+    let (user, recovery_codes) = get_user_with_mfa().await;
+    let get_code = |code: &str| mfa_schema::MfaCode {
+        code: code.to_string(),
+    };
+
+    // A right code resets the counter
+    for _ in 0..mfa_schema::MAX_ATTEMPTS - 1 {
+        assert!(matches!(
+            mfa_usecase::regenerate_recovery_codes(user.id, &get_code("000000")).await,
+            Err(mfa_usecase::ErrorRegenerateRecoveryCodes::InvalidCode)
+        ));
+    }
+    let recovery_codes = match mfa_usecase::disable(user.id, &get_code(&recovery_codes[0])).await {
+        Ok(_) => {
+            mfa_usecase::enrol(user.id)
+                .await
+                .ok()
+                .unwrap()
+                .recovery_codes
+        }
+        Err(_) => panic!("mfa must be disabled"),
+    };
+    let user = enable_mfa(user.id).await;
+
+    // Even a right code is refused once the changes are locked
+    for _ in 0..mfa_schema::MAX_ATTEMPTS {
+        assert!(matches!(
+            mfa_usecase::disable(user.id, &get_code("000000")).await,
+            Err(mfa_usecase::ErrorDisable::InvalidCode)
+        ));
+    }
+    assert!(matches!(
+        mfa_usecase::disable(user.id, &get_code(&recovery_codes[0])).await,
+        Err(mfa_usecase::ErrorDisable::TooManyAttempts { retry_after })
+            if retry_after == mfa_schema::ATTEMPTS_LOCK_SEC
+    ));
+}
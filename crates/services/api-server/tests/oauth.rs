@@ -0,0 +1,24 @@
+use api_server::schema::oauth as oauth_schema;
+
+#[test]
+fn scope_is_split_without_duplicates() {
+    let scope = "openid  profile openid\temail".to_string();
+    assert_eq!(
+        oauth_schema::parse_scope(Some(&scope)),
+        Ok(vec![
+            "openid".to_string(),
+            "profile".to_string(),
+            "email".to_string()
+        ])
+    );
+    assert_eq!(oauth_schema::parse_scope(None), Ok(vec![]));
+}
+
+#[test]
+fn unsupported_scope_is_rejected() {
+    let scope = "openid admin profile".to_string();
+    assert_eq!(
+        oauth_schema::parse_scope(Some(&scope)),
+        Err("admin".to_string())
+    );
+}
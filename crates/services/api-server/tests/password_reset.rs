@@ -0,0 +1,169 @@
+use api_server::{
+    schema::{password_reset as password_reset_schema, user as user_schema},
+    usecase::{password_reset as password_reset_usecase, user as user_usecase},
+};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use repository_redis_lib as redis_repository;
+use time::{
+    macros::{date, datetime},
+    OffsetDateTime,
+};
+use util_lib::crypto;
+use uuid::Uuid;
+
+#[test]
+fn reset_confirm_needs_token_and_password() {
+    let reset_confirm: password_reset_schema::PasswordResetConfirm =
+        serde_json::from_str(r#"{"token": "token", "new_password": "password"}"#).unwrap();
+    assert_eq!(reset_confirm.token, "token");
+    assert_eq!(reset_confirm.new_password, "password");
+
+    assert!(
+        serde_json::from_str::<password_reset_schema::PasswordResetConfirm>(
+            r#"{"token": "", "new_password": "password"}"#
+        )
+        .is_err()
+    );
+    assert!(
+        serde_json::from_str::<password_reset_schema::PasswordResetConfirm>(
+            r#"{"token": "token"}"#
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn reset_token_is_cached_under_its_digest() {
+    let token = crypto::gen_token(password_reset_schema::TOKEN_LEN);
+    let token_digest = crypto::get_token_digest(&token);
+    let key = password_reset_schema::get_key_for_cache(token_digest.to_owned());
+    assert_eq!(key, format!("PASSWORD_RESET:{}", token_digest));
+    assert!(!key.contains(&token));
+
+    let user_id = Uuid::new_v4();
+    assert_eq!(
+        password_reset_schema::get_user_key_for_cache(user_id.to_string()),
+        format!("USER:{}_PASSWORD_RESET", user_id)
+    );
+}
+
+#[test]
+fn notification_has_rfc3339_expiration() {
+    let notification = password_reset_schema::PasswordResetNotification {
+        user_id: Uuid::new_v4(),
+        email: "a@example.com".to_string(),
+        name: "User".to_string(),
+        token: "token".to_string(),
+        expires_at: datetime!(2026-10-18 12:00 UTC),
+    };
+    let value = serde_json::to_value(&notification).unwrap();
+    assert_eq!(value["expires_at"], "2026-10-18T12:00:00Z");
+    assert_eq!(value["token"], "token");
+}
+
+/// Creates a new user in the database of `DATABASE_URL`.
+async fn get_user() -> user_entity::Model {
+    migration::init().await;
+    let user = match user_usecase::create(
+        &user_schema::CreateUser {
+            name: "User".to_string(),
+            email: format!("{}@example.com", Uuid::new_v4()),
+            is_staff: Some(false),
+            birthday: date!(2000 - 01 - 01),
+        },
+        Some("password"),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("user must be created"),
+    };
+    get_user_by_id(user.id).await
+}
+
+async fn get_user_by_id(id: Uuid) -> user_entity::Model {
+    UserRep::new().await.get_by_id(id).await.unwrap().unwrap()
+}
+
+/// Saves a reset token of the user in cache, as sent with the notification.
+async fn get_token(user_model: &user_entity::Model) -> String {
+    let token = crypto::gen_token(password_reset_schema::TOKEN_LEN);
+    let token_digest = crypto::get_token_digest(&token);
+    let reset_cache = password_reset_schema::PasswordResetCache {
+        user_id: user_model.id,
+        token_version: user_model.token_version,
+    };
+    redis_repository::set(
+        password_reset_schema::get_key_for_cache(token_digest.to_owned()),
+        serde_json::to_string(&reset_cache).unwrap(),
+        Some(60),
+    )
+    .await;
+    redis_repository::set(
+        password_reset_schema::get_user_key_for_cache(user_model.id.to_string()),
+        token_digest,
+        Some(60),
+    )
+    .await;
+    token
+}
+
+async fn confirm(token: &str) -> Result<(), password_reset_usecase::ErrorConfirm> {
+    password_reset_usecase::confirm(&password_reset_schema::PasswordResetConfirm {
+        token: token.to_string(),
+        new_password: "new_password".to_string(),
+    })
+    .await
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn password_is_reset_once() {
+    token_resets_password_once().await;
+    token_is_void_after_password_change().await;
+    token_is_voided().await;
+}
+
+async fn token_resets_password_once() {
+    let user_model = get_user().await;
+    let token = get_token(&user_model).await;
+    assert!(confirm(&token).await.is_ok());
+
+    let updated_model = get_user_by_id(user_model.id).await;
+    assert_ne!(updated_model.password, user_model.password);
+    assert_ne!(updated_model.token_version, user_model.token_version);
+    assert!(updated_model
+        .email_verified_at
+        .is_some_and(|v| v <= OffsetDateTime::now_utc()));
+
+    assert!(matches!(
+        confirm(&token).await,
+        Err(password_reset_usecase::ErrorConfirm::InvalidToken)
+    ));
+}
+
+async fn token_is_void_after_password_change() {
+    let user_model = get_user().await;
+    let token = get_token(&user_model).await;
+    // The password changes with another token in between
+    let other_token = get_token(&user_model).await;
+    assert!(confirm(&other_token).await.is_ok());
+    assert!(matches!(
+        confirm(&token).await,
+        Err(password_reset_usecase::ErrorConfirm::InvalidToken)
+    ));
+}
+
+async fn token_is_voided() {
+    let user_model = get_user().await;
+    let token = get_token(&user_model).await;
+    password_reset_usecase::void_token(user_model.id).await;
+    assert!(matches!(
+        confirm(&token).await,
+        Err(password_reset_usecase::ErrorConfirm::InvalidToken)
+    ));
+}
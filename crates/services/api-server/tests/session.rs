@@ -0,0 +1,98 @@
+use api_server::{
+    guard::client::Client,
+    schema::{auth as auth_schema, session as session_schema, user as user_schema},
+    usecase::{auth as auth_usecase, session as session_usecase, user as user_usecase},
+};
+use repository_db_lib::{user::User as UserRep, Repository};
+use time::{macros::date, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+fn get_client(ip: &str) -> Client {
+    Client {
+        ip: Some(ip.to_string()),
+        user_agent: Some("test".to_string()),
+        credentials: None,
+        locale: None,
+    }
+}
+
+#[test]
+fn session_is_built_from_meta() {
+    let mut meta = auth_schema::SessionMeta::new(Some("127.0.0.1".to_string()), None);
+    assert_eq!(meta.created_at, meta.last_used_at);
+    meta.last_used_at += Duration::minutes(5);
+
+    let id = Uuid::new_v4();
+    let session =
+        serde_json::to_value(session_schema::Session::from_meta(id, &meta, true)).unwrap();
+    assert_eq!(session["id"], id.to_string());
+    assert_eq!(session["ip"], "127.0.0.1");
+    assert!(session["user_agent"].is_null());
+    assert_eq!(session["is_current"], true);
+    let last_used_at = OffsetDateTime::parse(
+        session["last_used_at"].as_str().unwrap(),
+        &time::format_description::well_known::Rfc3339,
+    )
+    .unwrap();
+    assert_eq!(last_used_at, meta.last_used_at);
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn sessions_are_listed_and_revoked() {
+    migration::init().await;
+    let user = match user_usecase::create(
+        &user_schema::CreateUser {
+            name: "User".to_string(),
+            email: format!("{}@example.com", Uuid::new_v4()),
+            is_staff: Some(false),
+            birthday: date!(2000 - 01 - 01),
+        },
+        Some("password"),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("user must be created"),
+    };
+    let user = UserRep::new()
+        .await
+        .get_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut claims = Vec::new();
+    for ip in ["10.0.0.1", "10.0.0.2", "10.0.0.3"] {
+        let tokens = auth_usecase::issue_tokens(&user, &get_client(ip), false).await;
+        claims.push(auth_schema::SelfUserTokenClaims::from_jwt(&tokens.access_token).unwrap());
+    }
+
+    // The access and refresh tokens of a login are one session, the latest used first
+    let sessions = session_usecase::get_all(&claims[0]).await.sessions;
+    assert_eq!(sessions.len(), 3);
+    assert_eq!(sessions.iter().filter(|v| v.is_current).count(), 1);
+    assert!(sessions
+        .iter()
+        .any(|v| v.is_current && v.id == claims[0].oauth2_claims.sid));
+    assert!(sessions
+        .windows(2)
+        .all(|v| v[0].last_used_at >= v[1].last_used_at));
+
+    assert!(
+        session_usecase::revoke(&claims[0], claims[1].oauth2_claims.sid)
+            .await
+            .is_ok()
+    );
+    assert!(
+        session_usecase::revoke(&claims[0], claims[1].oauth2_claims.sid)
+            .await
+            .is_err()
+    );
+    assert_eq!(session_usecase::get_all(&claims[0]).await.sessions.len(), 2);
+
+    session_usecase::revoke_others(&claims[0]).await;
+    let sessions = session_usecase::get_all(&claims[0]).await.sessions;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, claims[0].oauth2_claims.sid);
+}
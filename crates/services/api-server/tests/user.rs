@@ -0,0 +1,72 @@
+use std::str::FromStr;
+
+use api_server::{
+    schema::user::{self as user_schema, StaffPermission},
+    usecase::user as user_usecase,
+};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use sea_orm::{ActiveValue, Iterable};
+use time::{macros::date, OffsetDateTime};
+use uuid::Uuid;
+
+#[test]
+fn superuser_has_every_staff_permission_and_verified_email() {
+    let verified_at = OffsetDateTime::now_utc();
+    let user_model = user_usecase::get_superuser_model(verified_at);
+    let permissions = match user_model.staff_permissions {
+        ActiveValue::Set(v) => v,
+        _ => panic!("staff permissions must be set"),
+    };
+    assert_eq!(
+        permissions,
+        user_entity::UserStaffPermission::iter().collect::<Vec<_>>()
+    );
+    // Every permission of the database is a permission of the API
+    for permission in &permissions {
+        assert!(StaffPermission::from_str(&permission.to_string()).is_ok());
+    }
+    assert_eq!(
+        user_model.email_verified_at,
+        ActiveValue::Set(Some(verified_at))
+    );
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn superuser_is_created() {
+    migration::init().await;
+    let new_user = user_schema::CreateUser {
+        name: "Superuser".to_string(),
+        email: format!("{}@example.com", Uuid::new_v4()),
+        is_staff: Some(false),
+        birthday: date!(2000 - 01 - 01),
+    };
+    let user = match user_usecase::create_superuser(&new_user, "password").await {
+        Ok(v) => v,
+        Err(_) => panic!("superuser must be created"),
+    };
+
+    // Saved with the insert, not only in the response
+    let user_model = UserRep::new()
+        .await
+        .get_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(user_model.is_staff);
+    assert!(user_model.is_email_verified());
+    assert_eq!(
+        user_model.staff_permissions,
+        user_entity::UserStaffPermission::iter().collect::<Vec<_>>()
+    );
+
+    // The email is taken
+    assert!(matches!(
+        user_usecase::create_superuser(&new_user, "password").await,
+        Err(user_usecase::ErrorCreate::EmailAllreadyExist)
+    ));
+}
@@ -3,13 +3,39 @@ use sea_orm::DatabaseConnection;
 pub use sea_orm_migration::prelude::*;
 
 mod m20250503_000001_create_tables;
+mod m20261018_000001_fix_app_staff_permissions;
+mod m20261018_000002_create_outbox;
+mod m20261018_000003_add_user_token_version;
+mod m20261018_000004_create_app_client;
+mod m20261018_000005_create_app_consent;
+mod m20261018_000006_add_user_mfa;
+mod m20261018_000007_add_user_email_verification;
+mod m20261018_000008_create_notification;
+mod m20261018_000009_add_outbox_retry;
+mod m20261018_000010_backfill_app_staff_permissions;
+
+pub use m20261018_000010_backfill_app_staff_permissions::{
+    get_backfill_sql, APPLICATION_PERMISSIONS, CLIENT_PERMISSIONS, STAFF_PERMISSIONS,
+};
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20250503_000001_create_tables::Migration)]
+        vec![
+            Box::new(m20250503_000001_create_tables::Migration),
+            Box::new(m20261018_000001_fix_app_staff_permissions::Migration),
+            Box::new(m20261018_000002_create_outbox::Migration),
+            Box::new(m20261018_000003_add_user_token_version::Migration),
+            Box::new(m20261018_000004_create_app_client::Migration),
+            Box::new(m20261018_000005_create_app_consent::Migration),
+            Box::new(m20261018_000006_add_user_mfa::Migration),
+            Box::new(m20261018_000007_add_user_email_verification::Migration),
+            Box::new(m20261018_000008_create_notification::Migration),
+            Box::new(m20261018_000009_add_outbox_retry::Migration),
+            Box::new(m20261018_000010_backfill_app_staff_permissions::Migration),
+        ]
     }
 }
 
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS "outbox" (
+                "id" UUID PRIMARY KEY,
+                "queue" VARCHAR(255) NOT NULL,
+                "event" VARCHAR(255) NOT NULL,
+                "payload" TEXT NOT NULL,
+                "attempts" INTEGER NOT NULL DEFAULT 0,
+                "last_error" TEXT,
+                "sent_at" TIMESTAMPTZ,
+                "created_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"CREATE INDEX "idx_outbox_pending" ON "outbox" ("created_at") WHERE "sent_at" IS NULL;"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS "outbox";"#)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS "app_client" (
+                "id" UUID PRIMARY KEY,
+                "application_id" UUID NOT NULL,
+                "name" VARCHAR(255) NOT NULL,
+                "secret" VARCHAR NOT NULL,
+                "permissions" VARCHAR(255)[] NOT NULL DEFAULT '{}',
+                "created_by_user_id" UUID NOT NULL,
+                "secret_rotated_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                "is_deleted" BOOLEAN NOT NULL DEFAULT FALSE,
+                "created_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                "updated_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CONSTRAINT "fk_application_id" FOREIGN KEY ("application_id") REFERENCES "application" ("id") ON DELETE CASCADE,
+                CONSTRAINT "fk_created_by_user_id" FOREIGN KEY ("created_by_user_id") REFERENCES "user" ("id") ON DELETE CASCADE
+            )"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"CREATE INDEX "idx_app_client_application_id" ON "app_client" ("application_id");
+            CREATE INDEX "idx_app_client_is_deleted" ON "app_client" ("is_deleted");"#,
+        )
+        .await?;
+
+        // Staff holding every application permission keep holding every permission
+        db.execute_unprepared(
+            r#"UPDATE "app_staff"
+            SET "permissions" = "permissions" || ARRAY['CreateClient', 'ReadClient', 'UpdateClient', 'DeleteClient']::VARCHAR(255)[]
+            WHERE "permissions" @> ARRAY[
+                'UpdateApplication', 'ReadApplication', 'DeleteApplication',
+                'CreateKey', 'ReadKey', 'ReadKeyDetail', 'UpdateKey', 'DeleteKey',
+                'CreateStaff', 'ReadStaff', 'UpdateStaff', 'DeleteStaff'
+            ]::VARCHAR(255)[];"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"UPDATE "app_staff"
+            SET "permissions" = ARRAY(
+                SELECT p FROM UNNEST("permissions") AS p
+                WHERE p NOT IN ('CreateClient', 'ReadClient', 'UpdateClient', 'DeleteClient')
+            );"#,
+        )
+        .await?;
+
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS "app_client";"#)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"ALTER TABLE "application"
+            ADD COLUMN IF NOT EXISTS "redirect_uris" VARCHAR(2048)[] NOT NULL DEFAULT '{}';"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS "app_consent" (
+                "id" UUID PRIMARY KEY,
+                "application_id" UUID NOT NULL,
+                "user_id" UUID NOT NULL,
+                "scopes" VARCHAR(255)[] NOT NULL DEFAULT '{}',
+                "created_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                "updated_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CONSTRAINT "fk_application_id" FOREIGN KEY ("application_id") REFERENCES "application" ("id") ON DELETE CASCADE,
+                CONSTRAINT "fk_user_id" FOREIGN KEY ("user_id") REFERENCES "user" ("id") ON DELETE CASCADE,
+                CONSTRAINT "uq_app_consent_application_id_user_id" UNIQUE ("application_id", "user_id")
+            )"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"CREATE INDEX "idx_app_consent_user_id" ON "app_consent" ("user_id");"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS "app_consent";"#)
+            .await?;
+
+        db.execute_unprepared(
+            r#"ALTER TABLE "application" DROP COLUMN IF EXISTS "redirect_uris";"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"ALTER TABLE "user"
+            ADD COLUMN IF NOT EXISTS "mfa_secret" VARCHAR(255),
+            ADD COLUMN IF NOT EXISTS "mfa_enabled_at" TIMESTAMPTZ,
+            ADD COLUMN IF NOT EXISTS "mfa_recovery_codes" VARCHAR(255)[] NOT NULL DEFAULT '{}',
+            ADD COLUMN IF NOT EXISTS "mfa_required" BOOLEAN NOT NULL DEFAULT FALSE;"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"ALTER TABLE "user"
+            DROP COLUMN IF EXISTS "mfa_secret",
+            DROP COLUMN IF EXISTS "mfa_enabled_at",
+            DROP COLUMN IF EXISTS "mfa_recovery_codes",
+            DROP COLUMN IF EXISTS "mfa_required";"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, sea_orm::Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // The users created before the verification are kept usable
+        db.execute_unprepared(
+            r#"ALTER TABLE "user"
+            ADD COLUMN IF NOT EXISTS "email_verified_at" TIMESTAMPTZ;
+            UPDATE "user" SET "email_verified_at" = "created_at" WHERE "email_verified_at" IS NULL;"#,
+        )
+        .await?;
+        // The index fails if two users (deleted included) have the same email with another case,
+        // they are listed to be merged or renamed before the migration runs again
+        let duplicates = db
+            .query_all(Statement::from_string(
+                manager.get_database_backend(),
+                r#"SELECT LOWER("email") AS "email", STRING_AGG("id"::TEXT, ', ' ORDER BY "created_at") AS "ids"
+                FROM "user" GROUP BY LOWER("email") HAVING COUNT(*) > 1;"#,
+            ))
+            .await?;
+        if !duplicates.is_empty() {
+            let mut lines = Vec::new();
+            for row in duplicates {
+                let email: String = row.try_get("", "email")?;
+                let ids: String = row.try_get("", "ids")?;
+                lines.push(format!("{}: {}", email, ids));
+            }
+            return Err(DbErr::Migration(format!(
+                "Users with the same email in another case must be merged or renamed (see README) before the unique index on the email is created:\n{}",
+                lines.join("\n")
+            )));
+        }
+        db.execute_unprepared(
+            r#"CREATE UNIQUE INDEX IF NOT EXISTS "idx_user_email_unique" ON "user" (LOWER("email"));"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"DROP INDEX IF EXISTS "idx_user_email_unique";
+            ALTER TABLE "user"
+            DROP COLUMN IF EXISTS "email_verified_at";"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS "notification" (
+                "id" UUID PRIMARY KEY,
+                "template" VARCHAR(255) NOT NULL,
+                "locale" VARCHAR(35) NOT NULL,
+                "recipient" VARCHAR(255) NOT NULL,
+                "subject" VARCHAR(998) NOT NULL,
+                "body_text" TEXT NOT NULL,
+                "body_html" TEXT,
+                "status" VARCHAR(255) NOT NULL DEFAULT 'pending',
+                "attempts" INTEGER NOT NULL DEFAULT 0,
+                "last_error" TEXT,
+                "next_attempt_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                "sent_at" TIMESTAMPTZ,
+                "created_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"CREATE INDEX "idx_notification_pending" ON "notification" ("next_attempt_at") WHERE "status" = 'pending';"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS "notification";"#)
+            .await?;
+
+        Ok(())
+    }
+}
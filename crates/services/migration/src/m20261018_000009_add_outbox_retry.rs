@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"ALTER TABLE "outbox"
+            ADD COLUMN "status" VARCHAR(255) NOT NULL DEFAULT 'pending',
+            ADD COLUMN "next_attempt_at" TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"UPDATE "outbox" SET "status" = 'sent' WHERE "sent_at" IS NOT NULL;"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"DROP INDEX IF EXISTS "idx_outbox_pending";
+            CREATE INDEX "idx_outbox_pending" ON "outbox" ("next_attempt_at") WHERE "status" = 'pending';
+            CREATE INDEX "idx_outbox_sent" ON "outbox" ("sent_at") WHERE "status" = 'sent';"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"DROP INDEX IF EXISTS "idx_outbox_sent";
+            DROP INDEX IF EXISTS "idx_outbox_pending";
+            CREATE INDEX "idx_outbox_pending" ON "outbox" ("created_at") WHERE "sent_at" IS NULL;"#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"ALTER TABLE "outbox" DROP COLUMN "next_attempt_at", DROP COLUMN "status";"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+/// Permissions of the application staff before the staff and client ones were added.
+pub const APPLICATION_PERMISSIONS: [&str; 8] = [
+    "UpdateApplication",
+    "ReadApplication",
+    "DeleteApplication",
+    "CreateKey",
+    "ReadKey",
+    "ReadKeyDetail",
+    "UpdateKey",
+    "DeleteKey",
+];
+pub const STAFF_PERMISSIONS: [&str; 4] = ["CreateStaff", "ReadStaff", "UpdateStaff", "DeleteStaff"];
+pub const CLIENT_PERMISSIONS: [&str; 4] =
+    ["CreateClient", "ReadClient", "UpdateClient", "DeleteClient"];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Staff holding every application permission keep holding every permission, the staff
+        // permissions are granted first so the client ones are granted to the same staff
+        db.execute_unprepared(&get_backfill_sql(
+            &STAFF_PERMISSIONS,
+            &APPLICATION_PERMISSIONS,
+        ))
+        .await?;
+        db.execute_unprepared(&get_backfill_sql(
+            &CLIENT_PERMISSIONS,
+            &[&APPLICATION_PERMISSIONS[..], &STAFF_PERMISSIONS[..]].concat(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // The backfilled permissions can't be told from the ones granted afterwards, they are kept
+        Ok(())
+    }
+}
+
+/// Returns the statement granting the permissions to the staff holding every required permission
+/// and none of the granted ones.
+pub fn get_backfill_sql(permissions: &[&str], required_permissions: &[&str]) -> String {
+    let permissions = get_array_sql(permissions);
+    format!(
+        r#"UPDATE "app_staff"
+        SET "permissions" = "permissions" || {permissions}
+        WHERE "permissions" @> {}
+        AND NOT "permissions" && {permissions};"#,
+        get_array_sql(required_permissions),
+    )
+}
+
+/// Returns the literal of a permission array, the permissions are names of variants.
+fn get_array_sql(permissions: &[&str]) -> String {
+    let permissions: Vec<String> = permissions.iter().map(|v| format!("'{}'", v)).collect();
+    format!("ARRAY[{}]::VARCHAR(255)[]", permissions.join(", "))
+}
@@ -0,0 +1,106 @@
+use adapter_lib::db;
+use entity_lib::app_staff::AppStaffPermissions;
+use migration::{get_backfill_sql, APPLICATION_PERMISSIONS, CLIENT_PERMISSIONS, STAFF_PERMISSIONS};
+use sea_orm::{ConnectionTrait, DbBackend, Statement, TransactionTrait};
+use uuid::Uuid;
+
+#[test]
+fn backfilled_permissions_are_every_permission() {
+    let mut names: Vec<&str> = [
+        &APPLICATION_PERMISSIONS[..],
+        &STAFF_PERMISSIONS[..],
+        &CLIENT_PERMISSIONS[..],
+    ]
+    .concat();
+    names.sort();
+    let mut permissions: Vec<String> = AppStaffPermissions::get_all()
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    permissions.sort();
+    assert_eq!(names, permissions);
+}
+
+#[test]
+fn backfill_grants_only_to_staff_holding_required_permissions() {
+    let sql = get_backfill_sql(&["CreateStaff", "ReadStaff"], &["ReadApplication"]);
+    let sql: Vec<&str> = sql.lines().map(|v| v.trim()).collect();
+    assert_eq!(
+        sql,
+        [
+            r#"UPDATE "app_staff""#,
+            r#"SET "permissions" = "permissions" || ARRAY['CreateStaff', 'ReadStaff']::VARCHAR(255)[]"#,
+            r#"WHERE "permissions" @> ARRAY['ReadApplication']::VARCHAR(255)[]"#,
+            r#"AND NOT "permissions" && ARRAY['CreateStaff', 'ReadStaff']::VARCHAR(255)[];"#,
+        ]
+    );
+}
+
+/// The statements run again in a transaction rolled back at the end, they only change the
+/// inserted staff.
+#[tokio::test]
+#[ignore = "needs PostgreSQL (docker-compose up -d)"]
+async fn backfill_grants_permissions_to_staff_holding_every_permission() {
+    migration::init().await;
+    let txn = db::get_connection().await.begin().await.unwrap();
+    let application_id = Uuid::new_v4();
+    txn.execute_unprepared(&format!(
+        r#"INSERT INTO "application" ("id", "name") VALUES ('{0}', '{0}')"#,
+        application_id
+    ))
+    .await
+    .unwrap();
+    let mut staff = Vec::new();
+    for permissions in [&APPLICATION_PERMISSIONS[..], &APPLICATION_PERMISSIONS[1..]] {
+        let user_id = Uuid::new_v4();
+        txn.execute_unprepared(&format!(
+            r#"INSERT INTO "user" ("id", "name", "email", "password", "birthday")
+            VALUES ('{0}', 'User', '{0}@example.com', '', '2000-01-01')"#,
+            user_id
+        ))
+        .await
+        .unwrap();
+        txn.execute_unprepared(&format!(
+            r#"INSERT INTO "app_staff" ("id", "application_id", "user_id", "permissions")
+            VALUES ('{}', '{}', '{}', ARRAY['{}']::VARCHAR(255)[])"#,
+            Uuid::new_v4(),
+            application_id,
+            user_id,
+            permissions.join("', '")
+        ))
+        .await
+        .unwrap();
+        staff.push(user_id);
+    }
+
+    txn.execute_unprepared(&get_backfill_sql(
+        &STAFF_PERMISSIONS,
+        &APPLICATION_PERMISSIONS,
+    ))
+    .await
+    .unwrap();
+    txn.execute_unprepared(&get_backfill_sql(
+        &CLIENT_PERMISSIONS,
+        &[&APPLICATION_PERMISSIONS[..], &STAFF_PERMISSIONS[..]].concat(),
+    ))
+    .await
+    .unwrap();
+
+    let mut counts = Vec::new();
+    for user_id in staff {
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"SELECT cardinality("permissions") AS "count" FROM "app_staff" WHERE "user_id" = $1"#,
+                [user_id.into()],
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        counts.push(row.try_get::<i32>("", "count").unwrap() as usize);
+    }
+    txn.rollback().await.unwrap();
+
+    assert_eq!(counts[0], AppStaffPermissions::get_all().len());
+    assert_eq!(counts[1], APPLICATION_PERMISSIONS.len() - 1);
+}
@@ -0,0 +1,275 @@
+use std::{fmt, io, str::FromStr};
+
+use api_server::{
+    query::user as user_query,
+    schema::user::{self as user_schema, StaffPermission},
+    usecase::{session as session_usecase, user as user_usecase},
+};
+use clap::Subcommand;
+use orm_util_lib::{LIMIT_DEFAULT, OFFSET_DEFAULT};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository, SoftDelete,
+};
+use sea_orm::{ColumnTrait, Condition, Iterable};
+use serde::Serialize;
+use time::{macros::format_description, Date};
+use util_lib::{
+    crypto::{Algorithm, ErrorAlgorithm},
+    settings::SETTINGS as UTIL_SETTINGS,
+    string::validate::{is_email, is_str_1_255},
+};
+use uuid::Uuid;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Creates a staff user with every staff permission and a verified email.
+    CreateSuperuser {
+        #[arg(long, value_parser = parse_name)]
+        name: String,
+        #[arg(long, value_parser = parse_email)]
+        email: String,
+        /// Birthday, as `YYYY-MM-DD`.
+        #[arg(long, value_parser = parse_date)]
+        birthday: Date,
+        /// Reads the password from the standard input, else a password is generated.
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Grants staff permissions to a staff user.
+    Grant {
+        /// Id or email of the user.
+        user: String,
+        /// Staff permissions, e.g. `CreateStaffUser`.
+        #[arg(required = true, value_parser = parse_permission)]
+        permissions: Vec<StaffPermission>,
+    },
+    /// Revokes staff permissions from a staff user.
+    Revoke {
+        /// Id or email of the user.
+        user: String,
+        /// Staff permissions, e.g. `CreateStaffUser`.
+        #[arg(required = true, value_parser = parse_permission)]
+        permissions: Vec<StaffPermission>,
+    },
+    /// Sets a new password and revokes every session of the user.
+    ResetPassword {
+        /// Id or email of the user.
+        user: String,
+        /// Reads the password from the standard input, else a password is generated.
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Lists the users as JSON.
+    ListUsers {
+        /// Lists only the staff users.
+        #[arg(long)]
+        staff: bool,
+        /// Part of the name of the users.
+        #[arg(long)]
+        name: Option<String>,
+        /// Part of the email of the users.
+        #[arg(long)]
+        email: Option<String>,
+        #[arg(long, default_value_t = OFFSET_DEFAULT)]
+        offset: u64,
+        #[arg(long, default_value_t = LIMIT_DEFAULT)]
+        limit: i64,
+    },
+    /// Revokes every session of the user.
+    RevokeSessions {
+        /// Id or email of the user.
+        user: String,
+    },
+}
+
+#[derive(Debug)]
+pub enum ErrorCommand {
+    UserNotFound(String),
+    UserNotStaff(String),
+    EmailAllreadyExist(String),
+    InvalidPassword,
+    Io(io::Error),
+    Hashing(ErrorAlgorithm),
+}
+
+impl fmt::Display for ErrorCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UserNotFound(v) => write!(f, "user {} doesn't exist", v),
+            Self::UserNotStaff(v) => write!(f, "user {} isn't a staff user", v),
+            Self::EmailAllreadyExist(v) => write!(f, "email {} allready exist", v),
+            Self::InvalidPassword => write!(f, "password must have 1 to 255 characters"),
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Hashing(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+pub async fn run(command: Command) -> Result<(), ErrorCommand> {
+    // Refuse to run before a password is hashed with an unsupported algorithm
+    Algorithm::from_settings(&UTIL_SETTINGS.hashing).map_err(ErrorCommand::Hashing)?;
+    match command {
+        Command::CreateSuperuser {
+            name,
+            email,
+            birthday,
+            password_stdin,
+        } => {
+            let password = get_password(password_stdin)?;
+            let new_user = user_schema::CreateUser {
+                name,
+                email: email.to_owned(),
+                is_staff: Some(true),
+                birthday,
+            };
+            match user_usecase::create_superuser(&new_user, &password).await {
+                Ok(v) => print_json(&v),
+                Err(e) => match e {
+                    user_usecase::ErrorCreate::EmailAllreadyExist => {
+                        Err(ErrorCommand::EmailAllreadyExist(email))
+                    }
+                },
+            }
+        }
+        Command::Grant { user, permissions } => {
+            let user_id = get_user_id(&user).await?;
+            let result = user_usecase::grant_staff_permissions(user_id, &permissions).await;
+            print_staff_permissions_result(result, user)
+        }
+        Command::Revoke { user, permissions } => {
+            let user_id = get_user_id(&user).await?;
+            let result = user_usecase::revoke_staff_permissions(user_id, &permissions).await;
+            print_staff_permissions_result(result, user)
+        }
+        Command::ResetPassword {
+            user,
+            password_stdin,
+        } => {
+            let user_id = get_user_id(&user).await?;
+            let password = get_password(password_stdin)?;
+            match user_usecase::set_password(user_id, &password).await {
+                Ok(v) => print_json(&v),
+                Err(e) => match e {
+                    user_usecase::ErrorSetPassword::UserNotFound => {
+                        Err(ErrorCommand::UserNotFound(user))
+                    }
+                },
+            }
+        }
+        Command::ListUsers {
+            staff,
+            name,
+            email,
+            offset,
+            limit,
+        } => {
+            let query_filter = user_query::User {
+                id: None,
+                name,
+                email,
+                created_start: None,
+                created_end: None,
+                is_staff: staff.then_some(true),
+                offset: Some(offset),
+                limit: Some(limit),
+            };
+            print_json(&user_usecase::get_all(&query_filter).await)
+        }
+        Command::RevokeSessions { user } => {
+            let user_id = get_user_id(&user).await?;
+            match session_usecase::revoke_all(user_id).await {
+                Ok(_) => Ok(()),
+                Err(e) => match e {
+                    session_usecase::ErrorRevokeAll::UserNotFound => {
+                        Err(ErrorCommand::UserNotFound(user))
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Returns the id of the user of the id or the email (in any case), deleted users included.
+async fn get_user_id(user: &str) -> Result<Uuid, ErrorCommand> {
+    let filter = match Uuid::parse_str(user) {
+        Ok(v) => Condition::all().add(user_entity::Column::Id.eq(v)),
+        Err(_) => UserRep::get_email_filter(user),
+    };
+    let rep = UserRep::new().await.with_deleted();
+    match rep.get_one(Some(filter)).await.unwrap() {
+        Some(v) => Ok(v.id),
+        None => Err(ErrorCommand::UserNotFound(user.to_string())),
+    }
+}
+
+/// Returns the password read from the standard input, or a generated one printed to the
+/// standard error (the password is never an argument, it would stay in the shell history).
+fn get_password(password_stdin: bool) -> Result<String, ErrorCommand> {
+    if !password_stdin {
+        let password = user_entity::Model::gen_password();
+        eprintln!("Generated password: {}", password);
+        return Ok(password);
+    }
+    let mut password = String::new();
+    io::stdin()
+        .read_line(&mut password)
+        .map_err(ErrorCommand::Io)?;
+    let password = password.trim_end_matches(['\r', '\n']).to_string();
+    match is_str_1_255(&password) {
+        true => Ok(password),
+        false => Err(ErrorCommand::InvalidPassword),
+    }
+}
+
+fn print_staff_permissions_result(
+    result: Result<user_schema::User, user_usecase::ErrorUpdateStaffPermissions>,
+    user: String,
+) -> Result<(), ErrorCommand> {
+    match result {
+        Ok(v) => print_json(&v),
+        Err(e) => match e {
+            user_usecase::ErrorUpdateStaffPermissions::UserNotFound => {
+                Err(ErrorCommand::UserNotFound(user))
+            }
+            user_usecase::ErrorUpdateStaffPermissions::UserNotStaff => {
+                Err(ErrorCommand::UserNotStaff(user))
+            }
+        },
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), ErrorCommand> {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+    Ok(())
+}
+
+fn parse_name(v: &str) -> Result<String, String> {
+    match is_str_1_255(v) {
+        true => Ok(v.to_string()),
+        false => Err("name must have 1 to 255 characters".to_string()),
+    }
+}
+
+fn parse_email(v: &str) -> Result<String, String> {
+    match is_email(v) {
+        true => Ok(v.to_string()),
+        false => Err("invalid email".to_string()),
+    }
+}
+
+fn parse_date(v: &str) -> Result<Date, String> {
+    Date::parse(v, format_description!("[year]-[month]-[day]")).map_err(|e| e.to_string())
+}
+
+fn parse_permission(v: &str) -> Result<StaffPermission, String> {
+    StaffPermission::from_str(v).map_err(|_| {
+        let permissions: Vec<String> = user_entity::UserStaffPermission::iter()
+            .map(|v| v.to_string())
+            .collect();
+        format!(
+            "unknown permission, expected one of: {}",
+            permissions.join(", ")
+        )
+    })
+}
@@ -0,0 +1,13 @@
+pub mod command;
+
+use clap::Parser;
+
+/// Administration of the users of a deployment.
+///
+/// The settings are the ones of the API server (`DATABASE_URL`, `REDIS_URL`, ...).
+#[derive(Parser)]
+#[command(name = "rbca-admin", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: command::Command,
+}
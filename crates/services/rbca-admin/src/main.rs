@@ -0,0 +1,16 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use rbca_admin::{command, Cli};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match command::run(cli.command).await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use api_server::{
+    schema::user::{self as user_schema, StaffPermission},
+    usecase::user as user_usecase,
+};
+use clap::Parser;
+use orm_util_lib::{LIMIT_DEFAULT, OFFSET_DEFAULT};
+use rbca_admin::{
+    command::{self, Command},
+    Cli,
+};
+use repository_db_lib::{
+    user::{user_entity, User as UserRep},
+    Repository,
+};
+use time::macros::date;
+use uuid::Uuid;
+
+fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+    Cli::try_parse_from([&["rbca-admin"], args].concat()).map(|v| v.command)
+}
+
+#[test]
+fn create_superuser_arguments_are_validated() {
+    let command = parse(&[
+        "create-superuser",
+        "--name",
+        "Admin",
+        "--email",
+        "admin@example.com",
+        "--birthday",
+        "2000-01-31",
+    ])
+    .unwrap();
+    assert!(matches!(
+        command,
+        Command::CreateSuperuser { name, email, birthday, password_stdin: false }
+            if name == "Admin" && email == "admin@example.com" && birthday == date!(2000 - 01 - 31)
+    ));
+
+    for (arg, value) in [
+        ("--name", ""),
+        ("--email", "admin"),
+        ("--birthday", "31/01/2000"),
+    ] {
+        let mut args = vec![
+            "create-superuser",
+            "--name",
+            "Admin",
+            "--email",
+            "admin@example.com",
+            "--birthday",
+            "2000-01-31",
+        ];
+        let i = args.iter().position(|v| *v == arg).unwrap();
+        args[i + 1] = value;
+        assert!(parse(&args).is_err(), "{} {} must be refused", arg, value);
+    }
+}
+
+#[test]
+fn staff_permissions_are_parsed() {
+    let command = parse(&[
+        "grant",
+        "admin@example.com",
+        "CreateStaffUser",
+        "ClearLoginLockouts",
+    ])
+    .unwrap();
+    assert!(matches!(
+        command,
+        Command::Grant { user, permissions }
+            if user == "admin@example.com"
+                && permissions
+                    == vec![StaffPermission::CreateStaffUser, StaffPermission::ClearLoginLockouts]
+    ));
+
+    let error = parse(&["revoke", "admin@example.com", "Admin"])
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("expected one of: CreateApplication"));
+    // At least one permission
+    assert!(parse(&["revoke", "admin@example.com"]).is_err());
+}
+
+#[test]
+fn list_users_has_default_pagination() {
+    assert!(matches!(
+        parse(&["list-users"]).unwrap(),
+        Command::ListUsers { staff: false, name: None, email: None, offset, limit }
+            if offset == OFFSET_DEFAULT && limit == LIMIT_DEFAULT
+    ));
+    assert!(matches!(
+        parse(&["list-users", "--staff", "--email", "example.com"]).unwrap(),
+        Command::ListUsers { staff: true, email: Some(email), .. } if email == "example.com"
+    ));
+    assert!(matches!(
+        parse(&["revoke-sessions", "admin@example.com"]).unwrap(),
+        Command::RevokeSessions { user } if user == "admin@example.com"
+    ));
+    assert!(parse(&["revoke-sessions"]).is_err());
+}
+
+async fn get_user_model(email: &str) -> user_entity::Model {
+    UserRep::new()
+        .await
+        .get_one(Some(UserRep::get_email_filter(email)))
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+/// One test: the connections of the repositories are bound to the runtime of the first test.
+#[tokio::test]
+#[ignore = "needs PostgreSQL and Redis (docker-compose up -d)"]
+async fn staff_permissions_are_updated() {
+    migration::init().await;
+    let email = format!("{}@example.com", Uuid::new_v4());
+    assert!(command::run(Command::CreateSuperuser {
+        name: "Admin".to_string(),
+        email: email.to_owned(),
+        birthday: date!(2000 - 01 - 31),
+        password_stdin: false,
+    })
+    .await
+    .is_ok());
+    let user_model = get_user_model(&email).await;
+    assert!(user_model.is_email_verified());
+    assert!(user_model
+        .staff_permissions
+        .contains(&user_entity::UserStaffPermission::ClearLoginLockouts));
+
+    // By id or by email in any case
+    assert!(command::run(Command::Revoke {
+        user: user_model.id.to_string(),
+        permissions: vec![StaffPermission::ClearLoginLockouts],
+    })
+    .await
+    .is_ok());
+    assert!(!get_user_model(&email)
+        .await
+        .staff_permissions
+        .contains(&user_entity::UserStaffPermission::ClearLoginLockouts));
+    assert!(command::run(Command::Grant {
+        user: email.to_uppercase(),
+        permissions: vec![StaffPermission::ClearLoginLockouts],
+    })
+    .await
+    .is_ok());
+    assert!(get_user_model(&email)
+        .await
+        .staff_permissions
+        .contains(&user_entity::UserStaffPermission::ClearLoginLockouts));
+
+    // Only staff users have permissions
+    let user = match user_usecase::create(
+        &user_schema::CreateUser {
+            name: "User".to_string(),
+            email: format!("{}@example.com", Uuid::new_v4()),
+            is_staff: Some(false),
+            birthday: date!(2000 - 01 - 31),
+        },
+        Some("password"),
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => panic!("user must be created"),
+    };
+    assert!(matches!(
+        command::run(Command::Grant {
+            user: user.id.to_string(),
+            permissions: vec![StaffPermission::ClearLoginLockouts],
+        })
+        .await,
+        Err(command::ErrorCommand::UserNotStaff(_))
+    ));
+    assert!(matches!(
+        command::run(Command::RevokeSessions {
+            user: Uuid::new_v4().to_string()
+        })
+        .await,
+        Err(command::ErrorCommand::UserNotFound(_))
+    ));
+}